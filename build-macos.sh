@@ -21,7 +21,7 @@ sleep 2
 
 # Clean target directory
 echo "Cleaning target directory..."
-rm -rf src-tauri/target
+rm -rf target
 sleep 2
 
 # Build the project
@@ -38,11 +38,11 @@ for attempt in 1 2 3; do
         echo "Build successful!"
         
         # Check if the bundle was created
-        if [ -d "src-tauri/target/release/bundle/macos/vibe-process-bar.app" ]; then
+        if [ -d "target/release/bundle/macos/vibe-process-bar.app" ]; then
             echo ""
             echo "=== Build Complete ==="
-            echo "App bundle created at: src-tauri/target/release/bundle/macos/vibe-process-bar.app"
-            echo "DMG created at: src-tauri/target/release/bundle/dmg/*.dmg"
+            echo "App bundle created at: target/release/bundle/macos/vibe-process-bar.app"
+            echo "DMG created at: target/release/bundle/dmg/*.dmg"
             echo ""
             exit 0
         fi
@@ -54,7 +54,7 @@ for attempt in 1 2 3; do
             echo "Detected macOS resource fork issue. Cleaning and retrying..."
             
             # Clean target and resource files
-            rm -rf src-tauri/target
+            rm -rf target
             find . -name "._*" -type f -delete 2>/dev/null
             rm -rf ~/.cargo/registry/src/*/tauri-*/scripts/bundle.global.js 2>/dev/null
             
@@ -68,4 +68,4 @@ for attempt in 1 2 3; do
 done
 
 echo "Build failed after 3 attempts. Please try manually cleaning the target directory."
-echo "You can also try: rm -rf src-tauri/target && cd src-tauri && cargo build"
+echo "You can also try: rm -rf target && cd src-tauri && cargo build"
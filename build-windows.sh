@@ -31,7 +31,7 @@ cargo build --release
 echo "✅ Build complete!"
 echo ""
 echo "📦 Windows executable location:"
-echo "   src-tauri/target/release/vibe-process-bar.exe"
+echo "   target/release/vibe-process-bar.exe"
 echo ""
 echo "To create installer, run:"
 echo "   npm run tauri build"
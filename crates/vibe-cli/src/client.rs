@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+use vibe_core::Task;
+
+/// 正在运行的 Vibe Process Bar（桌面应用或 vibe-processd）的 HTTP 客户端
+pub struct Client {
+    base_url: String,
+    agent: ureq::Agent,
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    tasks: Vec<Task>,
+}
+
+#[derive(Deserialize)]
+struct ApiResponse {
+    #[serde(default)]
+    error: Option<String>,
+}
+
+impl Client {
+    pub fn new(base_url: &str) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout_connect(Duration::from_secs(2))
+            .timeout(Duration::from_secs(5))
+            .build();
+        Self { base_url: base_url.trim_end_matches('/').to_string(), agent }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// 合并后的任务列表，与悬浮窗显示的一致
+    pub fn tasks(&self) -> Result<Vec<Task>, String> {
+        let response = self
+            .agent
+            .get(&format!("{}/api/status", self.base_url))
+            .call()
+            .map_err(describe_error)?;
+        let status: StatusResponse = response.into_json().map_err(|e| e.to_string())?;
+        Ok(status.tasks)
+    }
+
+    /// 服务端渲染的状态行；`output` 为 text 或 waybar
+    pub fn status_line(&self, format: Option<&str>, output: &str) -> Result<String, String> {
+        let mut request = self
+            .agent
+            .get(&format!("{}/api/status/line", self.base_url))
+            .query("output", output);
+        if let Some(format) = format {
+            request = request.query("format", format);
+        }
+        let response = request.call().map_err(describe_error)?;
+        response.into_string().map_err(|e| e.to_string())
+    }
+
+    pub fn reset_to_armed(&self, task_id: &str) -> Result<(), String> {
+        self.post_task("/api/task/reset_armed", task_id)
+    }
+
+    pub fn delete(&self, task_id: &str) -> Result<(), String> {
+        self.post_task("/api/task/delete", task_id)
+    }
+
+    pub fn activate(&self, task_id: &str) -> Result<(), String> {
+        self.post_task("/api/task/activate", task_id)
+    }
+
+    fn post_task(&self, path: &str, task_id: &str) -> Result<(), String> {
+        self.agent
+            .post(&format!("{}{}", self.base_url, path))
+            .send_json(serde_json::json!({ "task_id": task_id }))
+            .map(|_| ())
+            .map_err(describe_error)
+    }
+}
+
+/// 优先使用 server 返回的 error 字段
+fn describe_error(error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => response
+            .into_json::<ApiResponse>()
+            .ok()
+            .and_then(|r| r.error)
+            .unwrap_or_else(|| format!("HTTP {}", code)),
+        ureq::Error::Transport(transport) => transport.to_string(),
+    }
+}
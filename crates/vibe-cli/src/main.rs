@@ -0,0 +1,63 @@
+mod client;
+mod status;
+mod tui;
+
+use clap::{Parser, Subcommand};
+use std::process::ExitCode;
+use std::time::Duration;
+
+use client::Client;
+use status::StatusOptions;
+
+/// Command line client for a running Vibe Process Bar (desktop app or vibe-processd)
+#[derive(Parser, Debug)]
+#[command(name = "vibe", version, about)]
+struct Cli {
+    /// Base URL of the Vibe Process Bar HTTP server
+    #[arg(long, global = true, env = "VIBE_URL", default_value = "http://127.0.0.1:31415")]
+    url: String,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Live task dashboard in the terminal
+    Tui,
+    /// One-line status for tmux, shell prompts and waybar/polybar
+    Status {
+        /// Template, e.g. "[{running}▶ ][{waiting}? ]{stage}" [default: server default]
+        #[arg(long, short)]
+        format: Option<String>,
+        /// Print waybar JSON (text, tooltip, class, percentage)
+        #[arg(long)]
+        waybar: bool,
+        /// Keep running and print a new line whenever the status changes
+        #[arg(long, short)]
+        watch: bool,
+        /// Poll interval in milliseconds for --watch
+        #[arg(long, default_value_t = 1000)]
+        interval: u64,
+    },
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let client = Client::new(&cli.url);
+
+    let result = match cli.command {
+        Command::Tui => tui::run(client),
+        Command::Status { format, waybar, watch, interval } => {
+            let options = StatusOptions { format, waybar, watch, interval: Duration::from_millis(interval.max(100)) };
+            status::run(&client, &options)
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("vibe: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
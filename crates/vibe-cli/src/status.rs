@@ -0,0 +1,55 @@
+use std::io::Write;
+use std::time::Duration;
+
+use crate::client::Client;
+
+pub struct StatusOptions {
+    pub format: Option<String>,
+    pub waybar: bool,
+    pub watch: bool,
+    pub interval: Duration,
+}
+
+/// 输出一行状态；`watch` 时持续轮询，内容变化才输出新的一行
+pub fn run(client: &Client, options: &StatusOptions) -> Result<(), String> {
+    let output = if options.waybar { "waybar" } else { "text" };
+    let fetch = || client.status_line(options.format.as_deref(), output);
+
+    if !options.watch {
+        let line = fetch()?;
+        return print_line(&line).map_err(|e| e.to_string());
+    }
+
+    let mut last: Option<String> = None;
+    loop {
+        // server 不可达时输出空状态，恢复后继续输出
+        let line = fetch().unwrap_or_else(|e| offline_line(options.waybar, &e));
+        if last.as_ref() != Some(&line) {
+            // 读取方（状态栏）退出后结束
+            if print_line(&line).is_err() {
+                return Ok(());
+            }
+            last = Some(line);
+        }
+        std::thread::sleep(options.interval);
+    }
+}
+
+fn print_line(line: &str) -> std::io::Result<()> {
+    let mut stdout = std::io::stdout().lock();
+    writeln!(stdout, "{}", line.trim_end())?;
+    stdout.flush()
+}
+
+fn offline_line(waybar: bool, error: &str) -> String {
+    if !waybar {
+        return String::new();
+    }
+    serde_json::json!({
+        "text": "",
+        "tooltip": format!("Vibe Process Bar is not reachable: {}", error),
+        "class": "offline",
+        "percentage": 0,
+    })
+    .to_string()
+}
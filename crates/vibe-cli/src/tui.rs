@@ -0,0 +1,283 @@
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Cell, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use vibe_core::{status_line, Task};
+
+use crate::client::Client;
+
+/// 与悬浮窗的同步间隔一致
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+const PROGRESS_BAR_WIDTH: usize = 10;
+
+pub fn run(client: Client) -> Result<(), String> {
+    let mut terminal = ratatui::init();
+    let result = Dashboard::new(client).run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+// ============================================================================
+// 状态
+// ============================================================================
+
+struct Dashboard {
+    client: Client,
+    tasks: Vec<Task>,
+    table: TableState,
+    /// 连接失败时显示错误，成功刷新后清除
+    connection_error: Option<String>,
+    /// 最近一次操作的结果
+    message: Option<(String, bool)>,
+    /// 按两次 d 才删除
+    pending_delete: Option<String>,
+    last_refresh: Option<Instant>,
+    quit: bool,
+}
+
+impl Dashboard {
+    fn new(client: Client) -> Self {
+        Self {
+            client,
+            tasks: Vec::new(),
+            table: TableState::default(),
+            connection_error: None,
+            message: None,
+            pending_delete: None,
+            last_refresh: None,
+            quit: false,
+        }
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> Result<(), String> {
+        while !self.quit {
+            if self.last_refresh.is_none_or(|t| t.elapsed() >= REFRESH_INTERVAL) {
+                self.refresh();
+            }
+            terminal.draw(|frame| self.draw(frame)).map_err(|e| e.to_string())?;
+
+            // 等待按键，超时后继续刷新（运行时间每秒更新）
+            if event::poll(Duration::from_millis(250)).map_err(|e| e.to_string())? {
+                if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+                    if key.kind == KeyEventKind::Press {
+                        self.handle_key(key);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn refresh(&mut self) {
+        self.last_refresh = Some(Instant::now());
+        match self.client.tasks() {
+            Ok(tasks) => {
+                // 刷新后保持选中同一个任务
+                let selected_id = self.selected().map(|t| t.id.clone());
+                self.tasks = tasks;
+                self.connection_error = None;
+                let index = selected_id
+                    .and_then(|id| self.tasks.iter().position(|t| t.id == id))
+                    .or_else(|| (!self.tasks.is_empty()).then_some(0));
+                self.table.select(index);
+            }
+            Err(e) => self.connection_error = Some(e),
+        }
+    }
+
+    fn selected(&self) -> Option<&Task> {
+        self.table.selected().and_then(|i| self.tasks.get(i))
+    }
+
+    // ========================================================================
+    // 按键
+    // ========================================================================
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        let confirm_delete = self.pending_delete.take();
+
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.table.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.table.select_previous(),
+            KeyCode::Home | KeyCode::Char('g') => self.table.select_first(),
+            KeyCode::End | KeyCode::Char('G') => self.table.select_last(),
+            KeyCode::Char('r') => {
+                self.message = None;
+                self.refresh();
+            }
+            KeyCode::Enter | KeyCode::Char('o') => {
+                self.act_on_selected("Activated", |client, id| client.activate(id));
+            }
+            KeyCode::Char('a') => {
+                self.act_on_selected("Reset to armed", |client, id| client.reset_to_armed(id));
+            }
+            KeyCode::Char('d') | KeyCode::Delete => match self.selected().map(|t| t.id.clone()) {
+                Some(id) if confirm_delete.as_ref() == Some(&id) => {
+                    self.act_on_selected("Deleted", |client, id| client.delete(id));
+                }
+                Some(id) => {
+                    self.message = Some((format!("Press d again to delete {}", id), false));
+                    self.pending_delete = Some(id);
+                }
+                None => {}
+            },
+            _ => {}
+        }
+
+        if let Some(i) = self.table.selected() {
+            if i >= self.tasks.len() {
+                self.table.select(self.tasks.len().checked_sub(1));
+            }
+        }
+    }
+
+    fn act_on_selected(&mut self, done: &str, action: impl FnOnce(&Client, &str) -> Result<(), String>) {
+        let Some(task_id) = self.selected().map(|t| t.id.clone()) else { return };
+        self.message = Some(match action(&self.client, &task_id) {
+            Ok(()) => (format!("{}: {}", done, task_id), false),
+            Err(e) => (format!("{} failed: {}", task_id, e), true),
+        });
+        self.refresh();
+    }
+
+    // ========================================================================
+    // 绘制
+    // ========================================================================
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+
+        let running = self.tasks.iter().filter(|t| t.status == "running").count();
+        frame.render_widget(
+            Line::from(vec![
+                Span::from(" Vibe Process Bar ").bold().reversed(),
+                Span::from(format!("  {}  ", self.client.base_url())).dark_gray(),
+                Span::from(format!("{} tasks, {} running", self.tasks.len(), running)),
+            ]),
+            header,
+        );
+
+        let now = now_millis();
+        let rows = self.tasks.iter().map(|task| task_row(task, now));
+        let widths = [
+            Constraint::Length(18),
+            Constraint::Length(11),
+            Constraint::Length(PROGRESS_BAR_WIDTH as u16 + 5),
+            Constraint::Fill(3),
+            Constraint::Length(12),
+            Constraint::Fill(2),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(["Status", "Elapsed", "Progress", "Stage", "IDE", "Project"])
+                    .style(Style::new().add_modifier(Modifier::BOLD | Modifier::UNDERLINED)),
+            )
+            .block(Block::bordered())
+            .row_highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("▶ ");
+        frame.render_stateful_widget(table, body, &mut self.table);
+
+        if self.tasks.is_empty() && self.connection_error.is_none() {
+            let inner = body.inner(ratatui::layout::Margin::new(2, 2));
+            frame.render_widget(Line::from("No tasks").dark_gray(), inner);
+        }
+
+        let status_line = if let Some(error) = &self.connection_error {
+            Line::from(format!(" Cannot reach {}: {}", self.client.base_url(), error)).red()
+        } else if let Some((message, is_error)) = &self.message {
+            let line = Line::from(format!(" {}", message));
+            if *is_error { line.red() } else { line.green() }
+        } else {
+            Line::from(" ↑↓ select  Enter activate IDE  a reset to armed  d delete  r refresh  q quit").dark_gray()
+        };
+        frame.render_widget(status_line, footer);
+    }
+}
+
+fn task_row(task: &Task, now: u64) -> Row<'static> {
+    let color = if task.stale { Color::DarkGray } else { status_color(&task.status) };
+    let mut status = format!("{} {}", status_icon(task), task.status);
+    if task.stale {
+        status.push_str(" (off)");
+    } else if task.overtime {
+        status.push_str(" (late)");
+    } else if task.possibly_stalled {
+        status.push_str(" (stall)");
+    }
+
+    Row::new([
+        Cell::from(status).style(Style::new().fg(color)),
+        Cell::from(status_line::elapsed(task, now).map(status_line::format_duration).unwrap_or_default()),
+        Cell::from(progress_str(task, now)).style(Style::new().fg(color)),
+        Cell::from(stage_str(task)),
+        Cell::from(task.ide.clone()),
+        Cell::from(project_str(task)).dark_gray(),
+    ])
+}
+
+/// 与悬浮窗一致：告警优先，其次是聚焦窗口
+fn status_icon(task: &Task) -> &'static str {
+    if task.is_focused && !(task.overtime || task.possibly_stalled) {
+        return "◆";
+    }
+    status_line::icon(task)
+}
+
+fn status_color(status: &str) -> Color {
+    match status {
+        "running" => Color::Yellow,
+        "waiting_input" => Color::Magenta,
+        "completed" => Color::Green,
+        "error" => Color::Red,
+        "armed" => Color::Cyan,
+        _ => Color::DarkGray,
+    }
+}
+
+fn progress_str(task: &Task, now: u64) -> String {
+    let has_estimate = task.estimate.is_some() || task.estimated_duration.is_some_and(|d| d > 0);
+    if !(task.status == "completed" || task.status == "running" && has_estimate) {
+        return String::new();
+    }
+    let progress = task.progress(now) as usize;
+    let filled = progress * PROGRESS_BAR_WIDTH / 100;
+    format!(
+        "{}{} {:>3}%",
+        "█".repeat(filled),
+        "░".repeat(PROGRESS_BAR_WIDTH - filled),
+        progress
+    )
+}
+
+/// 远程实例转发来的任务附上主机名
+fn project_str(task: &Task) -> String {
+    match &task.host {
+        Some(host) => format!("{}@{}", status_line::project(task), host),
+        None => status_line::project(task),
+    }
+}
+
+/// 没有阶段描述时显示当前文件
+fn stage_str(task: &Task) -> String {
+    status_line::stage(task)
+        .or_else(|| task.active_file.as_deref().map(|f| status_line::last_component(f).to_string()))
+        .unwrap_or_default()
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,804 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::{debug, info};
+
+use crate::clock::{Clock, SystemClock};
+use crate::events::{EngineEvent, EventSink, NullSink, TimelineEvent, TimelineEventKind};
+use crate::project_identity::{self, PathMatch};
+use crate::task::{
+    can_update_source, sort_tasks_by_priority, CompleteTaskRequest, CompletionReport, MatchCandidate, ReportRequest,
+    Task, UpdateStateByPathRequest, UpdateStateRequest, UpdateTokensRequest, END_STATUSES, VALID_SOURCES, VALID_STATUSES,
+};
+
+/// 插件心跳超过该时长的任务在合并时被清理
+pub const HEARTBEAT_TIMEOUT_MS: u64 = 5000;
+/// 两次插件心跳间隔超过该值时记录一次心跳中断
+pub const HEARTBEAT_GAP_MS: u64 = 2500;
+
+pub const PLUGIN_STATUS_BLOCKED: &str = "plugin_status_blocked";
+pub const LOWER_PRIORITY_SOURCE: &str = "lower_priority_source";
+
+/// 一次更新的结果：已应用，或被忽略（附原因）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpdateOutcome {
+    Applied,
+    Ignored(&'static str),
+}
+
+#[derive(Debug, Clone)]
+pub enum EngineError {
+    /// 请求参数无效
+    BadRequest(String),
+    NotFound(String),
+    /// 按路径匹配到多个任务
+    Ambiguous { message: String, candidates: Vec<MatchCandidate> },
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::BadRequest(message) | EngineError::NotFound(message) => f.write_str(message),
+            EngineError::Ambiguous { message, .. } => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+fn validate_source(source: &str) -> Result<(), EngineError> {
+    if VALID_SOURCES.contains(&source) {
+        Ok(())
+    } else {
+        Err(EngineError::BadRequest(format!("Invalid source '{}'. Valid: {:?}", source, VALID_SOURCES)))
+    }
+}
+
+/// 插件状态被屏蔽时插件任务的来源不构成优先级，fs 等兜底来源可以代为更新
+fn accepts_source(current_source: &str, new_source: &str, block_plugin_status: bool) -> bool {
+    (current_source == "plugin" && block_plugin_status) || can_update_source(current_source, new_source)
+}
+
+fn validate_status(status: Option<&str>) -> Result<(), EngineError> {
+    match status {
+        Some(status) if !VALID_STATUSES.contains(&status) => Err(EngineError::BadRequest(format!(
+            "Invalid status '{}'. Valid: {:?}",
+            status, VALID_STATUSES
+        ))),
+        _ => Ok(()),
+    }
+}
+
+// ============================================================================
+// Changes
+// ============================================================================
+
+/// 一次操作中产生的事件，释放任务锁之后再投递
+struct Changes {
+    now: u64,
+    events: Vec<EngineEvent>,
+}
+
+impl Changes {
+    fn timeline(&mut self, task_id: &str, kind: TimelineEventKind) {
+        self.events.push(EngineEvent::Timeline {
+            task_id: task_id.to_string(),
+            event: TimelineEvent { at: self.now, kind },
+        });
+    }
+
+    fn ignored(&mut self, reason: &str) {
+        self.events.push(EngineEvent::UpdateIgnored { reason: reason.to_string() });
+    }
+
+    /// 已存在任务上被忽略的更新，同时记入时间线
+    fn ignored_update(&mut self, task_id: &str, source: &str, reason: &str, status: Option<&str>) {
+        self.ignored(reason);
+        self.timeline(task_id, TimelineEventKind::Ignored {
+            source: source.to_string(),
+            reason: reason.to_string(),
+            status: status.map(str::to_string),
+        });
+    }
+
+    fn set_source(&mut self, task: &mut Task, source: &str) {
+        if task.source != source {
+            self.timeline(&task.id, TimelineEventKind::Source {
+                from: task.source.clone(),
+                to: source.to_string(),
+            });
+            task.source = source.to_string();
+        }
+    }
+
+    fn set_stage(&mut self, task: &mut Task, stage: &str) {
+        if task.current_stage.as_deref() != Some(stage) {
+            task.current_stage = Some(stage.to_string());
+            self.timeline(&task.id, TimelineEventKind::Stage { stage: task.current_stage.clone() });
+        }
+    }
+
+    /// 状态切换：维护 start_time / end_time / 预估 / 阶段，并在开始和结束时发出 RunStarted / RunEnded
+    ///
+    /// 调用方负责校验 status 和来源优先级。
+    fn apply_status_transition(&mut self, task: &mut Task, status: &str) {
+        let old_status = std::mem::replace(&mut task.status, status.to_string());
+        let now = self.now;
+        if old_status != status {
+            self.timeline(&task.id, TimelineEventKind::Status {
+                from: old_status.clone(),
+                to: status.to_string(),
+            });
+        }
+
+        match status {
+            "running" => {
+                // Reset start_time when transitioning from completed/error/cancelled to running
+                if END_STATUSES.contains(&old_status.as_str()) {
+                    task.start_time = now;
+                    task.end_time = None;
+                    task.estimated_duration = None;
+                    task.change_summary = None;
+                    task.completion = None;
+                    task.tokens = 0;
+                    // 重置 current_stage 为文件名
+                    task.current_stage = task.active_file.clone();
+                    info!(task_id = %task.id, "Task restarted from {}", old_status);
+                    self.events.push(EngineEvent::RunStarted { task: task.clone() });
+                } else if task.start_time == 0 {
+                    task.start_time = now;
+                    task.tokens = 0;
+                    info!(task_id = %task.id, "Task started");
+                    self.events.push(EngineEvent::RunStarted { task: task.clone() });
+                }
+            }
+            "completed" | "error" | "cancelled" => {
+                task.end_time = Some(now);
+                if status == "completed" {
+                    // 设置特殊标记，前端会根据语言设置显示对应文案
+                    task.current_stage = Some("__completed__".to_string());
+                }
+                info!(task_id = %task.id, new_status = %status, "Task ended");
+                if old_status != status && task.start_time > 0 {
+                    self.events.push(EngineEvent::RunEnded { task: task.clone() });
+                }
+            }
+            "armed" => {
+                // 重置为 armed 时清空预估时间和阶段描述
+                task.estimated_duration = None;
+                task.current_stage = None;
+                task.start_time = 0;
+                task.end_time = None;
+                task.change_summary = None;
+                task.completion = None;
+                task.tokens = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 新注册的 armed 任务
+fn new_armed_task(req: ReportRequest, source: &str, now: u64) -> Task {
+    Task {
+        id: req.task_id,
+        name: req.name,
+        status: "armed".to_string(),
+        is_focused: req.is_focused,
+        ide: req.ide,
+        window_title: req.window_title,
+        start_time: 0,
+        end_time: None,
+        project_path: req.project_path,
+        active_file: req.active_file,
+        source: source.to_string(),
+        last_heartbeat: now,
+        estimated_duration: None,
+        current_stage: None,
+        tokens: 0,
+        last_update: now,
+        overtime: false,
+        possibly_stalled: false,
+        git: None,
+        change_summary: None,
+        completion: None,
+        estimate: None,
+        host: None,
+        stale: false,
+    }
+}
+
+// ============================================================================
+// TaskEngine
+// ============================================================================
+
+/// 任务引擎：持有任务列表，负责来源优先级、状态切换、合并与清理
+///
+/// 不依赖任何全局状态，时间由注入的 [`Clock`] 提供，副作用通过 [`EventSink`] 交给宿主处理。
+pub struct TaskEngine {
+    tasks: Mutex<Vec<Task>>,
+    block_plugin_status: AtomicBool,
+    /// 由系统前台窗口决定 is_focused，插件上报的 is_focused 不再生效
+    os_focus: AtomicBool,
+    clock: Arc<dyn Clock>,
+    sink: Arc<dyn EventSink>,
+}
+
+impl Default for TaskEngine {
+    fn default() -> Self {
+        TaskEngine::new(Arc::new(SystemClock), Arc::new(NullSink))
+    }
+}
+
+impl TaskEngine {
+    pub fn new(clock: Arc<dyn Clock>, sink: Arc<dyn EventSink>) -> Self {
+        TaskEngine {
+            tasks: Mutex::new(Vec::new()),
+            block_plugin_status: AtomicBool::new(true),
+            os_focus: AtomicBool::new(false),
+            clock,
+            sink,
+        }
+    }
+
+    pub fn now(&self) -> u64 {
+        self.clock.now_millis()
+    }
+
+    /// 在任务锁内执行修改，锁释放后投递产生的事件
+    fn commit<R>(&self, f: impl FnOnce(&mut Vec<Task>, &mut Changes) -> R) -> R {
+        let mut changes = Changes { now: self.now(), events: Vec::new() };
+        let result = {
+            let mut tasks = self.tasks.lock().unwrap();
+            f(&mut tasks, &mut changes)
+        };
+        for event in changes.events {
+            self.sink.emit(event);
+        }
+        result
+    }
+
+    /// 开启后忽略来自插件的状态更新（插件上报仍用于注册任务和心跳）
+    pub fn set_block_plugin_status(&self, block: bool) {
+        self.block_plugin_status.store(block, Ordering::SeqCst);
+        info!("Block plugin status set to: {}", block);
+    }
+
+    pub fn block_plugin_status(&self) -> bool {
+        self.block_plugin_status.load(Ordering::SeqCst)
+    }
+
+    /// 该来源的 update_state 是否会作用于任务（不会因优先级被忽略）
+    pub fn accepts_update(&self, task: &Task, source: &str) -> bool {
+        accepts_source(&task.source, source, self.block_plugin_status())
+    }
+
+    /// 焦点跟踪开始/停止提供前台窗口时调用；开启期间 is_focused 只由 set_focused 修改
+    pub fn set_os_focus(&self, enabled: bool) {
+        if self.os_focus.swap(enabled, Ordering::SeqCst) != enabled {
+            info!("OS focus tracking set to: {}", enabled);
+        }
+    }
+
+    pub fn os_focus(&self) -> bool {
+        self.os_focus.load(Ordering::SeqCst)
+    }
+
+    // ------------------------------------------------------------------------
+    // 读取
+    // ------------------------------------------------------------------------
+
+    /// 清理心跳超时的任务，返回按来源优先级排序后的任务列表
+    pub fn merged_tasks(&self) -> Vec<Task> {
+        self.commit(|tasks, changes| {
+            let now = changes.now;
+            let mut removed = Vec::new();
+            tasks.retain(|t| {
+                let age = if t.last_heartbeat > 0 {
+                    now.saturating_sub(t.last_heartbeat)
+                } else {
+                    0
+                };
+                let keep = age < HEARTBEAT_TIMEOUT_MS;
+                if !keep {
+                    removed.push(t.id.clone());
+                }
+                keep
+            });
+            if !removed.is_empty() {
+                info!("Cleaned up {} stale tasks", removed.len());
+                changes.events.push(EngineEvent::TasksRemoved { task_ids: removed });
+            }
+
+            let mut merged = tasks.clone();
+            sort_tasks_by_priority(&mut merged);
+            merged
+        })
+    }
+
+    /// 当前任务列表（不清理、不排序）
+    pub fn tasks(&self) -> Vec<Task> {
+        self.tasks.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, task_id: &str) -> Option<Task> {
+        self.tasks.lock().unwrap().iter().find(|t| t.id == task_id).cloned()
+    }
+
+    /// 按 project_path 查找任务（规则见 project_identity::find_project）
+    pub fn find_task_id_by_path(&self, project_path: &str, ide: Option<&str>) -> Result<String, EngineError> {
+        // 路径解析会访问文件系统，在锁外完成
+        let candidates: Vec<(MatchCandidate, String)> = self
+            .tasks
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|t| ide.is_none_or(|ide| t.ide == ide))
+            .filter_map(|t| {
+                let path = t.project_path.clone()?;
+                let candidate = MatchCandidate {
+                    task_id: t.id.clone(),
+                    ide: t.ide.clone(),
+                    project_path: Some(path.clone()),
+                };
+                Some((candidate, path))
+            })
+            .collect();
+
+        match project_identity::find_project(project_path, &candidates) {
+            PathMatch::Found(candidate) => Ok(candidate.task_id),
+            PathMatch::Ambiguous(matched) => {
+                info!(project_path = %project_path, count = matched.len(), "Ambiguous project_path match");
+                Err(EngineError::Ambiguous {
+                    message: "Multiple tasks match project_path; pass ide to disambiguate".to_string(),
+                    candidates: matched,
+                })
+            }
+            PathMatch::NotFound => Err(EngineError::NotFound("Task not found for project_path".to_string())),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // 更新
+    // ------------------------------------------------------------------------
+
+    /// 插件上报：刷新心跳与窗口信息，未知任务自动注册为 armed
+    pub fn report(&self, req: ReportRequest) -> UpdateOutcome {
+        self.commit(|tasks, changes| {
+            let now = changes.now;
+            if let Some(task) = tasks.iter_mut().find(|t| t.id == req.task_id) {
+                let gap = now.saturating_sub(task.last_heartbeat);
+                if task.last_heartbeat > 0 && gap > HEARTBEAT_GAP_MS {
+                    changes.timeline(&task.id, TimelineEventKind::HeartbeatGap { gap_ms: gap });
+                }
+                task.last_heartbeat = now;
+                if !self.os_focus() {
+                    task.is_focused = req.is_focused;
+                }
+
+                if !can_update_source(&task.source, "plugin") {
+                    debug!(task_id = %req.task_id, "Report ignored - lower priority source");
+                    changes.ignored(LOWER_PRIORITY_SOURCE);
+                    return UpdateOutcome::Ignored(LOWER_PRIORITY_SOURCE);
+                }
+
+                task.name = req.name;
+                task.ide = req.ide;
+                task.window_title = req.window_title;
+                if let Some(path) = req.project_path {
+                    task.project_path = Some(path);
+                }
+                if let Some(file) = req.active_file {
+                    task.active_file = Some(file);
+                }
+
+                debug!(task_id = %req.task_id, is_focused = %req.is_focused, "Task report processed");
+            } else {
+                info!(task_id = %req.task_id, name = %req.name, ide = %req.ide, "Task auto-registered");
+                let task = new_armed_task(req, "plugin", now);
+                changes.timeline(&task.id, TimelineEventKind::Registered { source: task.source.clone() });
+                changes.events.push(EngineEvent::TaskRegistered { task: task.clone() });
+                tasks.push(task);
+            }
+            UpdateOutcome::Applied
+        })
+    }
+
+    /// 注册没有插件心跳的任务（如终端里的 CLI 会话）；last_heartbeat 为 0，不会因心跳超时被清理
+    ///
+    /// 任务已存在时不做修改，返回是否新建。
+    pub fn register(&self, req: ReportRequest, source: &str) -> Result<bool, EngineError> {
+        validate_source(source)?;
+        Ok(self.commit(|tasks, changes| {
+            if tasks.iter().any(|t| t.id == req.task_id) {
+                return false;
+            }
+            info!(task_id = %req.task_id, name = %req.name, ide = %req.ide, source = %source, "Task registered");
+            let mut task = new_armed_task(req, source, changes.now);
+            task.last_heartbeat = 0;
+            changes.timeline(&task.id, TimelineEventKind::Registered { source: task.source.clone() });
+            changes.events.push(EngineEvent::TaskRegistered { task: task.clone() });
+            tasks.push(task);
+            true
+        }))
+    }
+
+    /// 更新状态 / 预估 / 阶段，source 缺省为 plugin
+    pub fn update_state(&self, req: UpdateStateRequest) -> Result<UpdateOutcome, EngineError> {
+        let source = req.source.as_deref().unwrap_or("plugin");
+        validate_source(source)?;
+        validate_status(req.status.as_deref())?;
+        let block_plugin_status = self.block_plugin_status();
+
+        self.commit(|tasks, changes| {
+            let found = tasks.iter_mut().find(|t| t.id == req.task_id);
+
+            if source == "plugin" && block_plugin_status {
+                debug!(task_id = %req.task_id, "Ignoring plugin status update - blocked");
+                match found {
+                    Some(task) => changes.ignored_update(&task.id, source, PLUGIN_STATUS_BLOCKED, req.status.as_deref()),
+                    None => changes.ignored(PLUGIN_STATUS_BLOCKED),
+                }
+                return Ok(UpdateOutcome::Ignored(PLUGIN_STATUS_BLOCKED));
+            }
+
+            let task = found.ok_or_else(|| EngineError::NotFound("Task not found".to_string()))?;
+            if !accepts_source(&task.source, source, block_plugin_status) {
+                info!(task_id = %req.task_id, "Ignoring update_state - lower priority");
+                changes.ignored_update(&task.id, source, LOWER_PRIORITY_SOURCE, req.status.as_deref());
+                return Ok(UpdateOutcome::Ignored(LOWER_PRIORITY_SOURCE));
+            }
+
+            changes.set_source(task, source);
+            task.last_update = changes.now;
+
+            if let Some(estimated_duration) = req.estimated_duration {
+                task.estimated_duration = Some(estimated_duration);
+            }
+            if let Some(ref current_stage) = req.current_stage {
+                changes.set_stage(task, current_stage);
+            }
+            if let Some(ref status) = req.status {
+                changes.apply_status_transition(task, status);
+            }
+            Ok(UpdateOutcome::Applied)
+        })
+    }
+
+    /// 按 project_path 定位任务后更新状态，source 缺省为 hook
+    pub fn update_state_by_path(&self, req: UpdateStateByPathRequest) -> Result<UpdateOutcome, EngineError> {
+        let source = req.source.as_deref().unwrap_or("hook");
+        validate_source(source)?;
+        validate_status(req.status.as_deref())?;
+        let task_id = self.find_task_id_by_path(&req.project_path, req.ide.as_deref())?;
+        let block_plugin_status = self.block_plugin_status();
+
+        self.commit(|tasks, changes| {
+            let task = tasks
+                .iter_mut()
+                .find(|t| t.id == task_id)
+                .ok_or_else(|| EngineError::NotFound("Task not found for project_path".to_string()))?;
+
+            if source == "plugin" && block_plugin_status {
+                debug!(project_path = %req.project_path, "Ignoring plugin status update - blocked");
+                changes.ignored_update(&task.id, source, PLUGIN_STATUS_BLOCKED, req.status.as_deref());
+                return Ok(UpdateOutcome::Ignored(PLUGIN_STATUS_BLOCKED));
+            }
+            if !accepts_source(&task.source, source, block_plugin_status) {
+                info!(project_path = %req.project_path, "Ignoring update_state_by_path - lower priority");
+                changes.ignored_update(&task.id, source, LOWER_PRIORITY_SOURCE, req.status.as_deref());
+                return Ok(UpdateOutcome::Ignored(LOWER_PRIORITY_SOURCE));
+            }
+
+            changes.set_source(task, source);
+            task.last_update = changes.now;
+            if let Some(ref status) = req.status {
+                changes.apply_status_transition(task, status);
+            }
+            Ok(UpdateOutcome::Applied)
+        })
+    }
+
+    /// 更新预估时长和阶段描述；不检查来源优先级，任何来源都可以更新
+    pub fn update_progress(
+        &self,
+        task_id: &str,
+        estimated_duration: Option<u64>,
+        current_stage: Option<&str>,
+    ) -> Result<(), EngineError> {
+        self.commit(|tasks, changes| {
+            let task = tasks
+                .iter_mut()
+                .find(|t| t.id == task_id)
+                .ok_or_else(|| EngineError::NotFound(format!("Task not found: {}", task_id)))?;
+            task.last_update = changes.now;
+            if let Some(estimated) = estimated_duration {
+                task.estimated_duration = Some(estimated);
+            }
+            if let Some(stage) = current_stage {
+                changes.set_stage(task, stage);
+            }
+            Ok(())
+        })
+    }
+
+    /// 更新 token 计数；与 update_progress 一样不检查来源优先级
+    pub fn update_tokens(&self, req: UpdateTokensRequest) -> Result<(), EngineError> {
+        self.commit(|tasks, _changes| {
+            let task = tasks
+                .iter_mut()
+                .find(|t| t.id == req.task_id)
+                .ok_or_else(|| EngineError::NotFound(format!("Task not found: {}", req.task_id)))?;
+            task.tokens = if req.increment { task.tokens.saturating_add(req.tokens) } else { req.tokens };
+            Ok(())
+        })
+    }
+
+    /// 将 task_ids 中的任务标记为获得焦点、其余任务取消焦点，不检查来源优先级；返回是否有变化
+    pub fn set_focused(&self, task_ids: &[String]) -> bool {
+        self.commit(|tasks, _changes| {
+            let mut changed = false;
+            for task in tasks.iter_mut() {
+                let focused = task_ids.contains(&task.id);
+                changed |= task.is_focused != focused;
+                task.is_focused = focused;
+            }
+            changed
+        })
+    }
+
+    /// 保存完成报告；来源优先级允许时同时将任务标记为 completed
+    ///
+    /// 报告本身任何来源都可以提交（与 update_progress 一致），返回是否切换了状态。
+    pub fn complete(&self, req: CompleteTaskRequest, default_source: &str) -> Result<bool, EngineError> {
+        let source = req.source.as_deref().unwrap_or(default_source).to_string();
+        let report = CompletionReport {
+            summary: req.summary,
+            changed_files: req.changed_files,
+            artifacts: req.artifacts,
+            submitted_at: self.now(),
+        };
+        report.validate().map_err(EngineError::BadRequest)?;
+
+        let task_id = match (req.task_id, req.project_path) {
+            (Some(id), _) => id,
+            (None, Some(path)) => self.find_task_id_by_path(&path, req.ide.as_deref())?,
+            (None, None) => {
+                return Err(EngineError::BadRequest("task_id or project_path is required".to_string()));
+            }
+        };
+
+        self.commit(|tasks, changes| {
+            let task = tasks
+                .iter_mut()
+                .find(|t| t.id == task_id)
+                .ok_or_else(|| EngineError::NotFound("Task not found".to_string()))?;
+
+            info!(task_id = %task_id, files = report.changed_files.len(), artifacts = report.artifacts.len(), "Completion report submitted");
+            task.completion = Some(report);
+            task.last_update = changes.now;
+            changes.timeline(&task.id, TimelineEventKind::Completion { source: source.clone() });
+
+            if task.status != "completed" && can_update_source(&task.source, &source) {
+                changes.set_source(task, &source);
+                changes.apply_status_transition(task, "completed");
+                return Ok(true);
+            }
+
+            // 任务已结束并写入历史时，由宿主补充到历史记录
+            if END_STATUSES.contains(&task.status.as_str()) && task.start_time > 0 {
+                changes.events.push(EngineEvent::RunAmended { task: task.clone() });
+            }
+            Ok(false)
+        })
+    }
+
+    /// 重置任务为 armed 状态
+    pub fn reset_to_armed(&self, task_id: &str) -> Result<(), EngineError> {
+        self.commit(|tasks, changes| {
+            let task = tasks
+                .iter_mut()
+                .find(|t| t.id == task_id)
+                .ok_or_else(|| EngineError::NotFound(format!("Task not found: {}", task_id)))?;
+            changes.apply_status_transition(task, "armed");
+            task.last_update = changes.now;
+            info!(task_id = %task_id, "Task reset to armed");
+            Ok(())
+        })
+    }
+
+    /// 把任务交还给插件：来源改回 plugin，还在运行中或等待输入的任务重置为 armed
+    ///
+    /// 用于 agent 会话结束后释放复用的插件任务，之后插件上报的状态不再因来源优先级被忽略。
+    pub fn release_to_plugin(&self, task_id: &str) -> Result<(), EngineError> {
+        self.commit(|tasks, changes| {
+            let task = tasks
+                .iter_mut()
+                .find(|t| t.id == task_id)
+                .ok_or_else(|| EngineError::NotFound(format!("Task not found: {}", task_id)))?;
+            changes.set_source(task, "plugin");
+            if task.start_time > 0 && !END_STATUSES.contains(&task.status.as_str()) {
+                changes.apply_status_transition(task, "armed");
+            }
+            task.last_update = changes.now;
+            info!(task_id = %task_id, "Task released to plugin");
+            Ok(())
+        })
+    }
+
+    pub fn delete(&self, task_id: &str) -> Result<(), EngineError> {
+        self.commit(|tasks, changes| {
+            let before_count = tasks.len();
+            tasks.retain(|t| t.id != task_id);
+            if tasks.len() == before_count {
+                return Err(EngineError::NotFound("Task not found".to_string()));
+            }
+            info!(task_id = %task_id, "Task deleted");
+            changes.events.push(EngineEvent::TasksRemoved { task_ids: vec![task_id.to_string()] });
+            Ok(())
+        })
+    }
+
+    /// 移除指定任务；task_id 为 None 时清空所有任务
+    pub fn reset(&self, task_id: Option<&str>) {
+        self.commit(|tasks, changes| match task_id {
+            Some(task_id) => {
+                tasks.retain(|t| t.id != task_id);
+                info!(task_id = %task_id, "Task removed");
+                changes.events.push(EngineEvent::TasksRemoved { task_ids: vec![task_id.to_string()] });
+            }
+            None => {
+                tasks.clear();
+                info!("All tasks reset");
+                changes.events.push(EngineEvent::TasksCleared);
+            }
+        })
+    }
+
+    // ------------------------------------------------------------------------
+    // 宿主写回
+    // ------------------------------------------------------------------------
+
+    /// 修改某次运行（task_id + start_time）；任务已重新开始或被移除时不做修改，返回 None
+    ///
+    /// 用于 git 解析、改动统计等在后台完成的工作写回结果。
+    pub fn update_run<R>(&self, task_id: &str, start_time: u64, f: impl FnOnce(&mut Task) -> R) -> Option<R> {
+        let mut tasks = self.tasks.lock().unwrap();
+        tasks
+            .iter_mut()
+            .find(|t| t.id == task_id && t.start_time == start_time)
+            .map(f)
+    }
+
+    /// 在锁内遍历修改所有任务（如刷新告警标记），不产生事件
+    pub fn with_tasks_mut<R>(&self, f: impl FnOnce(&mut [Task]) -> R) -> R {
+        let mut tasks = self.tasks.lock().unwrap();
+        f(&mut tasks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::events::NullSink;
+
+    fn engine_at(now: u64) -> (Arc<ManualClock>, TaskEngine) {
+        let clock = Arc::new(ManualClock::new(now));
+        let engine = TaskEngine::new(clock.clone(), Arc::new(NullSink));
+        (clock, engine)
+    }
+
+    fn report(task_id: &str) -> ReportRequest {
+        ReportRequest {
+            task_id: task_id.to_string(),
+            name: task_id.to_string(),
+            ide: "vscode".to_string(),
+            window_title: task_id.to_string(),
+            is_focused: false,
+            project_path: None,
+            active_file: None,
+        }
+    }
+
+    fn set_status(engine: &TaskEngine, task_id: &str, status: &str) {
+        let request = UpdateStateRequest {
+            task_id: task_id.to_string(),
+            status: Some(status.to_string()),
+            source: Some("hook".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(engine.update_state(request).unwrap(), UpdateOutcome::Applied);
+    }
+
+    #[test]
+    fn run_times_come_from_the_clock() {
+        let (clock, engine) = engine_at(1_000);
+        engine.report(report("a"));
+        clock.advance(500);
+        set_status(&engine, "a", "running");
+        clock.advance(2_000);
+        set_status(&engine, "a", "completed");
+
+        let task = engine.get("a").unwrap();
+        assert_eq!(task.start_time, 1_500);
+        assert_eq!(task.end_time, Some(3_500));
+    }
+
+    #[test]
+    fn released_task_accepts_plugin_status_again() {
+        let (_, engine) = engine_at(1_000);
+        engine.set_block_plugin_status(false);
+        engine.report(report("a"));
+        set_status(&engine, "a", "running");
+
+        engine.release_to_plugin("a").unwrap();
+        let task = engine.get("a").unwrap();
+        assert_eq!((task.source.as_str(), task.status.as_str()), ("plugin", "armed"));
+
+        let request = UpdateStateRequest {
+            task_id: "a".to_string(),
+            status: Some("running".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(engine.update_state(request).unwrap(), UpdateOutcome::Applied);
+    }
+
+    #[test]
+    fn stale_plugin_tasks_are_removed_after_heartbeat_timeout() {
+        let (clock, engine) = engine_at(1_000);
+        engine.report(report("plugin"));
+        engine.register(report("cli"), "hook").unwrap();
+
+        clock.advance(HEARTBEAT_TIMEOUT_MS - 1);
+        assert_eq!(engine.merged_tasks().len(), 2);
+
+        // 没有心跳的任务（last_heartbeat 为 0）不会因超时被清理
+        clock.advance(1);
+        let ids: Vec<String> = engine.merged_tasks().into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, ["cli"]);
+    }
+
+    #[test]
+    fn fs_only_stands_in_for_blocked_plugin_status() {
+        let (_, engine) = engine_at(1_000);
+        engine.report(report("a"));
+        let fs_update = |status: &str| UpdateStateRequest {
+            task_id: "a".to_string(),
+            status: Some(status.to_string()),
+            source: Some("fs".to_string()),
+            ..Default::default()
+        };
+
+        // 插件状态被屏蔽时由文件活动代为推断
+        assert_eq!(engine.update_state(fs_update("running")).unwrap(), UpdateOutcome::Applied);
+
+        // 插件状态放开后插件收回任务，文件活动不能再覆盖
+        engine.set_block_plugin_status(false);
+        let plugin_update = UpdateStateRequest {
+            task_id: "a".to_string(),
+            status: Some("completed".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(engine.update_state(plugin_update).unwrap(), UpdateOutcome::Applied);
+        assert_eq!(engine.get("a").unwrap().source, "plugin");
+        assert_eq!(engine.update_state(fs_update("running")).unwrap(), UpdateOutcome::Ignored(LOWER_PRIORITY_SOURCE));
+    }
+
+    #[test]
+    fn path_updates_follow_the_same_source_rules() {
+        let (_, engine) = engine_at(1_000);
+        engine.report(ReportRequest { project_path: Some("/work/app".to_string()), ..report("a") });
+        let by_path = |source: &str| UpdateStateByPathRequest {
+            project_path: "/work/app".to_string(),
+            ide: None,
+            status: Some("running".to_string()),
+            source: Some(source.to_string()),
+        };
+
+        let blocked = engine.update_state_by_path(by_path("plugin")).unwrap();
+        assert_eq!(blocked, UpdateOutcome::Ignored(PLUGIN_STATUS_BLOCKED));
+        assert_eq!(engine.update_state_by_path(by_path("fs")).unwrap(), UpdateOutcome::Applied);
+        assert_eq!(engine.get("a").unwrap().source, "fs");
+    }
+}
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::task::Task;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TimelineEventKind {
+    /// 插件首次上报，任务被自动注册
+    Registered { source: String },
+    Status { from: String, to: String },
+    Stage { stage: Option<String> },
+    Source { from: String, to: String },
+    /// 被更高优先级来源屏蔽或被设置屏蔽的更新
+    Ignored {
+        source: String,
+        reason: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        status: Option<String>,
+    },
+    HeartbeatGap { gap_ms: u64 },
+    /// 收到完成报告
+    Completion { source: String },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct TimelineEvent {
+    /// 事件时间（毫秒）
+    pub at: u64,
+    #[serde(flatten)]
+    pub kind: TimelineEventKind,
+}
+
+/// 引擎发出的事件
+///
+/// 引擎本身只维护内存中的任务列表；时间线存储、指标、git 解析、历史记录等副作用
+/// 都由宿主通过 [`EventSink`] 处理。
+#[derive(Clone, Debug)]
+pub enum EngineEvent {
+    /// 任务时间线上的一条事件
+    Timeline { task_id: String, event: TimelineEvent },
+    /// 被忽略的更新（任务不存在时不会有对应的 Timeline 事件）
+    UpdateIgnored { reason: String },
+    /// 插件首次上报，任务被自动注册
+    TaskRegistered { task: Task },
+    /// 任务开始或重新开始运行
+    RunStarted { task: Task },
+    /// 任务进入 completed / error / cancelled，task 为结束时的快照
+    RunEnded { task: Task },
+    /// 已结束的运行补充了完成报告
+    RunAmended { task: Task },
+    /// 任务被删除或因心跳超时被清理
+    TasksRemoved { task_ids: Vec<String> },
+    /// 所有任务被重置
+    TasksCleared,
+}
+
+/// 接收引擎事件
+///
+/// 事件在引擎释放任务锁之后按发生顺序投递，处理时可以再调用引擎。
+pub trait EventSink: Send + Sync {
+    fn emit(&self, event: EngineEvent);
+}
+
+/// 丢弃所有事件
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullSink;
+
+impl EventSink for NullSink {
+    fn emit(&self, _event: EngineEvent) {}
+}
+
+impl<F> EventSink for F
+where
+    F: Fn(EngineEvent) + Send + Sync,
+{
+    fn emit(&self, event: EngineEvent) {
+        self(event)
+    }
+}
@@ -0,0 +1,114 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use tracing::debug;
+
+/// 任务所在 git 仓库的信息，任务开始时通过 git 命令行解析
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct GitContext {
+    pub repo_root: String,
+    /// 当前分支，detached HEAD 时为 None
+    pub branch: Option<String>,
+    /// 链接 worktree 的名称，主 worktree 为 None
+    pub worktree: Option<String>,
+    /// 任务开始时的 HEAD commit
+    pub head_at_start: Option<String>,
+    /// 任务开始时未提交的文件数（含未跟踪文件）
+    pub dirty_files: u32,
+}
+
+/// 任务结束时相对开始 commit 的改动统计
+///
+/// 比较的是开始 commit 与结束时的工作区，因此同时包含 agent 的提交和未提交的修改；
+/// 未跟踪的新文件单独计数。
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ChangeSummary {
+    pub base_commit: String,
+    pub head_commit: Option<String>,
+    /// base_commit 之后新增的提交数
+    pub commits: u32,
+    pub files_changed: u32,
+    pub insertions: u32,
+    pub deletions: u32,
+    pub untracked_files: u32,
+}
+
+fn git(dir: &str, args: &[&str]) -> Option<String> {
+    let mut cmd = Command::new("git");
+    cmd.arg("-C").arg(dir).args(args);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        debug!(dir = %dir, args = ?args, "git command failed");
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim_end().to_string())
+}
+
+/// 解析 project_path 所在仓库，不在 git 仓库中（或没有安装 git）时返回 None
+pub fn resolve(project_path: &str) -> Option<GitContext> {
+    if !Path::new(project_path).is_dir() {
+        return None;
+    }
+    let repo_root = git(project_path, &["rev-parse", "--show-toplevel"])?;
+
+    let branch = git(&repo_root, &["symbolic-ref", "--quiet", "--short", "HEAD"]);
+    let head_at_start = git(&repo_root, &["rev-parse", "--verify", "--quiet", "HEAD"]);
+
+    // 链接 worktree 的 git-dir 形如 <common>/worktrees/<name>
+    let git_dir = git(&repo_root, &["rev-parse", "--absolute-git-dir"]);
+    let common_dir = git(&repo_root, &["rev-parse", "--path-format=absolute", "--git-common-dir"]);
+    let worktree = match (git_dir, common_dir) {
+        (Some(dir), Some(common)) if dir != common => Path::new(&dir)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string()),
+        _ => None,
+    };
+
+    let dirty_files = git(&repo_root, &["status", "--porcelain", "--untracked-files=normal"])
+        .map(|out| out.lines().filter(|l| !l.is_empty()).count() as u32)
+        .unwrap_or(0);
+
+    Some(GitContext {
+        repo_root,
+        branch,
+        worktree,
+        head_at_start,
+        dirty_files,
+    })
+}
+
+/// 统计从 base_commit 到当前工作区的改动
+pub fn change_summary(repo_root: &str, base_commit: &str) -> Option<ChangeSummary> {
+    let numstat = git(repo_root, &["diff", "--numstat", base_commit])?;
+
+    let mut summary = ChangeSummary {
+        base_commit: base_commit.to_string(),
+        ..Default::default()
+    };
+    for line in numstat.lines().filter(|l| !l.is_empty()) {
+        let mut parts = line.splitn(3, '\t');
+        // 二进制文件的增删行数为 "-"
+        let added = parts.next().and_then(|n| n.parse::<u32>().ok()).unwrap_or(0);
+        let removed = parts.next().and_then(|n| n.parse::<u32>().ok()).unwrap_or(0);
+        summary.files_changed += 1;
+        summary.insertions += added;
+        summary.deletions += removed;
+    }
+
+    summary.head_commit = git(repo_root, &["rev-parse", "--verify", "--quiet", "HEAD"]);
+    summary.commits = git(repo_root, &["rev-list", "--count", &format!("{}..HEAD", base_commit)])
+        .and_then(|n| n.parse().ok())
+        .unwrap_or(0);
+    summary.untracked_files = git(repo_root, &["ls-files", "--others", "--exclude-standard"])
+        .map(|out| out.lines().filter(|l| !l.is_empty()).count() as u32)
+        .unwrap_or(0);
+
+    Some(summary)
+}
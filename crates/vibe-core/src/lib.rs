@@ -0,0 +1,19 @@
+//! Vibe Process Bar 的任务引擎
+//!
+//! [`TaskEngine`] 持有任务列表并实现来源优先级、状态切换、合并与清理，不依赖全局状态。
+//! Tauri 应用、HTTP server 等前端只负责把请求转交给引擎，并通过 [`EventSink`]
+//! 处理时间线、指标、历史记录等副作用。
+
+pub mod clock;
+pub mod engine;
+pub mod events;
+pub mod git_context;
+pub mod project_identity;
+pub mod status_line;
+pub mod task;
+pub mod window_match;
+
+pub use clock::{Clock, ManualClock, SystemClock};
+pub use engine::{EngineError, TaskEngine, UpdateOutcome, HEARTBEAT_GAP_MS, HEARTBEAT_TIMEOUT_MS};
+pub use events::{EngineEvent, EventSink, NullSink, TimelineEvent, TimelineEventKind};
+pub use task::*;
@@ -0,0 +1,370 @@
+use std::path::Path;
+
+/// 向上查找时识别为仓库/工作区根目录的标记
+const ROOT_MARKERS: &[&str] = &[".git", ".hg", ".jj"];
+
+/// 项目路径的规范化表示
+///
+/// 插件上报的 project_path 和 hook 脚本发送的 `$(pwd)` 往往不是同一个字符串：
+/// 可能是子目录、符号链接、WSL 的 `/mnt/c/...`、`\\wsl$\...` 或 `vscode-remote://` URI。
+/// 这里统一成 (host, path) 形式后再比较。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectIdentity {
+    /// 远程主机（如 `ssh-remote+devbox`、`wsl+ubuntu`），本地路径为 None
+    pub host: Option<String>,
+    /// 规范化后的路径，统一使用 '/' 分隔；大小写不敏感的文件系统上已转为小写
+    pub path: String,
+    /// 所在仓库/工作区的根目录（仅本地存在的路径可解析）
+    pub root: Option<String>,
+}
+
+impl ProjectIdentity {
+    pub fn resolve(raw: &str) -> Self {
+        let (host, path) = split_uri(raw.trim());
+
+        let mut root = None;
+        let mut path = path;
+        if host.is_none() {
+            if let Ok(canonical) = std::fs::canonicalize(&path) {
+                root = find_root(&canonical).map(|r| normalize_path(&path_to_string(&r)));
+                path = path_to_string(&canonical);
+            }
+        }
+
+        Self {
+            host: host.map(|h| h.to_lowercase()),
+            path: normalize_path(&path),
+            root,
+        }
+    }
+
+    /// 一侧没有主机信息时（hook 在远程机器上只知道本地路径）视为同一主机
+    fn same_host(&self, other: &Self) -> bool {
+        match (&self.host, &other.host) {
+            (Some(a), Some(b)) => a == b,
+            _ => true,
+        }
+    }
+
+    pub fn same_path(&self, other: &Self) -> bool {
+        self.same_host(other) && self.path == other.path
+    }
+
+    /// other 是否位于 self 目录之下（含相等）
+    pub fn contains(&self, other: &Self) -> bool {
+        self.same_host(other) && is_ancestor(&self.path, &other.path)
+    }
+
+    /// 根目录、家目录或其上层：这样的"项目"包含几乎所有路径，不能作为祖先目录匹配
+    pub fn is_broad(&self) -> bool {
+        is_broad_path(&self.path, self.host.is_none())
+    }
+
+    /// 位于同一个仓库/工作区根目录下；家目录本身是仓库（dotfiles）时不算
+    pub fn same_root(&self, other: &Self) -> bool {
+        self.same_host(other)
+            && matches!((&self.root, &other.root), (Some(a), Some(b)) if a == b && !is_broad_path(a, true))
+    }
+}
+
+/// 按路径查找任务的结果
+#[derive(Debug)]
+pub enum PathMatch<T> {
+    Found(T),
+    Ambiguous(Vec<T>),
+    NotFound,
+}
+
+fn pick<T>(mut matched: Vec<T>) -> Option<PathMatch<T>> {
+    match matched.len() {
+        0 => None,
+        1 => matched.pop().map(PathMatch::Found),
+        _ => Some(PathMatch::Ambiguous(matched)),
+    }
+}
+
+/// 在候选 (key, project_path) 中查找 target 对应的项目，按以下顺序逐级放宽：
+///
+/// 1. 字符串完全相等
+/// 2. 规范化后路径相等
+/// 3. 候选是 target 的祖先目录（取最深的一层；根目录、家目录这样过于宽泛的候选不参与）
+/// 4. 位于同一个仓库/工作区根目录下
+///
+/// 任一级命中多个候选时返回 Ambiguous，而不是随便挑一个。
+pub fn find_project<T: Clone>(target: &str, candidates: &[(T, String)]) -> PathMatch<T> {
+    let exact: Vec<T> = candidates
+        .iter()
+        .filter(|(_, p)| p == target)
+        .map(|(k, _)| k.clone())
+        .collect();
+    if let Some(m) = pick(exact) {
+        return m;
+    }
+
+    let target = ProjectIdentity::resolve(target);
+    let resolved: Vec<(T, ProjectIdentity)> = candidates
+        .iter()
+        .map(|(k, p)| (k.clone(), ProjectIdentity::resolve(p)))
+        .collect();
+
+    let same: Vec<T> = resolved
+        .iter()
+        .filter(|(_, id)| id.same_path(&target))
+        .map(|(k, _)| k.clone())
+        .collect();
+    if let Some(m) = pick(same) {
+        return m;
+    }
+
+    let is_ancestor = |id: &ProjectIdentity| id.contains(&target) && !id.is_broad();
+    let deepest = resolved
+        .iter()
+        .filter(|(_, id)| is_ancestor(id))
+        .map(|(_, id)| id.path.len())
+        .max();
+    if let Some(depth) = deepest {
+        let ancestors: Vec<T> = resolved
+            .iter()
+            .filter(|(_, id)| is_ancestor(id) && id.path.len() == depth)
+            .map(|(k, _)| k.clone())
+            .collect();
+        if let Some(m) = pick(ancestors) {
+            return m;
+        }
+    }
+
+    let same_root: Vec<T> = resolved
+        .iter()
+        .filter(|(_, id)| id.same_root(&target))
+        .map(|(k, _)| k.clone())
+        .collect();
+    pick(same_root).unwrap_or(PathMatch::NotFound)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn path_to_string(path: &Path) -> String {
+    let s = path.to_string_lossy();
+    // Windows canonicalize 返回 verbatim 路径
+    if let Some(unc) = s.strip_prefix(r"\\?\UNC\") {
+        format!(r"\\{}", unc)
+    } else if let Some(rest) = s.strip_prefix(r"\\?\") {
+        rest.to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+/// 规范化后的路径是否为根目录、家目录或其上层。
+/// 远程主机的家目录无法读取，按 /root、/home/<user>、/Users/<user> 的惯例判断
+fn is_broad_path(path: &str, local: bool) -> bool {
+    let components: Vec<String> =
+        path.split('/').filter(|c| !c.is_empty() && !c.ends_with(':')).map(str::to_lowercase).collect();
+    let home_like = match components.as_slice() {
+        [] => true,
+        [first] => ["root", "home", "users"].contains(&first.as_str()),
+        [first, _] => ["home", "users"].contains(&first.as_str()),
+        _ => false,
+    };
+    home_like || (local && home_dir().is_some_and(|home| is_ancestor(path, &normalize_path(&home))))
+}
+
+fn home_dir() -> Option<String> {
+    std::env::var("HOME").or_else(|_| std::env::var("USERPROFILE")).ok().filter(|h| !h.is_empty())
+}
+
+fn find_root(path: &Path) -> Option<std::path::PathBuf> {
+    path.ancestors()
+        .find(|dir| ROOT_MARKERS.iter().any(|m| dir.join(m).exists()))
+        .map(Path::to_path_buf)
+}
+
+/// 拆出 URI 中的远程主机，返回 (host, 本地形式的路径)
+fn split_uri(raw: &str) -> (Option<String>, String) {
+    if let Some(rest) = raw.strip_prefix("vscode-remote://") {
+        let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+        return (Some(percent_decode(authority)), format!("/{}", percent_decode(path)));
+    }
+    if let Some(rest) = raw.strip_prefix("file://") {
+        // file:///c%3A/Users/... 或 file://server/share/...
+        let decoded = percent_decode(rest);
+        return match decoded.strip_prefix('/') {
+            Some(path) if has_drive_letter(path) => (None, path.to_string()),
+            Some(path) => (None, format!("/{}", path)),
+            None => (None, format!("//{}", decoded)),
+        };
+    }
+    // \\wsl$\Ubuntu\home\... 或 \\wsl.localhost\Ubuntu\home\...
+    let slashed = raw.replace('\\', "/");
+    for prefix in ["//wsl$/", "//wsl.localhost/"] {
+        if let Some(rest) = slashed.strip_prefix(prefix) {
+            let (distro, path) = rest.split_once('/').unwrap_or((rest, ""));
+            return (Some(format!("wsl+{}", distro)), format!("/{}", path));
+        }
+    }
+    (None, raw.to_string())
+}
+
+fn has_drive_letter(path: &str) -> bool {
+    let bytes = path.as_bytes();
+    bytes.len() >= 2
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes.len() == 2 || bytes[2] == b'/' || bytes[2] == b'\\')
+}
+
+/// 统一分隔符、去掉末尾的 '/'、把 WSL 的 /mnt/<drive> 转为盘符，
+/// 并在大小写不敏感的文件系统上转小写
+fn normalize_path(path: &str) -> String {
+    let mut p = path.replace('\\', "/");
+
+    // /mnt/c/Users/... -> c:/Users/...
+    if let Some(rest) = p.strip_prefix("/mnt/") {
+        let mut chars = rest.chars();
+        if let Some(drive) = chars.next().filter(char::is_ascii_alphabetic) {
+            let tail = chars.as_str();
+            if tail.is_empty() || tail.starts_with('/') {
+                p = format!("{}:{}", drive, tail);
+            }
+        }
+    }
+
+    while p.len() > 1 && p.ends_with('/') && !p.ends_with(":/") {
+        p.pop();
+    }
+
+    if has_drive_letter(&p) || cfg!(any(target_os = "windows", target_os = "macos")) {
+        p.to_lowercase()
+    } else {
+        p
+    }
+}
+
+fn is_ancestor(ancestor: &str, path: &str) -> bool {
+    path == ancestor
+        || (path.starts_with(ancestor)
+            && (ancestor.ends_with('/') || path[ancestor.len()..].starts_with('/')))
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("");
+            if let Ok(b) = u8::from_str_radix(hex, 16) {
+                out.push(b);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(paths: &[&str]) -> Vec<(usize, String)> {
+        paths.iter().enumerate().map(|(i, p)| (i, p.to_string())).collect()
+    }
+
+    fn found(target: &str, paths: &[&str]) -> Option<usize> {
+        match find_project(target, &candidates(paths)) {
+            PathMatch::Found(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn vscode_remote_uri_keeps_host_and_decodes_path() {
+        let id = ProjectIdentity::resolve("vscode-remote://ssh-remote%2BDevBox/home/me/my%20app/");
+        assert_eq!(id.host.as_deref(), Some("ssh-remote+devbox"));
+        assert_eq!(id.path, "/home/me/my app");
+    }
+
+    #[test]
+    fn file_uri_becomes_a_local_path() {
+        assert_eq!(split_uri("file:///c%3A/Users/Me/app"), (None, "c:/Users/Me/app".to_string()));
+        assert_eq!(split_uri("file:///work/app"), (None, "/work/app".to_string()));
+        assert_eq!(split_uri("file://server/share/app"), (None, "//server/share/app".to_string()));
+    }
+
+    #[test]
+    fn wsl_share_maps_to_the_distro() {
+        for raw in [r"\\wsl$\Ubuntu\home\me\app", r"\\wsl.localhost\Ubuntu\home\me\app"] {
+            let id = ProjectIdentity::resolve(raw);
+            assert_eq!((id.host.as_deref(), id.path.as_str()), (Some("wsl+ubuntu"), "/home/me/app"));
+        }
+    }
+
+    #[test]
+    fn mnt_drive_and_drive_letters_are_case_folded() {
+        assert_eq!(normalize_path("/mnt/c/Users/Me/App"), "c:/users/me/app");
+        assert_eq!(normalize_path(r"C:\Users\Me\App\"), "c:/users/me/app");
+        assert_eq!(normalize_path("C:/"), "c:/");
+        // /mnt 下的其他目录不是盘符
+        assert_eq!(normalize_path("/mnt/data/app"), "/mnt/data/app");
+        assert_eq!(normalize_path("/work/app/"), "/work/app");
+    }
+
+    #[test]
+    fn ancestor_check_respects_path_components() {
+        assert!(is_ancestor("/work/app", "/work/app/src"));
+        assert!(is_ancestor("/work/app", "/work/app"));
+        assert!(!is_ancestor("/work/app", "/work/application"));
+        assert!(is_ancestor("c:/", "c:/work"));
+    }
+
+    #[test]
+    fn percent_decoding_leaves_invalid_escapes() {
+        assert_eq!(percent_decode("a%20b%2Fc"), "a b/c");
+        assert_eq!(percent_decode("100%"), "100%");
+        assert_eq!(percent_decode("%zz"), "%zz");
+    }
+
+    #[test]
+    fn matches_from_exact_to_deepest_ancestor() {
+        let paths = ["/work/app", "/work/app/packages/web", "/work/other"];
+        assert_eq!(found("/work/app", &paths), Some(0));
+        assert_eq!(found("/work/app/", &paths), Some(0));
+        assert_eq!(found("/work/app/packages/web/src", &paths), Some(1));
+        assert_eq!(found("/work/app/docs", &paths), Some(0));
+        assert!(matches!(find_project("/elsewhere", &candidates(&paths)), PathMatch::NotFound));
+    }
+
+    #[test]
+    fn home_and_root_are_not_ancestors() {
+        let paths = ["/", "/home/me", "/Users/me", r"C:\Users\Me"];
+        for target in ["/home/me/app", "/Users/me/app", "/work/app", r"C:\Users\Me\app"] {
+            assert_eq!(found(target, &paths), None, "{}", target);
+        }
+        // 相等时仍然匹配
+        assert_eq!(found("/home/me/", &paths), Some(1));
+    }
+
+    #[test]
+    fn several_matches_are_ambiguous() {
+        let paths = ["/work/app", "/work/app"];
+        match find_project("/work/app", &candidates(&paths)) {
+            PathMatch::Ambiguous(matched) => assert_eq!(matched, [0, 1]),
+            other => panic!("expected Ambiguous, got {:?}", other),
+        }
+        let paths = ["/work/app/", "/work/app"];
+        assert!(matches!(find_project("/work/app/src", &candidates(&paths)), PathMatch::Ambiguous(_)));
+    }
+
+    #[test]
+    fn remote_and_local_hosts_do_not_mix() {
+        let paths = ["vscode-remote://ssh-remote+devbox/work/app", "vscode-remote://ssh-remote+other/work/app"];
+        assert_eq!(found("vscode-remote://ssh-remote+devbox/work/app/src", &paths), Some(0));
+        // hook 在远程机器上只知道本地路径：两个主机都匹配
+        assert!(matches!(find_project("/work/app/src", &candidates(&paths)), PathMatch::Ambiguous(_)));
+    }
+}
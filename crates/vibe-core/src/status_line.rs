@@ -0,0 +1,433 @@
+use serde::Serialize;
+
+use crate::task::Task;
+
+// ============================================================================
+// 状态栏模板
+// ============================================================================
+//
+// 用于 tmux、shell 提示符、waybar/polybar 的一行状态文本。
+//
+// - `{running}` `{waiting}` `{done}` `{error}` `{cancelled}` `{total}`：各状态的任务数
+// - `{stage}` `{project}` `{ide}` `{status}` `{icon}` `{elapsed}` `{progress}`：当前任务的信息
+// - `[...]`：可选段，段内所有字段都为空或 0 时整段不输出
+// - `{{` `}}` `[[` `]]`：输出字面量括号
+
+pub const DEFAULT_FORMAT: &str = "[{running}▶ ][{waiting}? ][{done}✓ ][{error}✗ ]{stage}";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Running,
+    Waiting,
+    Done,
+    Error,
+    Cancelled,
+    Total,
+    Stage,
+    Project,
+    Ide,
+    Status,
+    Icon,
+    Elapsed,
+    Progress,
+}
+
+impl Field {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "running" => Self::Running,
+            "waiting" => Self::Waiting,
+            "done" => Self::Done,
+            "error" => Self::Error,
+            "cancelled" => Self::Cancelled,
+            "total" => Self::Total,
+            "stage" => Self::Stage,
+            "project" => Self::Project,
+            "ide" => Self::Ide,
+            "status" => Self::Status,
+            "icon" => Self::Icon,
+            "elapsed" => Self::Elapsed,
+            "progress" => Self::Progress,
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+    Section(Vec<Segment>),
+}
+
+/// 解析后的模板，流式输出时只解析一次
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(format: &str) -> Result<Self, String> {
+        let mut chars = format.chars().peekable();
+        let mut segments = Vec::new();
+        // 可选段不支持嵌套，进入段时把外层内容暂存在这里
+        let mut outer: Option<Vec<Segment>> = None;
+        let mut literal = String::new();
+
+        let flush = |literal: &mut String, segments: &mut Vec<Segment>| {
+            if !literal.is_empty() {
+                segments.push(Segment::Literal(std::mem::take(literal)));
+            }
+        };
+
+        while let Some(c) = chars.next() {
+            match c {
+                '{' | '}' | '[' | ']' if chars.peek() == Some(&c) => {
+                    chars.next();
+                    literal.push(c);
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("Unclosed placeholder {{{}", name)),
+                        }
+                    }
+                    let field = Field::parse(name.trim()).ok_or_else(|| format!("Unknown placeholder {{{}}}", name))?;
+                    flush(&mut literal, &mut segments);
+                    segments.push(Segment::Field(field));
+                }
+                '}' => return Err("Unmatched '}' (use '}}' for a literal brace)".to_string()),
+                '[' => {
+                    if outer.is_some() {
+                        return Err("Optional sections cannot be nested".to_string());
+                    }
+                    flush(&mut literal, &mut segments);
+                    outer = Some(std::mem::take(&mut segments));
+                }
+                ']' => {
+                    let Some(mut parent) = outer.take() else {
+                        return Err("Unmatched ']' (use ']]' for a literal bracket)".to_string());
+                    };
+                    flush(&mut literal, &mut segments);
+                    parent.push(Segment::Section(std::mem::take(&mut segments)));
+                    segments = parent;
+                }
+                c => literal.push(c),
+            }
+        }
+        if outer.is_some() {
+            return Err("Unclosed '['".to_string());
+        }
+        flush(&mut literal, &mut segments);
+        Ok(Self { segments })
+    }
+
+    /// 渲染为一行文本，去掉首尾空白
+    pub fn render(&self, summary: &StatusSummary) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            render_segment(segment, summary, &mut out);
+        }
+        out.trim().to_string()
+    }
+}
+
+fn render_segment(segment: &Segment, summary: &StatusSummary, out: &mut String) {
+    match segment {
+        Segment::Literal(text) => out.push_str(text),
+        Segment::Field(field) => out.push_str(&summary.value(*field).unwrap_or_else(|| zero_value(*field))),
+        Segment::Section(inner) => {
+            let has_value = inner.iter().any(|s| matches!(s, Segment::Field(f) if summary.value(*f).is_some()));
+            if has_value {
+                for segment in inner {
+                    render_segment(segment, summary, out);
+                }
+            }
+        }
+    }
+}
+
+/// 可选段之外，空的计数显示为 0，其余字段为空字符串
+fn zero_value(field: Field) -> String {
+    match field {
+        Field::Running | Field::Waiting | Field::Done | Field::Error | Field::Cancelled | Field::Total => {
+            "0".to_string()
+        }
+        _ => String::new(),
+    }
+}
+
+// ============================================================================
+// 任务汇总
+// ============================================================================
+
+/// 模板渲染所需的任务计数和当前任务
+pub struct StatusSummary<'a> {
+    tasks: &'a [Task],
+    now: u64,
+    running: usize,
+    waiting: usize,
+    done: usize,
+    error: usize,
+    cancelled: usize,
+    /// 聚焦的运行中任务优先，其次是任意运行中任务、等待输入的任务，最后是聚焦的任务
+    primary: Option<&'a Task>,
+}
+
+impl<'a> StatusSummary<'a> {
+    /// 已断开的远程任务状态未知，不参与计数，也不作为当前任务
+    pub fn new(tasks: &'a [Task], now: u64) -> Self {
+        let live = || tasks.iter().filter(|t| !t.stale);
+        let count = |statuses: &[&str]| live().filter(|t| statuses.contains(&t.status.as_str())).count();
+        let running = live().filter(|t| t.status == "running");
+        let primary = running
+            .clone()
+            .find(|t| t.is_focused)
+            .or_else(|| running.clone().next())
+            .or_else(|| live().find(|t| t.status == "waiting_input"))
+            .or_else(|| live().find(|t| t.is_focused));
+
+        Self {
+            tasks,
+            now,
+            running: count(&["running"]),
+            waiting: count(&["waiting_input"]),
+            done: count(&["completed"]),
+            error: count(&["error"]),
+            cancelled: count(&["cancelled"]),
+            primary,
+        }
+    }
+
+    /// 字段的值；计数为 0 或没有当前任务时返回 None
+    fn value(&self, field: Field) -> Option<String> {
+        let count = |n: usize| (n > 0).then(|| n.to_string());
+        let task = self.primary;
+        match field {
+            Field::Running => count(self.running),
+            Field::Waiting => count(self.waiting),
+            Field::Done => count(self.done),
+            Field::Error => count(self.error),
+            Field::Cancelled => count(self.cancelled),
+            Field::Total => count(self.tasks.len()),
+            Field::Stage => task.and_then(stage),
+            Field::Project => task.map(project),
+            Field::Ide => task.map(|t| t.ide.clone()).filter(|s| !s.is_empty()),
+            Field::Status => task.map(|t| t.status.clone()),
+            Field::Icon => task.map(|t| icon(t).to_string()),
+            Field::Elapsed => task.and_then(|t| elapsed(t, self.now)).map(format_duration),
+            Field::Progress => task
+                .filter(|t| t.status == "running" || t.status == "completed")
+                .map(|t| t.progress(self.now))
+                .filter(|p| *p > 0)
+                .map(|p| p.to_string()),
+        }
+    }
+
+    /// waybar 的 class：running > error > done > waiting > idle
+    pub fn class(&self) -> &'static str {
+        if self.running > 0 {
+            "running"
+        } else if self.error > 0 {
+            "error"
+        } else if self.done > 0 {
+            "done"
+        } else if self.waiting > 0 {
+            "waiting"
+        } else {
+            "idle"
+        }
+    }
+
+    /// 每个任务一行，用于悬停提示
+    pub fn tooltip(&self) -> String {
+        self.tasks
+            .iter()
+            .map(|task| {
+                let mut line = format!("{} {} ({})", icon(task), project(task), task.ide);
+                if let Some(host) = &task.host {
+                    line.push_str(&format!(" @{}", host));
+                }
+                if task.stale {
+                    line.push_str(" [disconnected]");
+                }
+                if let Some(stage) = stage(task) {
+                    line.push_str(&format!(" - {}", stage));
+                }
+                if let Some(ms) = elapsed(task, self.now) {
+                    line.push_str(&format!(" {}", format_duration(ms)));
+                }
+                line
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn percentage(&self) -> u32 {
+        self.primary.map(|t| t.progress(self.now)).unwrap_or(0)
+    }
+}
+
+/// waybar `return-type: json` 的输出
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WaybarStatus {
+    pub text: String,
+    pub tooltip: String,
+    pub class: String,
+    pub percentage: u32,
+}
+
+pub fn waybar(template: &Template, summary: &StatusSummary) -> WaybarStatus {
+    WaybarStatus {
+        text: template.render(summary),
+        tooltip: summary.tooltip(),
+        class: summary.class().to_string(),
+        percentage: summary.percentage(),
+    }
+}
+
+// ============================================================================
+// 显示辅助
+// ============================================================================
+
+/// 与悬浮窗一致：告警优先，其次按状态
+pub fn icon(task: &Task) -> &'static str {
+    if task.overtime || task.possibly_stalled {
+        return "⚠";
+    }
+    match task.status.as_str() {
+        "running" => "◉",
+        "waiting_input" => "?",
+        "completed" => "✓",
+        "armed" => "◎",
+        "error" => "✗",
+        _ => "○",
+    }
+}
+
+/// 阶段描述，没有时返回 None
+pub fn stage(task: &Task) -> Option<String> {
+    match task.current_stage.as_deref() {
+        Some("__completed__") => Some("Completed".to_string()),
+        Some(stage) if !stage.is_empty() => Some(stage.to_string()),
+        _ => None,
+    }
+}
+
+/// 项目目录名，没有项目路径时用任务名
+pub fn project(task: &Task) -> String {
+    match &task.project_path {
+        Some(path) => last_component(path).to_string(),
+        None => task.name.clone(),
+    }
+}
+
+pub fn last_component(path: &str) -> &str {
+    path.trim_end_matches(['/', '\\'])
+        .rsplit(['/', '\\'])
+        .next()
+        .filter(|s| !s.is_empty())
+        .unwrap_or(path)
+}
+
+/// 运行时间（毫秒），armed 或没有开始时间时返回 None
+pub fn elapsed(task: &Task, now: u64) -> Option<u64> {
+    if task.status == "armed" || task.start_time == 0 {
+        return None;
+    }
+    Some(task.end_time.unwrap_or(now).saturating_sub(task.start_time))
+}
+
+/// m:ss，超过一小时为 h:mm:ss
+pub fn format_duration(ms: u64) -> String {
+    let seconds = ms / 1000;
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+    } else {
+        format!("{}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+    use crate::engine::TaskEngine;
+    use crate::events::NullSink;
+    use crate::{ReportRequest, UpdateStateRequest};
+    use std::sync::Arc;
+
+    /// 在 1_000 时按给定状态创建任务
+    fn tasks(statuses: &[(&str, &str)]) -> Vec<Task> {
+        let engine = TaskEngine::new(Arc::new(ManualClock::new(1_000)), Arc::new(NullSink));
+        for (id, status) in statuses {
+            engine.report(ReportRequest {
+                task_id: id.to_string(),
+                name: id.to_string(),
+                ide: "cursor".to_string(),
+                window_title: id.to_string(),
+                is_focused: false,
+                project_path: Some(format!("/work/{}", id)),
+                active_file: None,
+            });
+            let request = UpdateStateRequest {
+                task_id: id.to_string(),
+                status: Some(status.to_string()),
+                source: Some("hook".to_string()),
+                ..Default::default()
+            };
+            engine.update_state(request).unwrap();
+        }
+        engine.tasks()
+    }
+
+    fn render(format: &str, tasks: &[Task]) -> String {
+        Template::parse(format).unwrap().render(&StatusSummary::new(tasks, 61_000))
+    }
+
+    #[test]
+    fn empty_sections_are_dropped() {
+        assert_eq!(render(DEFAULT_FORMAT, &tasks(&[])), "");
+        assert_eq!(render("[{running}▶ ][{error}✗ ]{stage}", &tasks(&[("a", "error")])), "1✗");
+        // 段内只要有一个字段有值就整段输出，其中为 0 的计数照常显示
+        assert_eq!(render("[{running}/{waiting} waiting]", &tasks(&[("a", "waiting_input")])), "0/1 waiting");
+        // 没有字段的段永远不输出，段外的计数为 0 时显示 0
+        assert_eq!(render("[static]{running} running", &tasks(&[])), "0 running");
+        assert_eq!(render("[[{total}]] {{x}}", &tasks(&[("a", "armed")])), "[1] {x}");
+    }
+
+    #[test]
+    fn counts_follow_task_statuses() {
+        let mut tasks = tasks(&[
+            ("a", "running"),
+            ("b", "running"),
+            ("c", "waiting_input"),
+            ("d", "completed"),
+            ("e", "cancelled"),
+            ("remote", "running"),
+        ]);
+        tasks[5].stale = true;
+        tasks[1].is_focused = true;
+        tasks[1].current_stage = Some("Editing".to_string());
+
+        let format = "{running} {waiting} {done} {error} {cancelled} {total} {project} {stage} {elapsed}";
+        assert_eq!(render(format, &tasks), "2 1 1 0 1 6 b Editing 1:00");
+        let summary = StatusSummary::new(&tasks, 61_000);
+        assert_eq!(summary.class(), "running");
+
+        // 没有运行中的任务时，等待输入的任务成为当前任务
+        let idle: Vec<Task> = tasks.into_iter().filter(|t| t.status != "running").collect();
+        assert_eq!(render("[{running}▶ ][{waiting}? ]{project} {icon}", &idle), "1? c ?");
+        assert_eq!(StatusSummary::new(&idle, 61_000).class(), "done");
+    }
+
+    #[test]
+    fn rejects_malformed_templates() {
+        for format in ["{running", "{nope}", "}", "]", "[", "[[{running}]", "[a[b]]"] {
+            assert!(Template::parse(format).is_err(), "{}", format);
+        }
+    }
+}
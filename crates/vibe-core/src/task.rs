@@ -0,0 +1,286 @@
+use serde::{Deserialize, Serialize};
+
+use crate::git_context::{ChangeSummary, GitContext};
+
+// ============================================================================
+// Task 数据结构
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Task {
+    pub id: String,
+    pub name: String,
+    pub is_focused: bool,
+    pub ide: String,
+    pub window_title: String,
+    pub project_path: Option<String>,
+    pub active_file: Option<String>,
+    pub status: String,
+    pub source: String,
+    pub start_time: u64,
+    pub end_time: Option<u64>,
+    #[serde(default)]
+    pub last_heartbeat: u64,
+    /// 预估总时长（毫秒）
+    #[serde(default)]
+    pub estimated_duration: Option<u64>,
+    /// 当前阶段描述
+    #[serde(default)]
+    pub current_stage: Option<String>,
+    /// 本次运行消耗的 token 数，新的运行开始时清零
+    #[serde(default)]
+    pub tokens: u64,
+    /// 最近一次状态/阶段/预估更新时间（毫秒），插件心跳不计入
+    #[serde(default)]
+    pub last_update: u64,
+    /// 运行时间超出预估时长（由 task_monitor 置位）
+    #[serde(default)]
+    pub overtime: bool,
+    /// 长时间没有任何状态/阶段更新（由 task_monitor 置位）
+    #[serde(default)]
+    pub possibly_stalled: bool,
+    /// project_path 所在 git 仓库的信息，任务开始时解析
+    #[serde(default)]
+    pub git: Option<GitContext>,
+    /// 任务结束时相对开始 commit 的改动统计
+    #[serde(default)]
+    pub change_summary: Option<ChangeSummary>,
+    /// agent 通过 complete_task 提交的完成报告
+    #[serde(default)]
+    pub completion: Option<CompletionReport>,
+    /// 由历史学习得到的预估（estimated_duration 保留 agent 的原始值），仅在输出时计算
+    #[serde(default)]
+    pub estimate: Option<Estimate>,
+    /// 由远程实例转发来的任务所在的主机，本机任务为 None
+    #[serde(default)]
+    pub host: Option<String>,
+    /// 远程实例已断开，任务状态不再更新
+    #[serde(default)]
+    pub stale: bool,
+}
+
+impl Task {
+    /// 进度百分比，由 elapsed / effective_estimated 计算。
+    /// 如果运行时间超过预估时间，预估时间跟随运行时间，进度保持在 99%。
+    pub fn progress(&self, now: u64) -> u32 {
+        if self.status == "completed" {
+            return 100;
+        }
+        let estimated = self
+            .estimate
+            .as_ref()
+            .map(|e| e.duration_ms)
+            .or(self.estimated_duration)
+            .unwrap_or(0);
+        if estimated == 0 || self.start_time == 0 {
+            return 0;
+        }
+        let elapsed = now.saturating_sub(self.start_time);
+        let effective_estimated = estimated.max(elapsed);
+        ((elapsed as f64 / effective_estimated as f64) * 100.0).min(99.0) as u32
+    }
+}
+
+/// 完成报告：markdown 摘要、修改的文件和产物链接
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CompletionReport {
+    pub summary: String,
+    #[serde(default)]
+    pub changed_files: Vec<String>,
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+    #[serde(default)]
+    pub submitted_at: u64,
+}
+
+/// 产物链接，如 PR 地址、测试报告路径、生成的文档
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Artifact {
+    /// pr / test_report / doc / link 等，仅用于展示
+    #[serde(default = "default_artifact_kind")]
+    pub kind: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// URL 或本地文件路径
+    pub url: String,
+}
+
+fn default_artifact_kind() -> String {
+    "link".to_string()
+}
+
+const MAX_SUMMARY_LEN: usize = 64 * 1024;
+const MAX_CHANGED_FILES: usize = 1000;
+const MAX_ARTIFACTS: usize = 50;
+
+impl CompletionReport {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.summary.len() > MAX_SUMMARY_LEN {
+            return Err(format!("summary exceeds {} bytes", MAX_SUMMARY_LEN));
+        }
+        if self.changed_files.len() > MAX_CHANGED_FILES {
+            return Err(format!("changed_files exceeds {} entries", MAX_CHANGED_FILES));
+        }
+        if self.artifacts.len() > MAX_ARTIFACTS {
+            return Err(format!("artifacts exceeds {} entries", MAX_ARTIFACTS));
+        }
+        if self.artifacts.iter().any(|a| a.url.trim().is_empty()) {
+            return Err("artifact url must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EstimateBasis {
+    /// agent 给出的预估，没有可用于修正的历史
+    Agent,
+    /// agent 的预估按该 agent 历史上的实际/预估比例修正
+    AgentCorrected,
+    /// agent 没有给出预估，完全由历史推断
+    History,
+}
+
+/// 预估时长及置信区间（毫秒）
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Estimate {
+    pub duration_ms: u64,
+    /// 区间下界（p25）
+    pub low_ms: u64,
+    /// 区间上界（p75）
+    pub high_ms: u64,
+    /// 参与计算的历史样本数
+    pub samples: u32,
+    pub basis: EstimateBasis,
+}
+
+/// 按路径匹配到多个任务时返回的候选
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MatchCandidate {
+    pub task_id: String,
+    pub ide: String,
+    pub project_path: Option<String>,
+}
+
+// ============================================================================
+// Request 数据结构
+// ============================================================================
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ReportRequest {
+    pub task_id: String,
+    pub name: String,
+    pub ide: String,
+    pub window_title: String,
+    #[serde(default)]
+    pub is_focused: bool,
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub active_file: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct UpdateStateRequest {
+    pub task_id: String,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    /// 预估总时长（毫秒）
+    #[serde(default)]
+    pub estimated_duration: Option<u64>,
+    /// 当前阶段描述
+    #[serde(default)]
+    pub current_stage: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateTokensRequest {
+    pub task_id: String,
+    pub tokens: u64,
+    /// true 时累加到当前计数，否则设置为该值
+    #[serde(default)]
+    pub increment: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResetRequest {
+    #[serde(default)]
+    pub task_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeleteTaskRequest {
+    pub task_id: String,
+}
+
+/// 提交完成报告，通过 task_id 或 project_path（同 update_state_by_path 的匹配规则）定位任务
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CompleteTaskRequest {
+    #[serde(default)]
+    pub task_id: Option<String>,
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub ide: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub changed_files: Vec<String>,
+    #[serde(default)]
+    pub artifacts: Vec<Artifact>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UpdateStateByPathRequest {
+    pub project_path: String,
+    #[serde(default)]
+    pub ide: Option<String>,
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+}
+
+// ============================================================================
+// 状态与来源
+// ============================================================================
+
+pub const VALID_STATUSES: [&str; 6] = ["armed", "running", "waiting_input", "completed", "error", "cancelled"];
+pub const END_STATUSES: [&str; 3] = ["completed", "error", "cancelled"];
+pub const VALID_SOURCES: [&str; 6] = ["hook", "transcript", "mcp", "plugin", "fs", "process"];
+
+/// 来源优先级：hook > transcript > mcp > plugin > fs > process，低优先级来源不能覆盖高优先级来源设置的状态
+///
+/// 数值留有间隔，便于插入新的推断类来源。fs 只是兜底信号，低于 plugin，插件状态未被屏蔽时总能收回任务。
+pub fn source_priority(source: &str) -> u8 {
+    match source {
+        "hook" => 40,
+        "transcript" => 35,
+        "mcp" => 30,
+        "plugin" => 20,
+        "fs" => 15,
+        "process" => 10,
+        _ => 0,
+    }
+}
+
+pub fn can_update_source(current_source: &str, new_source: &str) -> bool {
+    source_priority(new_source) >= source_priority(current_source)
+}
+
+/// 高优先级来源在前，同优先级按 id 排序
+pub fn sort_tasks_by_priority(tasks: &mut [Task]) {
+    tasks.sort_by(|a, b| {
+        let priority_cmp = source_priority(&b.source).cmp(&source_priority(&a.source));
+        if priority_cmp == std::cmp::Ordering::Equal {
+            a.id.cmp(&b.id)
+        } else {
+            priority_cmp
+        }
+    });
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// 操作系统中的一个 IDE 窗口
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IdeWindow {
+    pub bundle_id: String,
+    pub ide: String,
+    pub app_name: String,
+    pub window_title: String,
+    pub window_index: i32,
+    pub pid: String,
+}
+
+/// Match criteria for finding the best window
+#[derive(Debug)]
+pub struct MatchCriteria {
+    pub ide: Option<String>,
+    pub workspace: Option<String>,
+    pub file: Option<String>,
+}
+
+/// 窗口与条件的匹配分数，IDE 不一致或分数不足 100（未指定 IDE）时返回 None
+pub fn match_score(window: &IdeWindow, criteria: &MatchCriteria) -> Option<u32> {
+    let mut score = 0u32;
+    let mut match_reasons: Vec<String> = Vec::new();
+
+    // IDE match (required if specified)
+    if let Some(ref ide) = criteria.ide {
+        if window.ide.to_lowercase() == ide.to_lowercase() {
+            score += 100;
+            match_reasons.push("ide_match(+100)".to_string());
+        } else {
+            debug!("    -> SKIP: IDE mismatch ({} != {})", window.ide, ide);
+            return None;
+        }
+    }
+
+    // Workspace/window_title match (high priority)
+    if let Some(ref workspace) = criteria.workspace {
+        // 精确匹配窗口标题
+        if window.window_title == *workspace {
+            score += 80;
+            match_reasons.push("exact_title_match(+80)".to_string());
+        }
+        // 窗口标题包含workspace
+        else if window.window_title.contains(workspace) {
+            score += 50;
+            match_reasons.push("title_contains_workspace(+50)".to_string());
+        }
+        // workspace包含窗口标题
+        else if workspace.contains(&window.window_title) {
+            score += 45;
+            match_reasons.push("workspace_contains_title(+45)".to_string());
+        }
+        // workspace包含在窗口标题的某部分（处理 "file — Project" 格式）
+        else {
+            for part in window.window_title.split(" — ").map(str::trim) {
+                if part.contains(workspace) {
+                    score += 40;
+                    match_reasons.push(format!("part_contains_workspace(+40): \"{}\"", part));
+                    break;
+                }
+                if workspace.contains(part) && part.len() > 3 {
+                    score += 35;
+                    match_reasons.push(format!("workspace_contains_part(+35): \"{}\"", part));
+                    break;
+                }
+            }
+        }
+    }
+
+    // File match (lower priority)
+    if let Some(ref file) = criteria.file {
+        if window.window_title.contains(file) {
+            score += 20;
+            match_reasons.push("file_match(+20)".to_string());
+        }
+    }
+
+    debug!("    \"{}\" -> Score: {}, Reasons: {:?}", window.window_title, score, match_reasons);
+
+    // 只有IDE匹配时也算候选（score >= 100）
+    (score >= 100).then_some(score)
+}
+
+/// Find the best matching window based on criteria
+/// Priority: ide > workspace > file
+/// 当同一IDE有多个窗口时，优先匹配workspace，然后file
+pub fn find_best_match<'a>(windows: &'a [IdeWindow], criteria: &MatchCriteria) -> Option<&'a IdeWindow> {
+    debug!(
+        "Window matching: ide={:?}, workspace={:?}, file={:?}, windows={}",
+        criteria.ide,
+        criteria.workspace,
+        criteria.file,
+        windows.len()
+    );
+
+    let mut candidates: Vec<(&IdeWindow, u32)> =
+        windows.iter().filter_map(|w| match_score(w, criteria).map(|score| (w, score))).collect();
+
+    // Sort by score descending, then by window_index for stability
+    candidates.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.window_index.cmp(&b.0.window_index)));
+
+    match candidates.first() {
+        Some((window, score)) => debug!("Best match: \"{}\" (score: {})", window.window_title, score),
+        None => debug!("No match found"),
+    }
+
+    candidates.first().map(|(w, _)| *w)
+}
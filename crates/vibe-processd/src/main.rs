@@ -0,0 +1,257 @@
+use clap::Parser;
+use serde_json::json;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use vibe_server::federation::{self, RelayConfig};
+use vibe_server::services::{self, AppDirs};
+use vibe_server::settings::{self, AppSettings, ExternalChange, SettingsChange, SettingsState};
+use vibe_server::settings_profiles::{self, ImportPreview, ProfileList};
+use vibe_server::task_monitor::{self, MonitorConfig};
+use vibe_server::server::Server;
+use vibe_server::{http_server, logging};
+
+/// Headless Vibe Process Bar: runs the HTTP/MCP server, settings and task history without a window or tray
+#[derive(Parser, Debug)]
+#[command(name = "vibe-processd", version, about)]
+struct Args {
+    /// Directory of settings.json and profiles [default: same as the desktop app]
+    #[arg(long, env = "VIBE_CONFIG_DIR")]
+    config_dir: Option<PathBuf>,
+    /// Directory of history.json [default: same as the desktop app]
+    #[arg(long, env = "VIBE_DATA_DIR")]
+    data_dir: Option<PathBuf>,
+    /// Directory of the rotating log files [default: same as the desktop app]
+    #[arg(long, env = "VIBE_LOG_DIR")]
+    log_dir: Option<PathBuf>,
+    /// Listen address, overrides httpHost from settings (e.g. 0.0.0.0 in a container)
+    #[arg(long, env = "VIBE_HOST")]
+    host: Option<String>,
+    /// Listen port, overrides httpPort from settings
+    #[arg(long, env = "VIBE_PORT")]
+    port: Option<u16>,
+    /// Forward tasks to another Vibe Process Bar, e.g. http://host.docker.internal:31415
+    #[arg(long, env = "VIBE_UPSTREAM", requires = "upstream_token")]
+    upstream: Option<String>,
+    /// Bearer token matching federationToken on the upstream instance
+    #[arg(long, env = "VIBE_UPSTREAM_TOKEN", hide_env_values = true)]
+    upstream_token: Option<String>,
+    /// Host name shown upstream [default: $HOSTNAME or /etc/hostname]
+    #[arg(long, env = "VIBE_RELAY_HOST")]
+    relay_host: Option<String>,
+}
+
+fn resolve_dirs(args: &Args) -> Option<AppDirs> {
+    let defaults = AppDirs::default_dirs();
+    let default = |pick: fn(&AppDirs) -> &PathBuf| defaults.as_ref().map(|d| pick(d).clone());
+    Some(AppDirs {
+        config_dir: args.config_dir.clone().or_else(|| default(|d| &d.config_dir))?,
+        data_dir: args.data_dir.clone().or_else(|| default(|d| &d.data_dir))?,
+        log_dir: args.log_dir.clone().or_else(|| default(|d| &d.log_dir))?,
+    })
+}
+
+// ============================================================================
+// Settings
+// ============================================================================
+
+struct Daemon {
+    settings: SettingsState,
+    /// 命令行指定的地址/端口优先于设置文件
+    host: Option<String>,
+    port: Option<u16>,
+    /// services::start 创建的服务实例
+    server: OnceLock<Arc<Server>>,
+}
+
+impl Daemon {
+    fn effective_settings(&self) -> AppSettings {
+        let mut settings = self.settings.get_settings();
+        if let Some(host) = &self.host {
+            settings.http_host = host.clone();
+        }
+        if let Some(port) = self.port {
+            settings.http_port = port;
+        }
+        settings
+    }
+
+    /// 应用设置 patch 并同步到后台服务；`persist` 为 false 时不写文件（外部修改过的 settings.json）
+    fn apply_settings_patch(&self, patch: &serde_json::Value, persist: bool) -> Result<SettingsChange, String> {
+        let mut change = self.settings.patch(patch, None, persist)?;
+        if self.host.is_some() {
+            change.changed.remove("httpHost");
+        }
+        if self.port.is_some() {
+            change.changed.remove("httpPort");
+        }
+        if let Some(server) = self.server.get().filter(|_| !change.changed.is_empty()) {
+            services::apply_change(server, &self.effective_settings(), &change);
+        }
+        Ok(change)
+    }
+
+    fn import_settings_value(
+        &self,
+        value: serde_json::Value,
+        dry_run: bool,
+        profile: Option<&str>,
+    ) -> Result<ImportPreview, String> {
+        let current = self.settings.get_settings();
+        let (mut patch, preview) = settings_profiles::prepare_import(&current, value)?;
+        if !dry_run {
+            if let Some(name) = profile {
+                patch["activeProfile"] = json!(name);
+            }
+            self.apply_settings_patch(&patch, true)?;
+            info!(changed = preview.changes.len(), profile = ?profile, "Settings imported");
+        }
+        Ok(preview)
+    }
+
+    fn profiles_dir(&self) -> PathBuf {
+        settings_profiles::profiles_dir(&self.settings.path)
+    }
+}
+
+impl http_server::SettingsBridge for Daemon {
+    fn export(&self) -> Result<serde_json::Value, String> {
+        Ok(settings_profiles::export_value(&self.settings.get_settings()))
+    }
+
+    fn import(&self, settings: serde_json::Value, dry_run: bool) -> Result<serde_json::Value, String> {
+        let preview = self.import_settings_value(settings, dry_run, None)?;
+        serde_json::to_value(preview).map_err(|e| e.to_string())
+    }
+
+    fn list_profiles(&self) -> Result<serde_json::Value, String> {
+        let list = ProfileList {
+            profiles: settings_profiles::list_profiles(&self.profiles_dir()),
+            active: self.settings.get_settings().active_profile,
+        };
+        serde_json::to_value(list).map_err(|e| e.to_string())
+    }
+
+    fn activate_profile(&self, name: &str) -> Result<serde_json::Value, String> {
+        let value = settings_profiles::load_profile(&self.profiles_dir(), name)?;
+        let preview = self.import_settings_value(value, false, Some(name))?;
+        serde_json::to_value(preview).map_err(|e| e.to_string())
+    }
+}
+
+// ============================================================================
+// Shutdown
+// ============================================================================
+
+#[cfg(unix)]
+async fn shutdown_signal() -> &'static str {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut terminate = signal(SignalKind::terminate()).expect("Failed to install SIGTERM handler");
+    tokio::select! {
+        _ = terminate.recv() => "SIGTERM",
+        _ = tokio::signal::ctrl_c() => "SIGINT",
+    }
+}
+
+#[cfg(not(unix))]
+async fn shutdown_signal() -> &'static str {
+    let _ = tokio::signal::ctrl_c().await;
+    "Ctrl-C"
+}
+
+/// 阻塞直到收到 SIGTERM / SIGINT；server 绑定失败或异常退出时返回失败，交给 systemd 重启
+fn wait_for_shutdown(server: &Server) -> ExitCode {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .expect("Failed to create Tokio runtime");
+
+    runtime.block_on(async {
+        let signal = shutdown_signal();
+        tokio::pin!(signal);
+        let mut check = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            tokio::select! {
+                name = &mut signal => {
+                    info!("Received {}, shutting down", name);
+                    return ExitCode::SUCCESS;
+                }
+                _ = check.tick() => {
+                    if !server.http.is_running() {
+                        error!("HTTP server is not running, exiting");
+                        return ExitCode::FAILURE;
+                    }
+                }
+            }
+        }
+    })
+}
+
+// ============================================================================
+// Main
+// ============================================================================
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    logging::init();
+
+    let Some(dirs) = resolve_dirs(&args) else {
+        error!("Could not determine the config/data/log directories; pass --config-dir, --data-dir and --log-dir");
+        return ExitCode::FAILURE;
+    };
+
+    let daemon = Arc::new(Daemon {
+        settings: SettingsState::load(dirs.settings_path()),
+        host: args.host,
+        port: args.port,
+        server: OnceLock::new(),
+    });
+
+    let server = services::start(&daemon.effective_settings(), &dirs);
+    server.set_settings_bridge(daemon.clone());
+    let _ = daemon.server.set(server.clone());
+
+    if let (Some(upstream), Some(token)) = (args.upstream, args.upstream_token) {
+        server.start_relay(RelayConfig {
+            upstream,
+            token,
+            host: args.relay_host.unwrap_or_else(federation::default_host_name),
+        });
+    }
+
+    // 告警只记录到日志（scan 中已输出），overtime / possibly_stalled 标记通过 API 返回
+    let monitor = daemon.clone();
+    task_monitor::start_monitor(
+        &server,
+        move || MonitorConfig::from(&monitor.settings.get_settings()),
+        |_| {},
+    );
+
+    // 监听 settings.json 的外部修改（包括同一台机器上桌面应用的修改）
+    let current = daemon.clone();
+    let changed = daemon.clone();
+    settings::watch_settings_file(
+        daemon.settings.path.clone(),
+        move || current.settings.get_settings(),
+        move |change| match change {
+            ExternalChange::Valid(patch) => {
+                if let Err(e) = changed.apply_settings_patch(&serde_json::Value::Object(patch), false) {
+                    error!(error = %e, "Failed to apply external settings change");
+                }
+            }
+            ExternalChange::Invalid(warning) => {
+                warn!(keys = ?warning.keys, "Rejected external settings edit");
+            }
+        },
+    );
+
+    let code = wait_for_shutdown(&server);
+    server.federation.stop_relay();
+    server.http.stop();
+    info!("vibe-processd stopped");
+    code
+}
@@ -0,0 +1,187 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tracing::info;
+use vibe_core::status_line::last_component;
+use vibe_core::{EngineError, ReportRequest, TaskEngine, UpdateStateRequest, END_STATUSES};
+
+// ============================================================================
+// Agent 会话：把 CLI agent 的会话（hook、ingest 适配器）映射到任务
+// ============================================================================
+//
+// 会话以 (ide, key) 标识，hook、ingest 和 transcript 使用相同的键，同一会话只对应一个任务。优先复用项目路径所在项目的插件任务（agent 在 IDE 终端里运行时），
+// 否则新建一个 `<ide>-<key>` 任务，会话结束或超时后删除。
+
+/// 会话超过该时间没有事件时移除映射，并删除为它新建的任务
+pub const SESSION_TTL_MS: u64 = 2 * 60 * 60 * 1000;
+const SOURCE: &str = "hook";
+const MAX_KEY_LEN: usize = 128;
+
+struct Session {
+    task_id: String,
+    /// 任务由会话新建（而不是复用的插件任务），会话结束时一并删除
+    owned: bool,
+    last_event: u64,
+}
+
+/// 新建任务的 id；key 中 `:`（远程任务前缀）、`/` 等字符替换为 `_`
+fn owned_task_id(ide: &str, key: &str) -> String {
+    let key: String = key
+        .chars()
+        .take(MAX_KEY_LEN)
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '_' })
+        .collect();
+    format!("{}-{}", ide, key)
+}
+
+/// 会话到任务的映射
+#[derive(Default)]
+pub struct AgentSessions {
+    sessions: Mutex<HashMap<(String, String), Session>>,
+}
+
+impl AgentSessions {
+    /// 会话对应的任务 id：已有映射且任务仍在时直接使用，否则按项目路径匹配或新建
+    ///
+    /// source 为新建任务的来源，之后同一来源的状态更新不会因优先级被忽略。
+    pub fn resolve(
+        &self,
+        engine: &TaskEngine,
+        ide: &str,
+        key: &str,
+        project_path: Option<&str>,
+        source: &str,
+        now: u64,
+    ) -> Result<String, EngineError> {
+        let session_key = (ide.to_string(), key.to_string());
+        if let Some(task_id) = mapped(&mut self.sessions.lock().unwrap(), engine, &session_key, now) {
+            return Ok(task_id);
+        }
+        // 路径匹配会访问文件系统，在锁外完成
+        let found = project_path.and_then(|path| engine.find_task_id_by_path(path, None).ok());
+
+        let mut sessions = self.sessions.lock().unwrap();
+        // 匹配期间同一会话的另一个事件可能已经建立了映射
+        if let Some(task_id) = mapped(&mut sessions, engine, &session_key, now) {
+            return Ok(task_id);
+        }
+        // 匹配不到、有歧义或任务已被其他会话使用时新建：同一项目里同时运行的两个会话不能共用一个插件任务，
+        // 否则先结束的会话会把任务交还给插件
+        let found = found.filter(|id| !sessions.values().any(|s| s.task_id == *id));
+        let (task_id, owned) = match found {
+            Some(task_id) => (task_id, false),
+            None => {
+                let task_id = owned_task_id(ide, key);
+                let name = last_component(project_path.unwrap_or(key)).to_string();
+                engine.register(
+                    ReportRequest {
+                        task_id: task_id.clone(),
+                        name: name.clone(),
+                        ide: ide.to_string(),
+                        window_title: name,
+                        is_focused: false,
+                        project_path: project_path.map(str::to_string),
+                        active_file: None,
+                    },
+                    source,
+                )?;
+                (task_id, true)
+            }
+        };
+        info!(ide = %ide, key = %key, task_id = %task_id, "Agent session mapped");
+        sessions.insert(session_key, Session { task_id: task_id.clone(), owned, last_event: now });
+        Ok(task_id)
+    }
+
+    /// 会话结束：删除新建的任务，或把复用的任务交还给插件
+    pub fn end(&self, engine: &TaskEngine, ide: &str, key: &str) {
+        let session = self.sessions.lock().unwrap().remove(&(ide.to_string(), key.to_string()));
+        if let Some(session) = session {
+            info!(ide = %ide, key = %key, task_id = %session.task_id, "Agent session ended");
+            release_task(engine, &session);
+        }
+    }
+
+    /// 清理超时的会话；由请求和任务列表读取时顺带调用
+    pub fn expire(&self, engine: &TaskEngine, now: u64) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let expired: Vec<(String, String)> = sessions
+            .iter()
+            .filter(|(_, s)| now.saturating_sub(s.last_event) > SESSION_TTL_MS)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for session_key in expired {
+            if let Some(session) = sessions.remove(&session_key) {
+                info!(ide = %session_key.0, key = %session_key.1, task_id = %session.task_id, "Agent session expired");
+                release_task(engine, &session);
+            }
+        }
+    }
+}
+
+/// 已有映射且任务仍在时返回任务 id，并刷新会话的活动时间
+fn mapped(
+    sessions: &mut HashMap<(String, String), Session>,
+    engine: &TaskEngine,
+    session_key: &(String, String),
+    now: u64,
+) -> Option<String> {
+    let session = sessions.get_mut(session_key)?;
+    engine.get(&session.task_id)?;
+    session.last_event = now;
+    Some(session.task_id.clone())
+}
+
+/// 删除会话新建的任务；复用的插件任务交还给插件（来源改回 plugin，未结束的运行重置为 armed）
+///
+/// 未结束的自建任务先标记为 cancelled，记入历史。
+fn release_task(engine: &TaskEngine, session: &Session) {
+    if !session.owned {
+        let _ = engine.release_to_plugin(&session.task_id);
+        return;
+    }
+    let Some(task) = engine.get(&session.task_id) else { return };
+    if task.start_time > 0 && !END_STATUSES.contains(&task.status.as_str()) {
+        let _ = engine.update_state(UpdateStateRequest {
+            task_id: task.id.clone(),
+            status: Some("cancelled".to_string()),
+            source: Some(SOURCE.to_string()),
+            ..Default::default()
+        });
+    }
+    let _ = engine.delete(&task.id);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use vibe_core::{ManualClock, NullSink};
+
+    #[test]
+    fn concurrent_sessions_do_not_share_a_plugin_task() {
+        let engine = TaskEngine::new(Arc::new(ManualClock::new(1_000)), Arc::new(NullSink));
+        engine.report(ReportRequest {
+            task_id: "plugin-app".to_string(),
+            name: "app".to_string(),
+            ide: "cursor".to_string(),
+            window_title: "app".to_string(),
+            is_focused: false,
+            project_path: Some("/work/app".to_string()),
+            active_file: None,
+        });
+        let sessions = AgentSessions::default();
+
+        let first = sessions.resolve(&engine, "claude-code", "s1", Some("/work/app"), SOURCE, 1_000).unwrap();
+        let second = sessions.resolve(&engine, "claude-code", "s2", Some("/work/app"), SOURCE, 1_000).unwrap();
+        assert_eq!((first.as_str(), second.as_str()), ("plugin-app", "claude-code-s2"));
+        assert_eq!(sessions.resolve(&engine, "claude-code", "s1", None, SOURCE, 2_000).unwrap(), first);
+
+        // 先结束的会话只交还自己复用的任务
+        sessions.end(&engine, "claude-code", "s1");
+        assert_eq!(engine.get("plugin-app").unwrap().source, "plugin");
+        assert!(engine.get("claude-code-s2").is_some());
+
+        sessions.end(&engine, "claude-code", "s2");
+        assert!(engine.get("claude-code-s2").is_none());
+    }
+}
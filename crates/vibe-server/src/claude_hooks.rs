@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use tracing::debug;
+use vibe_core::status_line::last_component;
+use vibe_core::{EngineError, TaskEngine, UpdateOutcome, UpdateStateRequest};
+
+use crate::agent_sessions::AgentSessions;
+
+// ============================================================================
+// Claude Code hooks：直接接收 Claude Code（及兼容 CLI）hook 写到 stdin 的 JSON
+// ============================================================================
+//
+// 每个 session_id 对应一个任务（见 agent_sessions），新建的任务 id 为 `claude-code-<session_id>`。
+
+const HOOK_IDE: &str = "claude-code";
+const SOURCE: &str = "hook";
+pub const UNSUPPORTED_EVENT: &str = "unsupported_event";
+pub const NOT_RUNNING: &str = "not_running";
+
+/// hook 输入，只取用到的字段，其余字段忽略
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct HookEvent {
+    pub hook_event_name: String,
+    pub session_id: String,
+    pub cwd: String,
+    pub transcript_path: Option<String>,
+    pub tool_name: Option<String>,
+    pub tool_input: Option<serde_json::Value>,
+    /// Notification 的提示文本
+    pub message: Option<String>,
+}
+
+/// 处理一个 hook 事件，返回对应任务的更新结果
+pub fn handle(engine: &TaskEngine, sessions: &AgentSessions, event: HookEvent) -> Result<UpdateOutcome, EngineError> {
+    if event.session_id.trim().is_empty() || event.cwd.trim().is_empty() {
+        return Err(EngineError::BadRequest("session_id and cwd are required".to_string()));
+    }
+    let now = engine.now();
+    sessions.expire(engine, now);
+
+    if event.hook_event_name == "SessionEnd" {
+        sessions.end(engine, HOOK_IDE, &event.session_id);
+        return Ok(UpdateOutcome::Applied);
+    }
+
+    let task_id = sessions.resolve(engine, HOOK_IDE, &event.session_id, Some(&event.cwd), SOURCE, now)?;
+    debug!(task_id = %task_id, event = %event.hook_event_name, transcript = ?event.transcript_path, "Hook event");
+    let (status, stage) = match event.hook_event_name.as_str() {
+        "SessionStart" => return Ok(UpdateOutcome::Applied),
+        "UserPromptSubmit" | "SubagentStop" => ("running", None),
+        "PreToolUse" | "PostToolUse" => {
+            let stage = event.tool_name.as_deref().map(|name| tool_stage(name, event.tool_input.as_ref()));
+            ("running", stage)
+        }
+        // 只有运行中的任务才切换为等待输入；Stop 之后的空闲提醒不覆盖 completed
+        "Notification" => {
+            if engine.get(&task_id).is_none_or(|t| t.status != "running") {
+                return Ok(UpdateOutcome::Ignored(NOT_RUNNING));
+            }
+            ("waiting_input", event.message.clone().filter(|m| !m.trim().is_empty()))
+        }
+        "Stop" => ("completed", None),
+        other => {
+            debug!(event = %other, "Ignoring unsupported hook event");
+            return Ok(UpdateOutcome::Ignored(UNSUPPORTED_EVENT));
+        }
+    };
+
+    engine.update_state(UpdateStateRequest {
+        task_id,
+        status: Some(status.to_string()),
+        source: Some(SOURCE.to_string()),
+        estimated_duration: None,
+        current_stage: stage,
+    })
+}
+
+/// 阶段描述：工具名，文件类工具附上文件名，MCP 工具显示为 `server/tool`
+pub fn tool_stage(tool_name: &str, tool_input: Option<&serde_json::Value>) -> String {
+    if let Some(rest) = tool_name.strip_prefix("mcp__") {
+        return rest.replacen("__", "/", 1);
+    }
+    let file = tool_input
+        .and_then(|input| input.get("file_path").or_else(|| input.get("notebook_path")))
+        .and_then(|v| v.as_str())
+        .map(last_component);
+    match file {
+        Some(file) => format!("{} {}", tool_name, file),
+        None => tool_name.to_string(),
+    }
+}
@@ -0,0 +1,342 @@
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+use vibe_core::{Estimate, EstimateBasis, Task, TimelineEvent, TimelineEventKind};
+
+use crate::history::{History, HistoryEntry};
+use crate::timeline::Timelines;
+
+/// 每一级匹配至少需要的历史样本数，不足时退到更宽泛的一级
+const MIN_SAMPLES: usize = 3;
+/// 每一级最多使用的样本数（取最近的），让估计跟随近期的工作节奏
+const MAX_SAMPLES: usize = 50;
+/// 对 agent 预估的修正倍数范围，避免个别异常样本把预估拉得过于离谱
+const MIN_RATIO: f64 = 0.25;
+const MAX_RATIO: f64 = 4.0;
+/// 阶段关键词中忽略的常见词
+const STOPWORDS: &[&str] = &["the", "and", "for", "with", "from", "into", "completed"];
+
+/// 一次已完成运行
+struct Sample {
+    duration: u64,
+    project: Option<String>,
+    ide: String,
+    source: String,
+    agent_estimate: Option<u64>,
+    keywords: HashSet<String>,
+}
+
+/// 当前任务的匹配维度
+struct Key {
+    project: Option<String>,
+    ide: String,
+    source: String,
+    keywords: HashSet<String>,
+}
+
+/// 匹配层级：项目+IDE+来源 → 项目+IDE → 项目 → IDE+来源 → 全部
+const TIERS: usize = 5;
+
+impl Key {
+    fn matches(&self, tier: usize, sample: &Sample) -> bool {
+        let project = self.project.is_some() && sample.project == self.project;
+        match tier {
+            0 => project && sample.ide == self.ide && sample.source == self.source,
+            1 => project && sample.ide == self.ide,
+            2 => project,
+            3 => sample.ide == self.ide && sample.source == self.source,
+            _ => true,
+        }
+    }
+}
+
+fn project_key(task: &Task) -> Option<String> {
+    task.git
+        .as_ref()
+        .map(|g| g.repo_root.clone())
+        .or_else(|| task.project_path.clone())
+        .map(|p| p.trim_end_matches(['/', '\\']).to_lowercase())
+        .filter(|p| !p.is_empty())
+}
+
+/// 把阶段描述拆成关键词，如 "Running cargo tests" → {running, cargo, tests}
+fn keywords<'a>(stages: impl Iterator<Item = &'a str>) -> HashSet<String> {
+    stages
+        .flat_map(|stage| stage.split(|c: char| !c.is_alphanumeric()))
+        .filter(|word| word.chars().count() >= 3)
+        .map(|word| word.to_lowercase())
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .collect()
+}
+
+fn stage_names(events: &[TimelineEvent]) -> impl Iterator<Item = &str> {
+    events.iter().filter_map(|e| match &e.kind {
+        TimelineEventKind::Stage { stage } => stage.as_deref(),
+        _ => None,
+    })
+}
+
+fn sample_from(entry: &HistoryEntry) -> Option<Sample> {
+    let task = &entry.task;
+    if task.status != "completed" || task.start_time == 0 {
+        return None;
+    }
+    let duration = task.end_time?.checked_sub(task.start_time).filter(|d| *d > 0)?;
+    Some(Sample {
+        duration,
+        project: project_key(task),
+        ide: task.ide.clone(),
+        source: task.source.clone(),
+        agent_estimate: task.estimated_duration.filter(|d| *d > 0),
+        keywords: keywords(stage_names(&entry.timeline).chain(task.current_stage.as_deref())),
+    })
+}
+
+/// 从最具体的一级开始选样本；同一级内有足够多阶段关键词重合的样本时优先用它们
+fn select<'a>(samples: &'a [Sample], key: &Key, filter: impl Fn(&Sample) -> bool) -> Option<Vec<&'a Sample>> {
+    for tier in 0..TIERS {
+        let pool: Vec<&Sample> = samples.iter().filter(|s| key.matches(tier, s) && filter(s)).collect();
+        if pool.len() < MIN_SAMPLES {
+            continue;
+        }
+        if !key.keywords.is_empty() {
+            let related: Vec<&Sample> = pool
+                .iter()
+                .filter(|s| !s.keywords.is_disjoint(&key.keywords))
+                .take(MAX_SAMPLES)
+                .copied()
+                .collect();
+            if related.len() >= MIN_SAMPLES {
+                return Some(related);
+            }
+        }
+        return Some(pool.into_iter().take(MAX_SAMPLES).collect());
+    }
+    None
+}
+
+/// 已排序数据的分位数（线性插值）
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+    let pos = (sorted.len() - 1) as f64 * q;
+    let (lower, upper) = (pos.floor() as usize, pos.ceil() as usize);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+fn sorted(values: impl Iterator<Item = f64>) -> Vec<f64> {
+    let mut values: Vec<f64> = values.collect();
+    values.sort_by(|a, b| a.total_cmp(b));
+    values
+}
+
+/// 由历史耗时推断；任务已运行 elapsed 时只看比它更长的运行（条件分布）
+fn from_history(samples: &[Sample], key: &Key, elapsed: u64) -> Option<Estimate> {
+    let pool = select(samples, key, |s| s.duration > elapsed)?;
+    let durations = sorted(pool.iter().map(|s| s.duration as f64));
+    Some(Estimate {
+        duration_ms: quantile(&durations, 0.5) as u64,
+        low_ms: quantile(&durations, 0.25) as u64,
+        high_ms: quantile(&durations, 0.75) as u64,
+        samples: pool.len() as u32,
+        basis: EstimateBasis::History,
+    })
+}
+
+/// 按同类任务历史上 实际耗时 / agent 预估 的比例修正 agent 的预估
+fn correct_agent(samples: &[Sample], key: &Key, agent: u64) -> Estimate {
+    let Some(pool) = select(samples, key, |s| s.agent_estimate.is_some()) else {
+        return Estimate {
+            duration_ms: agent,
+            low_ms: agent,
+            high_ms: agent,
+            samples: 0,
+            basis: EstimateBasis::Agent,
+        };
+    };
+    let ratios = sorted(pool.iter().filter_map(|s| {
+        let estimate = s.agent_estimate? as f64;
+        Some((s.duration as f64 / estimate).clamp(MIN_RATIO, MAX_RATIO))
+    }));
+    let scale = |q: f64| (agent as f64 * quantile(&ratios, q)) as u64;
+    Estimate {
+        duration_ms: scale(0.5),
+        low_ms: scale(0.25),
+        high_ms: scale(0.75),
+        samples: pool.len() as u32,
+        basis: EstimateBasis::AgentCorrected,
+    }
+}
+
+/// 由历史推断运行中任务的时长
+#[derive(Default)]
+pub struct Estimator {
+    /// 从历史中提取的样本，按 History::revision() 缓存
+    samples: Mutex<(u64, Arc<Vec<Sample>>)>,
+}
+
+impl Estimator {
+    /// 最近的样本在前
+    fn samples(&self, history: &History) -> Arc<Vec<Sample>> {
+        let mut cache = self.samples.lock().unwrap();
+        let revision = history.revision();
+        if cache.0 != revision {
+            let samples = history.entries(None).iter().filter_map(sample_from).collect();
+            *cache = (revision, Arc::new(samples));
+        }
+        cache.1.clone()
+    }
+
+    /// 计算运行中任务的预估时长。
+    /// agent 给出了 estimated_duration 时按历史修正；没有给出时由历史推断。
+    /// 运行时间已超过区间上界时，改用历史上比当前运行更久的那些运行重新估计。
+    pub fn estimate(&self, history: &History, timelines: &Timelines, task: &Task, now: u64) -> Option<Estimate> {
+        let samples = self.samples(history);
+        let key = key_for(timelines, task);
+        let elapsed = if task.start_time > 0 { now.saturating_sub(task.start_time) } else { 0 };
+
+        match task.estimated_duration.filter(|d| *d > 0) {
+            Some(agent) => {
+                let corrected = correct_agent(&samples, &key, agent);
+                if elapsed > corrected.high_ms {
+                    if let Some(updated) = from_history(&samples, &key, elapsed) {
+                        return Some(updated);
+                    }
+                }
+                Some(corrected)
+            }
+            None => from_history(&samples, &key, elapsed),
+        }
+    }
+
+    /// agent 没有给出 estimated_duration 时由历史学到的默认时长（同类运行的中位数），用于超时告警。
+    /// 与 estimate 不同，不随已运行时间调整，否则预估总会超过运行时间
+    pub fn learned_duration(&self, history: &History, timelines: &Timelines, task: &Task) -> Option<u64> {
+        let samples = self.samples(history);
+        from_history(&samples, &key_for(timelines, task), 0).map(|e| e.duration_ms)
+    }
+}
+
+fn key_for(timelines: &Timelines, task: &Task) -> Key {
+    let live_stages = timelines.since(&task.id, task.start_time);
+    Key {
+        project: project_key(task),
+        ide: task.ide.clone(),
+        source: task.source.clone(),
+        keywords: keywords(stage_names(&live_stages).chain(task.current_stage.as_deref())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vibe_core::{ManualClock, NullSink, ReportRequest, TaskEngine, UpdateStateRequest};
+
+    fn sample(duration: u64, project: &str, ide: &str, agent_estimate: Option<u64>) -> Sample {
+        Sample {
+            duration,
+            project: Some(project.to_string()),
+            ide: ide.to_string(),
+            source: "hook".to_string(),
+            agent_estimate,
+            keywords: HashSet::new(),
+        }
+    }
+
+    fn key(project: &str, ide: &str) -> Key {
+        Key {
+            project: Some(project.to_string()),
+            ide: ide.to_string(),
+            source: "hook".to_string(),
+            keywords: HashSet::new(),
+        }
+    }
+
+    #[test]
+    fn quantiles_interpolate_between_samples() {
+        let values = [1.0, 2.0, 3.0, 4.0];
+        assert_eq!(quantile(&values, 0.5), 2.5);
+        assert_eq!(quantile(&values, 0.25), 1.75);
+        assert_eq!(quantile(&[7.0], 0.75), 7.0);
+    }
+
+    #[test]
+    fn falls_back_to_wider_tiers_when_samples_are_few() {
+        let mut samples = vec![
+            sample(1_000, "/app", "cursor", None),
+            sample(1_000, "/app", "cursor", None),
+            sample(60_000, "/other", "cursor", None),
+            sample(60_000, "/other", "cursor", None),
+            sample(60_000, "/other", "cursor", None),
+        ];
+        // 同一项目只有两次运行，退到同一 IDE 和来源
+        let estimate = from_history(&samples, &key("/app", "cursor"), 0).unwrap();
+        assert_eq!((estimate.duration_ms, estimate.samples), (60_000, 5));
+
+        // 第三次运行后使用同一项目的样本
+        samples.push(sample(2_000, "/app", "cursor", None));
+        let estimate = from_history(&samples, &key("/app", "cursor"), 0).unwrap();
+        assert_eq!((estimate.duration_ms, estimate.samples), (1_000, 3));
+
+        // 其他 IDE 退到全部样本
+        let estimate = from_history(&samples, &key("/elsewhere", "vscode"), 0).unwrap();
+        assert_eq!(estimate.samples, 6);
+
+        // 已运行时间超过所有样本时没有预估
+        assert!(from_history(&samples, &key("/app", "cursor"), 60_000).is_none());
+    }
+
+    #[test]
+    fn agent_correction_is_clamped() {
+        let key = key("/app", "cursor");
+        let slow: Vec<Sample> = (0..3).map(|_| sample(100_000, "/app", "cursor", Some(1_000))).collect();
+        let estimate = correct_agent(&slow, &key, 10_000);
+        assert_eq!(estimate.duration_ms, (10_000.0 * MAX_RATIO) as u64);
+        assert!(matches!(estimate.basis, EstimateBasis::AgentCorrected));
+
+        let fast: Vec<Sample> = (0..3).map(|_| sample(1, "/app", "cursor", Some(1_000))).collect();
+        assert_eq!(correct_agent(&fast, &key, 10_000).duration_ms, (10_000.0 * MIN_RATIO) as u64);
+
+        // 没有带 agent 预估的历史时原样使用
+        let estimate = correct_agent(&[], &key, 10_000);
+        assert_eq!((estimate.duration_ms, estimate.samples), (10_000, 0));
+        assert!(matches!(estimate.basis, EstimateBasis::Agent));
+    }
+
+    #[test]
+    fn learned_duration_does_not_follow_elapsed_time() {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let engine = TaskEngine::new(clock.clone(), Arc::new(NullSink));
+        let (history, timelines) = (History::default(), Timelines::default());
+        let report = ReportRequest {
+            task_id: "a".to_string(),
+            name: "app".to_string(),
+            ide: "cursor".to_string(),
+            window_title: "app".to_string(),
+            is_focused: false,
+            project_path: Some("/app".to_string()),
+            active_file: None,
+        };
+        engine.register(report, "hook").unwrap();
+        let set_status = |status: &str| {
+            let request = UpdateStateRequest {
+                task_id: "a".to_string(),
+                status: Some(status.to_string()),
+                source: Some("hook".to_string()),
+                ..Default::default()
+            };
+            engine.update_state(request).unwrap();
+        };
+        for _ in 0..3 {
+            set_status("running");
+            clock.advance(60_000);
+            set_status("completed");
+            history.record(engine.get("a").unwrap(), Vec::new(), engine.now());
+            clock.advance(1_000);
+        }
+
+        set_status("running");
+        clock.advance(600_000);
+        let task = engine.get("a").unwrap();
+        let estimator = Estimator::default();
+        assert!(estimator.estimate(&history, &timelines, &task, engine.now()).is_none());
+        assert_eq!(estimator.learned_duration(&history, &timelines, &task), Some(60_000));
+    }
+}
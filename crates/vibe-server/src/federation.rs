@@ -0,0 +1,315 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use vibe_core::Task;
+
+// ============================================================================
+// 联邦：远程实例（devcontainer、WSL、SSH 主机上的 vibe-processd）把任务推送到本机
+// ============================================================================
+
+/// 超过该时间没有收到推送，远程任务标记为 stale
+pub const STALE_AFTER_MS: u64 = 10_000;
+/// 断开超过该时间后移除该主机的任务
+pub const EXPIRE_AFTER_MS: u64 = 10 * 60 * 1000;
+/// 任务没有变化时也按该间隔推送，作为心跳
+const RELAY_HEARTBEAT: Duration = Duration::from_secs(2);
+const RELAY_POLL: Duration = Duration::from_millis(500);
+const RELAY_MAX_BACKOFF: Duration = Duration::from_secs(10);
+const MAX_HOST_LEN: usize = 64;
+const MAX_PUSHED_TASKS: usize = 500;
+
+/// 远程项目路径前缀到本机路径的映射
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PathMapping {
+    /// 只对该主机生效，为空时对所有主机生效
+    pub host: String,
+    pub remote: String,
+    pub local: String,
+}
+
+impl PathMapping {
+    pub fn is_valid(&self) -> bool {
+        !self.remote.trim().is_empty() && !self.local.trim().is_empty()
+    }
+
+    /// 按路径分量匹配前缀并替换，本机路径使用 `\` 时剩余部分也转换为 `\`
+    fn apply(&self, host: &str, path: &str) -> Option<String> {
+        if !self.host.is_empty() && self.host != host {
+            return None;
+        }
+        let remote = self.remote.trim().trim_end_matches(['/', '\\']);
+        let rest = path.strip_prefix(remote)?;
+        if !(rest.is_empty() || rest.starts_with(['/', '\\'])) {
+            return None;
+        }
+        let local = self.local.trim().trim_end_matches(['/', '\\']);
+        let rest = if local.contains('\\') { rest.replace('/', "\\") } else { rest.to_string() };
+        Some(format!("{}{}", local, rest))
+    }
+}
+
+/// 远程实例推送的任务快照
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RelayPush {
+    pub host: String,
+    pub tasks: Vec<Task>,
+    /// 远程实例正常退出，保留上一次的任务并立即标记为 stale（此时 tasks 为空）
+    #[serde(default)]
+    pub closing: bool,
+}
+
+struct RemoteHost {
+    tasks: Vec<Task>,
+    last_push: u64,
+    closed: bool,
+}
+
+#[derive(Default)]
+struct ReceiverConfig {
+    /// 为空时不接收推送
+    token: String,
+    mappings: Vec<PathMapping>,
+}
+
+/// 接收远程实例推送的任务，以及把本机任务转发到上游
+#[derive(Default)]
+pub struct Federation {
+    receiver: Mutex<ReceiverConfig>,
+    remote_hosts: Mutex<HashMap<String, RemoteHost>>,
+    /// 当前运行中的转发线程的停止标记
+    relay: Mutex<Option<(Arc<AtomicBool>, std::thread::JoinHandle<()>)>>,
+}
+
+// ============================================================================
+// Receiver
+// ============================================================================
+
+#[derive(Debug, PartialEq)]
+pub enum PushError {
+    /// 本机没有设置 federationToken
+    Disabled,
+    Unauthorized,
+    BadRequest(String),
+}
+
+/// 逐字节比较，耗时与内容无关
+fn token_matches(expected: &str, provided: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), provided.as_bytes());
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+impl Federation {
+    /// 由设置同步（federationToken / federationPathMappings）
+    pub fn configure(&self, token: &str, mappings: &[PathMapping]) {
+        let mut receiver = self.receiver.lock().unwrap();
+        receiver.token = token.trim().to_string();
+        receiver.mappings = mappings.iter().filter(|m| m.is_valid()).cloned().collect();
+    }
+
+    /// 校验 Bearer token（federationToken），设置路由也使用同一个 token
+    pub fn authorize(&self, bearer: Option<&str>) -> Result<(), PushError> {
+        let receiver = self.receiver.lock().unwrap();
+        if receiver.token.is_empty() {
+            return Err(PushError::Disabled);
+        }
+        if !bearer.is_some_and(|t| token_matches(&receiver.token, t)) {
+            return Err(PushError::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// 校验 Bearer token 并保存远程实例的任务快照
+    pub fn receive(&self, bearer: Option<&str>, push: RelayPush, now: u64) -> Result<(), PushError> {
+        self.authorize(bearer)?;
+
+        let host = push.host.trim().to_string();
+        if host.is_empty() || host.len() > MAX_HOST_LEN || host.contains([':', '/']) {
+            return Err(PushError::BadRequest(format!(
+                "host must be 1-{} characters without ':' or '/'",
+                MAX_HOST_LEN
+            )));
+        }
+        if push.tasks.len() > MAX_PUSHED_TASKS {
+            return Err(PushError::BadRequest(format!("tasks exceeds {} entries", MAX_PUSHED_TASKS)));
+        }
+
+        let mut hosts = self.remote_hosts.lock().unwrap();
+        if push.closing {
+            if let Some(remote) = hosts.get_mut(&host) {
+                remote.closed = true;
+                info!(host = %host, "Remote host disconnected");
+            }
+            return Ok(());
+        }
+
+        let previous = hosts.insert(host.clone(), RemoteHost { tasks: push.tasks, last_push: now, closed: false });
+        match previous {
+            None => info!(host = %host, "Remote host connected"),
+            Some(p) if p.closed || now.saturating_sub(p.last_push) > STALE_AFTER_MS => {
+                info!(host = %host, "Remote host reconnected")
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// 远程任务：id 加上 `<host>:` 前缀，附上 host / stale，项目路径按映射转换为本机路径
+    pub fn remote_tasks(&self, now: u64) -> Vec<Task> {
+        let mappings = self.receiver.lock().unwrap().mappings.clone();
+        let mut hosts = self.remote_hosts.lock().unwrap();
+        hosts.retain(|host, remote| {
+            let keep = now.saturating_sub(remote.last_push) <= EXPIRE_AFTER_MS;
+            if !keep {
+                info!(host = %host, "Remote host expired, removing its tasks");
+            }
+            keep
+        });
+
+        let mut names: Vec<&String> = hosts.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .flat_map(|host| {
+                let remote = &hosts[host];
+                let stale = remote.closed || now.saturating_sub(remote.last_push) > STALE_AFTER_MS;
+                remote.tasks.iter().map(move |task| (host, stale, task))
+            })
+            .map(|(host, stale, task)| {
+                let mut task = task.clone();
+                task.id = format!("{}:{}", host, task.id);
+                task.host = Some(host.clone());
+                task.stale = stale;
+                if let Some(path) = &task.project_path {
+                    if let Some(local) = mappings.iter().find_map(|m| m.apply(host, path)) {
+                        task.project_path = Some(local);
+                    }
+                }
+                task
+            })
+            .collect()
+    }
+
+    /// 按 `<host>:<id>` 查找远程任务
+    pub fn get(&self, task_id: &str, now: u64) -> Option<Task> {
+        if !task_id.contains(':') {
+            return None;
+        }
+        self.remote_tasks(now).into_iter().find(|t| t.id == task_id)
+    }
+}
+
+// ============================================================================
+// Relay
+// ============================================================================
+
+/// 转发到上游实例的配置
+#[derive(Debug, Clone)]
+pub struct RelayConfig {
+    /// 上游实例的地址，如 http://host.docker.internal:31415
+    pub upstream: String,
+    pub token: String,
+    /// 在上游显示的主机名
+    pub host: String,
+}
+
+/// 默认主机名：HOSTNAME 环境变量，其次 /etc/hostname
+pub fn default_host_name() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+        .map(|h| h.trim().replace([':', '/'], "-"))
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "remote".to_string())
+}
+
+impl Federation {
+    /// 在后台线程把本机任务（由 local_tasks 读取）推送到上游：
+    /// 任务变化时立即推送，否则每 2 秒推送一次作为心跳
+    pub fn start_relay(&self, config: RelayConfig, local_tasks: impl Fn() -> Vec<Task> + Send + 'static) {
+        self.stop_relay();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::spawn(move || run_relay(config, local_tasks, thread_stop));
+        *self.relay.lock().unwrap() = Some((stop, thread));
+    }
+
+    /// 停止转发，并通知上游本机已断开
+    pub fn stop_relay(&self) {
+        let Some((stop, thread)) = self.relay.lock().unwrap().take() else { return };
+        stop.store(true, Ordering::SeqCst);
+        let _ = thread.join();
+    }
+}
+
+fn run_relay(config: RelayConfig, local_tasks: impl Fn() -> Vec<Task>, stop: Arc<AtomicBool>) {
+    let url = format!("{}/api/federation/push", config.upstream.trim_end_matches('/'));
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(3))
+        .timeout(Duration::from_secs(5))
+        .build();
+    let push = |tasks: Vec<Task>, closing: bool| -> Result<(), String> {
+        let body = RelayPush { host: config.host.clone(), tasks, closing };
+        agent
+            .post(&url)
+            .set("Authorization", &format!("Bearer {}", config.token))
+            .send_json(&body)
+            .map(|_| ())
+            .map_err(|e| match e {
+                ureq::Error::Status(code, _) => format!("HTTP {}", code),
+                e => e.to_string(),
+            })
+    };
+
+    info!(upstream = %config.upstream, host = %config.host, "Relaying tasks upstream");
+    let mut last_sent: Option<(String, std::time::Instant)> = None;
+    let mut failures = 0u32;
+    let mut retry_at = std::time::Instant::now();
+
+    while !stop.load(Ordering::SeqCst) {
+        std::thread::sleep(RELAY_POLL);
+        let now = std::time::Instant::now();
+        if now < retry_at {
+            continue;
+        }
+        // 只转发本机任务，不转发从其他实例收到的任务
+        let tasks = local_tasks();
+        let snapshot = serde_json::to_string(&tasks).unwrap_or_default();
+        let unchanged = last_sent
+            .as_ref()
+            .is_some_and(|(sent, at)| *sent == snapshot && at.elapsed() < RELAY_HEARTBEAT);
+        if unchanged {
+            continue;
+        }
+
+        match push(tasks, false) {
+            Ok(()) => {
+                if failures > 0 {
+                    info!(upstream = %config.upstream, "Upstream reachable again");
+                }
+                failures = 0;
+                last_sent = Some((snapshot, now));
+            }
+            Err(e) => {
+                // 只在第一次失败时告警，避免断开期间刷屏
+                if failures == 0 {
+                    warn!(upstream = %config.upstream, error = %e, "Failed to push tasks upstream, retrying");
+                } else {
+                    debug!(upstream = %config.upstream, error = %e, "Push upstream failed");
+                }
+                failures += 1;
+                let backoff = RELAY_POLL * 2u32.saturating_pow(failures.min(5));
+                retry_at = now + backoff.min(RELAY_MAX_BACKOFF);
+                last_sent = None;
+            }
+        }
+    }
+
+    if let Err(e) = push(Vec::new(), true) {
+        debug!(error = %e, "Failed to notify upstream on shutdown");
+    }
+    info!("Relay stopped");
+}
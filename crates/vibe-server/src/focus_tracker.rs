@@ -0,0 +1,474 @@
+use serde_json::Value;
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Weak};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use vibe_core::project_identity::ProjectIdentity;
+use vibe_core::status_line::last_component;
+use vibe_core::window_match::{match_score, IdeWindow, MatchCriteria};
+use vibe_core::{Task, TaskEngine};
+
+use crate::process_watcher;
+
+// ============================================================================
+// 焦点跟踪：监听系统前台窗口，为所有来源的任务更新 is_focused（仅 Linux）
+// ============================================================================
+//
+// 依次尝试 Hyprland（事件 socket）、sway（swaymsg subscribe）和 X11（xprop -spy _NET_ACTIVE_WINDOW）；
+// 其他 Wayland 合成器下 XWayland 只能看到 X 客户端的窗口，不使用 X11。
+// IDE 窗口按 find_best_match 的打分与任务的窗口标题、当前文件比较；终端等其他窗口按子进程的 cwd
+// 与任务的项目路径或所在仓库对应。跟踪期间插件上报的 is_focused 不再生效。
+
+/// 没有新事件时按该间隔重新匹配一次，让新注册的任务也能拿到焦点
+const REAPPLY_INTERVAL: Duration = Duration::from_secs(2);
+/// 终端窗口子孙进程 cwd 的缓存时间；每次都扫描 /proc 代价太高，缓存到期或前台窗口变化时才重新扫描
+const CWD_CACHE_TTL: Duration = Duration::from_secs(10);
+/// 监听命令退出（合成器重启等）后的重试间隔
+const RESTART_DELAY: Duration = Duration::from_secs(5);
+/// 只有 IDE 一致时的分数；要求窗口标题也匹配，避免同一 IDE 的其他窗口抢走焦点
+const IDE_ONLY_SCORE: u32 = 100;
+/// Linux 上的窗口类名（WM_CLASS / Wayland app_id，小写）到 ide
+const WINDOW_CLASSES: [(&str, &str); 10] = [
+    ("code", "vscode"),
+    ("code-url-handler", "vscode"),
+    ("code-oss", "vscode"),
+    ("cursor", "cursor"),
+    ("kiro", "kiro"),
+    ("antigravity", "antigravity"),
+    ("windsurf", "windsurf"),
+    ("trae", "trae"),
+    ("codebuddy", "codebuddy"),
+    ("codebuddy cn", "codebuddycn"),
+];
+
+/// 监听线程的开关，线程在首次启用时启动，引擎释放后退出
+#[derive(Default)]
+pub struct FocusTracker {
+    enabled: Arc<AtomicBool>,
+    started: AtomicBool,
+}
+
+impl FocusTracker {
+    /// 由设置同步（focusTracking），首次启用时检测桌面环境并启动监听
+    pub fn configure(&self, engine: &Arc<TaskEngine>, enabled: bool) {
+        self.enabled.store(enabled, Ordering::SeqCst);
+        if !enabled {
+            return;
+        }
+        if !cfg!(target_os = "linux") {
+            warn!("focusTracking is enabled but only works on Linux");
+            return;
+        }
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        match Backend::detect() {
+            Some(backend) => {
+                info!(backend = ?backend, "Focus tracking started");
+                let (engine, enabled) = (Arc::downgrade(engine), self.enabled.clone());
+                std::thread::spawn(move || run(backend, engine, enabled));
+            }
+            None => warn!("No X11 session or sway/Hyprland compositor found, focus tracking unavailable"),
+        }
+    }
+}
+
+/// 前台窗口
+#[derive(Debug, Clone, PartialEq)]
+struct FocusedWindow {
+    /// WM_CLASS 或 Wayland app_id
+    class: String,
+    title: String,
+    pid: Option<u32>,
+}
+
+/// 监听线程发给主循环的消息
+enum FocusEvent {
+    /// 前台窗口（不是窗口或无法读取时为 None）
+    Window(Option<FocusedWindow>),
+    /// 监听结束（命令退出、socket 关闭、无法启动），稍后重试
+    Lost(String),
+}
+
+// ============================================================================
+// 前台窗口来源
+// ============================================================================
+
+#[derive(Debug)]
+enum Backend {
+    /// Hyprland 事件 socket（.socket2.sock）
+    Hyprland(PathBuf),
+    Sway,
+    X11,
+}
+
+impl Backend {
+    fn detect() -> Option<Backend> {
+        if let Ok(signature) = std::env::var("HYPRLAND_INSTANCE_SIGNATURE") {
+            // 新版本放在 $XDG_RUNTIME_DIR/hypr，旧版本在 /tmp/hypr
+            let runtime_dir = std::env::var("XDG_RUNTIME_DIR").map(|d| PathBuf::from(d).join("hypr"));
+            let socket = runtime_dir
+                .into_iter()
+                .chain([PathBuf::from("/tmp/hypr")])
+                .map(|dir| dir.join(&signature).join(".socket2.sock"))
+                .find(|path| path.exists());
+            if let Some(socket) = socket {
+                return Some(Backend::Hyprland(socket));
+            }
+        }
+        if std::env::var_os("SWAYSOCK").is_some() {
+            return Some(Backend::Sway);
+        }
+        let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some()
+            || std::env::var("XDG_SESSION_TYPE").is_ok_and(|t| t.eq_ignore_ascii_case("wayland"));
+        if wayland {
+            return None;
+        }
+        std::env::var_os("DISPLAY").map(|_| Backend::X11)
+    }
+
+    /// 持续把前台窗口发送到 tx，直到监听命令或 socket 结束
+    fn watch(&self, tx: &mpsc::Sender<FocusEvent>) -> Result<(), String> {
+        match self {
+            Backend::Hyprland(socket) => watch_hyprland(socket, tx),
+            Backend::Sway => watch_sway(tx),
+            Backend::X11 => watch_x11(tx),
+        }
+    }
+}
+
+/// 启动一个输出事件流的命令
+fn spawn_stream(program: &str, args: &[&str]) -> Result<std::process::Child, String> {
+    Command::new(program)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+#[cfg(unix)]
+fn watch_hyprland(socket: &std::path::Path, tx: &mpsc::Sender<FocusEvent>) -> Result<(), String> {
+    let stream = std::os::unix::net::UnixStream::connect(socket).map_err(|e| e.to_string())?;
+    let _ = tx.send(FocusEvent::Window(hyprland_active_window()));
+    for line in BufReader::new(stream).lines() {
+        let line = line.map_err(|e| e.to_string())?;
+        // activewindow>>class,title；标题可能含逗号，完整信息通过 hyprctl 读取
+        if line.starts_with("activewindow>>") && tx.send(FocusEvent::Window(hyprland_active_window())).is_err() {
+            return Ok(());
+        }
+    }
+    Err("Hyprland event socket closed".to_string())
+}
+
+#[cfg(not(unix))]
+fn watch_hyprland(_socket: &std::path::Path, _tx: &mpsc::Sender<FocusEvent>) -> Result<(), String> {
+    Err("Hyprland is not supported on this platform".to_string())
+}
+
+fn hyprland_active_window() -> Option<FocusedWindow> {
+    let output = Command::new("hyprctl").args(["activewindow", "-j"]).output().ok()?;
+    let value: Value = serde_json::from_slice(&output.stdout).ok()?;
+    Some(FocusedWindow {
+        class: value["class"].as_str()?.to_string(),
+        title: value["title"].as_str().unwrap_or_default().to_string(),
+        pid: value["pid"].as_u64().map(|p| p as u32),
+    })
+}
+
+/// sway 容器节点对应的窗口；XWayland 窗口没有 app_id，使用 window_properties.class
+fn sway_window(node: &Value) -> Option<FocusedWindow> {
+    let class = node["app_id"].as_str().or_else(|| node["window_properties"]["class"].as_str())?;
+    Some(FocusedWindow {
+        class: class.to_string(),
+        title: node["name"].as_str().unwrap_or_default().to_string(),
+        pid: node["pid"].as_u64().map(|p| p as u32),
+    })
+}
+
+/// get_tree 中当前获得焦点的窗口
+fn sway_focused(node: &Value) -> Option<FocusedWindow> {
+    if node["focused"].as_bool() == Some(true) {
+        return sway_window(node);
+    }
+    ["nodes", "floating_nodes"]
+        .iter()
+        .filter_map(|key| node[*key].as_array())
+        .flatten()
+        .find_map(sway_focused)
+}
+
+fn watch_sway(tx: &mpsc::Sender<FocusEvent>) -> Result<(), String> {
+    if let Ok(output) = Command::new("swaymsg").args(["-r", "-t", "get_tree"]).output() {
+        if let Ok(tree) = serde_json::from_slice::<Value>(&output.stdout) {
+            let _ = tx.send(FocusEvent::Window(sway_focused(&tree)));
+        }
+    }
+
+    let mut child = spawn_stream("swaymsg", &["-r", "-m", "-t", "subscribe", "[\"window\"]"])?;
+    let stdout = child.stdout.take().ok_or("swaymsg has no stdout")?;
+    for event in serde_json::Deserializer::from_reader(BufReader::new(stdout)).into_iter::<Value>() {
+        let Ok(event) = event else { break };
+        let container = &event["container"];
+        // 焦点切换，或当前窗口的标题变化（切换文件、打开其他项目）
+        let focus_changed = match event["change"].as_str() {
+            Some("focus") => true,
+            Some("title") => container["focused"].as_bool() == Some(true),
+            _ => false,
+        };
+        if focus_changed && tx.send(FocusEvent::Window(sway_window(container))).is_err() {
+            break;
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    Err("swaymsg subscription ended".to_string())
+}
+
+fn watch_x11(tx: &mpsc::Sender<FocusEvent>) -> Result<(), String> {
+    let mut child = spawn_stream("xprop", &["-root", "-spy", "_NET_ACTIVE_WINDOW"])?;
+    let stdout = child.stdout.take().ok_or("xprop has no stdout")?;
+    // _NET_ACTIVE_WINDOW(WINDOW): window id # 0x3a00007
+    for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+        let window = line.rsplit("# ").next().and_then(|id| x11_window(id.trim()));
+        if tx.send(FocusEvent::Window(window)).is_err() {
+            break;
+        }
+    }
+    let _ = child.kill();
+    let _ = child.wait();
+    Err("xprop exited".to_string())
+}
+
+/// 去掉 xprop 输出中字符串值两侧的引号
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').replace("\\\"", "\"")
+}
+
+fn x11_window(id: &str) -> Option<FocusedWindow> {
+    if !id.starts_with("0x") || id == "0x0" {
+        return None;
+    }
+    let output = Command::new("xprop").args(["-id", id, "WM_CLASS", "_NET_WM_NAME", "_NET_WM_PID"]).output().ok()?;
+    let mut window = FocusedWindow { class: String::new(), title: String::new(), pid: None };
+    for line in String::from_utf8_lossy(&output.stdout).lines() {
+        let Some((name, value)) = line.split_once(" = ") else { continue };
+        if name.starts_with("WM_CLASS") {
+            // WM_CLASS(STRING) = "code", "Code"：取实例名
+            window.class = value.split(", ").next().map(unquote).unwrap_or_default();
+        } else if name.starts_with("_NET_WM_NAME") {
+            window.title = unquote(value);
+        } else if name.starts_with("_NET_WM_PID") {
+            window.pid = value.trim().parse().ok();
+        }
+    }
+    Some(window)
+}
+
+// ============================================================================
+// 匹配
+// ============================================================================
+
+fn ide_for_class(class: &str) -> Option<&'static str> {
+    let class = class.to_lowercase();
+    WINDOW_CLASSES.iter().find(|(name, _)| *name == class).map(|(_, ide)| *ide)
+}
+
+/// 与激活任务窗口时相同的条件：窗口标题（缺失时用任务名）和当前文件
+fn criteria_for(task: &Task) -> MatchCriteria {
+    let workspace = if task.window_title.is_empty() { &task.name } else { &task.window_title };
+    MatchCriteria {
+        ide: Some(task.ide.clone()),
+        workspace: Some(workspace.clone()).filter(|w| !w.is_empty()),
+        file: task.active_file.as_deref().map(|f| last_component(f).to_string()),
+    }
+}
+
+/// 前台终端窗口子孙进程的工作目录
+#[derive(Default)]
+struct CwdCache {
+    /// 扫描时的窗口进程和时间
+    scanned: Option<(u32, Instant)>,
+    cwds: Vec<ProjectIdentity>,
+}
+
+impl CwdCache {
+    fn get(&mut self, pid: u32) -> Vec<ProjectIdentity> {
+        let fresh = self.scanned.is_some_and(|(p, at)| p == pid && at.elapsed() < CWD_CACHE_TTL);
+        if !fresh {
+            self.cwds =
+                process_watcher::descendant_cwds(pid).iter().map(|cwd| ProjectIdentity::resolve(cwd)).collect();
+            self.scanned = Some((pid, Instant::now()));
+        }
+        self.cwds.clone()
+    }
+}
+
+/// 前台窗口对应的任务：IDE 窗口取得分最高的任务，其他窗口按子进程 cwd（由 terminal_cwds 给出）对应项目
+fn focused_task_ids(
+    tasks: &[Task],
+    window: &FocusedWindow,
+    terminal_cwds: impl FnOnce(u32) -> Vec<ProjectIdentity>,
+) -> Vec<String> {
+    if let Some(ide) = ide_for_class(&window.class) {
+        let ide_window = IdeWindow {
+            bundle_id: window.class.clone(),
+            ide: ide.to_string(),
+            app_name: window.class.clone(),
+            window_title: window.title.clone(),
+            window_index: 0,
+            pid: window.pid.map(|p| p.to_string()).unwrap_or_default(),
+        };
+        let scored: Vec<(&Task, u32)> = tasks
+            .iter()
+            .filter_map(|t| match_score(&ide_window, &criteria_for(t)).map(|score| (t, score)))
+            .filter(|(_, score)| *score > IDE_ONLY_SCORE)
+            .collect();
+        if let Some(best) = scored.iter().map(|(_, score)| *score).max() {
+            return scored.iter().filter(|(_, score)| *score == best).map(|(t, _)| t.id.clone()).collect();
+        }
+    }
+
+    // 终端窗口：agent 运行在窗口进程的子孙进程中
+    let Some(pid) = window.pid else { return Vec::new() };
+    let cwds = terminal_cwds(pid);
+    if cwds.is_empty() {
+        return Vec::new();
+    }
+    let matched: Vec<&Task> = tasks
+        .iter()
+        .filter(|t| {
+            let Some(path) = t.project_path.as_deref() else { return false };
+            let project = ProjectIdentity::resolve(path);
+            // 不按祖先目录匹配：项目是家目录时会匹配所有终端
+            cwds.iter().any(|cwd| project.same_path(cwd) || project.same_root(cwd))
+        })
+        .collect();
+    // 一个终端进程可能承载多个窗口（如 gnome-terminal-server），对应到多个项目时无法判断是哪一个
+    let projects: HashSet<&str> = matched.iter().filter_map(|t| t.project_path.as_deref()).collect();
+    if projects.len() == 1 {
+        matched.iter().map(|t| t.id.clone()).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+// ============================================================================
+// 主循环
+// ============================================================================
+
+fn run(backend: Backend, engine: Weak<TaskEngine>, enabled: Arc<AtomicBool>) {
+    let (tx, rx) = mpsc::channel::<FocusEvent>();
+    std::thread::spawn(move || loop {
+        // watch 只在主循环退出（发送失败）时返回 Ok
+        let Err(error) = backend.watch(&tx) else { return };
+        if tx.send(FocusEvent::Lost(error)).is_err() {
+            return;
+        }
+        std::thread::sleep(RESTART_DELAY);
+    });
+
+    let mut current: Option<FocusedWindow> = None;
+    let mut cwd_cache = CwdCache::default();
+    // 监听启动后收到过前台窗口才接管 is_focused，监听中断时交还给插件
+    let mut watching = false;
+    let mut active = false;
+    loop {
+        match rx.recv_timeout(REAPPLY_INTERVAL) {
+            Ok(FocusEvent::Window(window)) => {
+                if !watching || window != current {
+                    debug!(window = ?window, "Foreground window changed");
+                    // 同一个终端进程可能承载多个窗口，切换窗口后重新扫描
+                    cwd_cache = CwdCache::default();
+                }
+                current = window;
+                watching = true;
+            }
+            Ok(FocusEvent::Lost(error)) => {
+                if watching {
+                    warn!(error = %error, "Focus watcher stopped, restarting");
+                } else {
+                    debug!(error = %error, "Focus watcher unavailable, retrying");
+                }
+                current = None;
+                watching = false;
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+            Err(mpsc::RecvTimeoutError::Disconnected) => return,
+        }
+        let Some(engine) = engine.upgrade() else { return };
+
+        let enabled = enabled.load(Ordering::SeqCst);
+        if active != (enabled && watching) {
+            active = enabled && watching;
+            engine.set_os_focus(active);
+            if !enabled {
+                info!("Focus tracking disabled");
+            }
+        }
+        if active {
+            apply(&engine, current.as_ref(), &mut cwd_cache);
+        }
+    }
+}
+
+fn apply(engine: &TaskEngine, window: Option<&FocusedWindow>, cwd_cache: &mut CwdCache) {
+    let task_ids = window.map(|w| focused_task_ids(&engine.tasks(), w, |pid| cwd_cache.get(pid))).unwrap_or_default();
+    if engine.set_focused(&task_ids) {
+        debug!(tasks = ?task_ids, "Focused tasks changed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vibe_core::{ManualClock, NullSink, ReportRequest};
+
+    fn tasks(projects: &[(&str, &str)]) -> Vec<Task> {
+        let engine = TaskEngine::new(Arc::new(ManualClock::new(1_000)), Arc::new(NullSink));
+        for (id, path) in projects {
+            let req = ReportRequest {
+                task_id: id.to_string(),
+                name: id.to_string(),
+                ide: "claude-code".to_string(),
+                window_title: String::new(),
+                is_focused: false,
+                project_path: Some(path.to_string()),
+                active_file: None,
+            };
+            engine.register(req, "hook").unwrap();
+        }
+        engine.tasks()
+    }
+
+    fn terminal(tasks: &[Task], cwds: &[&str]) -> Vec<String> {
+        let window = FocusedWindow { class: "kitty".to_string(), title: "zsh".to_string(), pid: Some(42) };
+        let mut ids = focused_task_ids(tasks, &window, |pid| {
+            assert_eq!(pid, 42);
+            cwds.iter().map(|cwd| ProjectIdentity::resolve(cwd)).collect()
+        });
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn terminal_matches_exact_project() {
+        let tasks = tasks(&[("home", "/home/dev"), ("app", "/home/dev/app"), ("web", "/home/dev/web")]);
+        assert_eq!(terminal(&tasks, &["/home/dev/app"]), ["app"]);
+        // 家目录项目不因包含终端的 cwd 而获得焦点
+        assert!(terminal(&tasks, &["/home/dev/notes"]).is_empty());
+        assert_eq!(terminal(&tasks, &["/home/dev"]), ["home"]);
+    }
+
+    #[test]
+    fn terminal_spanning_several_projects_focuses_nothing() {
+        let tasks = tasks(&[("app", "/home/dev/app"), ("app-2", "/home/dev/app"), ("web", "/home/dev/web")]);
+        assert_eq!(terminal(&tasks, &["/home/dev/app", "/tmp"]), ["app", "app-2"]);
+        assert!(terminal(&tasks, &["/home/dev/app", "/home/dev/web"]).is_empty());
+        assert!(terminal(&tasks, &[]).is_empty());
+    }
+}
@@ -0,0 +1,390 @@
+use notify::{RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex, Weak};
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+use vibe_core::{Task, TaskEngine, UpdateStateRequest};
+
+use crate::settings::AppSettings;
+
+// ============================================================================
+// 文件活动：监听任务项目目录的写入，从写入频率推断 agent 是否在工作
+// ============================================================================
+//
+// 与 VS Code 扩展的"插件检测"思路相同，但在后端实现，对任何 IDE 和终端 agent 都有效。
+// 短时间内写入次数达到阈值视为运行中，阶段显示最近写入的文件；安静一段时间后标记为完成。
+// 来源为 fs，优先级低于 plugin：插件任务只在插件状态被屏蔽时代为更新，process 任务有更可靠的进程信号，不参与。
+
+const SOURCE: &str = "fs";
+/// 这些目录下的写入不计入（版本库、构建产物、依赖）
+const IGNORED_DIRS: [&str; 3] = [".git", "target", "node_modules"];
+const TICK: Duration = Duration::from_secs(1);
+/// 每隔多少个 tick 按任务列表同步一次监听的目录
+const SYNC_EVERY_TICKS: u32 = 5;
+/// 单个项目最多监听的目录数，超过时放弃该项目（例如项目路径是家目录）
+const MAX_DIRS_PER_ROOT: usize = 5000;
+/// 同一文件在该时间内的多次事件算一次写入
+const WRITE_COALESCE_MS: u64 = 1000;
+/// 阶段中显示的最近文件数
+const RECENT_FILES: usize = 3;
+
+/// 推断阈值，由设置同步
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FsActivityConfig {
+    pub enabled: bool,
+    /// 窗口内至少多少次写入视为运行中
+    pub min_writes: u32,
+    pub window_ms: u64,
+    /// 运行中的任务多久没有写入后标记为完成
+    pub idle_ms: u64,
+}
+
+impl From<&AppSettings> for FsActivityConfig {
+    fn from(settings: &AppSettings) -> Self {
+        FsActivityConfig {
+            enabled: settings.fs_activity,
+            min_writes: settings.fs_activity_min_writes,
+            window_ms: settings.fs_activity_window_seconds * 1000,
+            idle_ms: settings.fs_activity_idle_seconds * 1000,
+        }
+    }
+}
+
+/// 监听线程的配置，线程在首次启用时启动，引擎释放后退出
+#[derive(Default)]
+pub struct FsActivity {
+    config: Arc<Mutex<Option<FsActivityConfig>>>,
+    started: AtomicBool,
+}
+
+impl FsActivity {
+    /// 由设置同步（fsActivity*），首次启用时启动监听线程
+    pub fn configure(&self, engine: &Arc<TaskEngine>, config: FsActivityConfig) {
+        *self.config.lock().unwrap() = Some(config);
+        if config.enabled && !self.started.swap(true, Ordering::SeqCst) {
+            let (engine, config) = (Arc::downgrade(engine), self.config.clone());
+            std::thread::spawn(move || run(engine, config));
+        }
+    }
+}
+
+// ============================================================================
+// 监听
+// ============================================================================
+
+/// 一个项目目录的监听和写入记录
+#[derive(Default)]
+struct Root {
+    dirs: Vec<PathBuf>,
+    writes: VecDeque<u64>,
+    recent_files: VecDeque<String>,
+    last_write: u64,
+    /// 本模块把该项目的任务置为运行中
+    active: bool,
+}
+
+fn is_ignored(name: &str) -> bool {
+    IGNORED_DIRS.contains(&name)
+}
+
+/// 列出 root 下需要监听的目录（跳过忽略目录和符号链接），超过上限时返回 None
+fn collect_dirs(root: &Path) -> Option<Vec<PathBuf>> {
+    let mut dirs = Vec::new();
+    let mut stack = vec![root.to_path_buf()];
+    while let Some(dir) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        dirs.push(dir);
+        if dirs.len() > MAX_DIRS_PER_ROOT {
+            return None;
+        }
+        for entry in entries.filter_map(|e| e.ok()) {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if is_dir && !entry.file_name().to_str().is_some_and(is_ignored) {
+                stack.push(entry.path());
+            }
+        }
+    }
+    Some(dirs)
+}
+
+/// 参与推断的任务：有本机存在的项目目录，且不是进程监视创建的任务
+fn watchable_root(task: &Task) -> Option<PathBuf> {
+    if task.source == "process" {
+        return None;
+    }
+    let path = PathBuf::from(task.project_path.as_deref()?);
+    (path.is_absolute() && path.parent().is_some() && path.is_dir()).then_some(path)
+}
+
+struct FsWatcher {
+    watcher: notify::RecommendedWatcher,
+    roots: HashMap<PathBuf, Root>,
+    /// 超过目录上限的项目，不再重试
+    skipped: HashSet<PathBuf>,
+}
+
+impl FsWatcher {
+    fn add_dirs(&mut self, root: &Path, dirs: Vec<PathBuf>) {
+        let watched: Vec<PathBuf> = dirs
+            .into_iter()
+            .filter(|dir| self.watcher.watch(dir, RecursiveMode::NonRecursive).is_ok())
+            .collect();
+        if let Some(state) = self.roots.get_mut(root) {
+            state.dirs.extend(watched);
+        }
+    }
+
+    /// 按任务列表增减监听的项目
+    fn sync(&mut self, engine: &TaskEngine) {
+        let wanted: HashSet<PathBuf> = engine.tasks().iter().filter_map(watchable_root).collect();
+        let removed: Vec<PathBuf> = self.roots.keys().filter(|r| !wanted.contains(*r)).cloned().collect();
+        for root in removed {
+            if let Some(state) = self.roots.remove(&root) {
+                for dir in state.dirs {
+                    let _ = self.watcher.unwatch(&dir);
+                }
+                debug!(root = ?root, "Stopped watching project");
+            }
+        }
+        for root in wanted {
+            if self.roots.contains_key(&root) || self.skipped.contains(&root) {
+                continue;
+            }
+            match collect_dirs(&root) {
+                Some(dirs) => {
+                    info!(root = ?root, dirs = dirs.len(), "Watching project for file activity");
+                    self.roots.insert(root.clone(), Root::default());
+                    self.add_dirs(&root, dirs);
+                }
+                None => {
+                    warn!(root = ?root, limit = MAX_DIRS_PER_ROOT, "Project has too many directories, not watching");
+                    self.skipped.insert(root);
+                }
+            }
+        }
+    }
+
+    fn clear(&mut self) {
+        for (_, state) in self.roots.drain() {
+            for dir in state.dirs {
+                let _ = self.watcher.unwatch(&dir);
+            }
+        }
+        self.skipped.clear();
+    }
+
+    /// 路径所属的项目（最长前缀），以及路径是否位于忽略目录中
+    fn root_of(&self, path: &Path) -> Option<PathBuf> {
+        let root = self.roots.keys().filter(|r| path.starts_with(r)).max_by_key(|r| r.as_os_str().len())?;
+        let relative = path.strip_prefix(root).ok()?;
+        let ignored = relative.components().any(|c| c.as_os_str().to_str().is_some_and(is_ignored));
+        (!ignored).then(|| root.clone())
+    }
+
+    fn on_event(&mut self, event: notify::Event, now: u64) {
+        if !(event.kind.is_create() || event.kind.is_modify() || event.kind.is_remove()) {
+            return;
+        }
+        for path in event.paths {
+            let Some(root) = self.root_of(&path) else { continue };
+            // 新建的目录需要补上监听；目录本身的变化不算写入
+            if path.is_dir() {
+                if event.kind.is_create() {
+                    let dirs = collect_dirs(&path).unwrap_or_default();
+                    self.add_dirs(&root, dirs);
+                }
+                continue;
+            }
+            let Some(state) = self.roots.get_mut(&root) else { continue };
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            // 一次保存会产生多个事件（创建临时文件、写入、重命名），同一文件一秒内只计一次
+            let repeated = state.recent_files.front().is_some_and(|f| f == name)
+                && now.saturating_sub(state.last_write) < WRITE_COALESCE_MS;
+            state.last_write = now;
+            if repeated {
+                continue;
+            }
+            state.writes.push_back(now);
+            state.recent_files.retain(|f| f != name);
+            state.recent_files.push_front(name.to_string());
+            state.recent_files.truncate(RECENT_FILES);
+        }
+    }
+}
+
+fn run(engine: Weak<TaskEngine>, config: Arc<Mutex<Option<FsActivityConfig>>>) {
+    let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+    let watcher = match notify::recommended_watcher(tx) {
+        Ok(w) => w,
+        Err(e) => {
+            error!("Failed to create file activity watcher: {}", e);
+            return;
+        }
+    };
+    let mut fs = FsWatcher { watcher, roots: HashMap::new(), skipped: HashSet::new() };
+    info!("File activity watcher started");
+
+    let mut ticks = 0u32;
+    loop {
+        let Some(engine) = engine.upgrade() else { return };
+        let deadline = std::time::Instant::now() + TICK;
+        while let Some(timeout) = deadline.checked_duration_since(std::time::Instant::now()) {
+            match rx.recv_timeout(timeout) {
+                Ok(Ok(event)) => fs.on_event(event, engine.now()),
+                Ok(Err(e)) => debug!(error = %e, "File watcher error"),
+                Err(mpsc::RecvTimeoutError::Timeout) => break,
+                Err(mpsc::RecvTimeoutError::Disconnected) => return,
+            }
+        }
+
+        let Some(config) = config.lock().unwrap().filter(|c| c.enabled) else {
+            if !fs.roots.is_empty() {
+                fs.clear();
+                info!("File activity watcher disabled");
+            }
+            continue;
+        };
+        if ticks.is_multiple_of(SYNC_EVERY_TICKS) {
+            fs.sync(&engine);
+        }
+        ticks = ticks.wrapping_add(1);
+        evaluate(&engine, &mut fs.roots, &config, engine.now());
+    }
+}
+
+// ============================================================================
+// 推断
+// ============================================================================
+
+fn evaluate(engine: &TaskEngine, roots: &mut HashMap<PathBuf, Root>, config: &FsActivityConfig, now: u64) {
+    let tasks = engine.tasks();
+    for (root, state) in roots.iter_mut() {
+        while state.writes.front().is_some_and(|t| now.saturating_sub(*t) > config.window_ms) {
+            state.writes.pop_front();
+        }
+        let stage = state.recent_files.iter().cloned().collect::<Vec<_>>().join(", ");
+        // 高优先级来源接管的任务不再尝试更新，避免每秒产生一条被忽略的记录
+        let root_tasks = tasks
+            .iter()
+            .filter(|t| engine.accepts_update(t, SOURCE))
+            .filter(|t| watchable_root(t).as_deref() == Some(root.as_path()));
+
+        if state.active && now.saturating_sub(state.last_write) >= config.idle_ms {
+            state.active = false;
+            state.writes.clear();
+            // 只结束由本模块置为运行中的任务
+            for task in root_tasks.filter(|t| t.source == SOURCE && t.status == "running") {
+                update(engine, &task.id, "completed", None);
+            }
+            continue;
+        }
+
+        let burst = state.writes.len() >= config.min_writes as usize;
+        if burst && !state.active {
+            info!(root = ?root, writes = state.writes.len(), "File activity burst");
+            state.active = true;
+        }
+        if !state.active {
+            continue;
+        }
+        // 运行期间持续刷新阶段；不在运行中的任务需要新的一波写入才会启动
+        for task in root_tasks {
+            let stage_changed = task.current_stage.as_deref() != Some(stage.as_str());
+            let start = burst && task.status != "running";
+            let refresh = task.status == "running" && stage_changed && (burst || task.source == SOURCE);
+            if start || refresh {
+                update(engine, &task.id, "running", Some(stage.clone()));
+            }
+        }
+    }
+}
+
+fn update(engine: &TaskEngine, task_id: &str, status: &str, stage: Option<String>) {
+    let result = engine.update_state(UpdateStateRequest {
+        task_id: task_id.to_string(),
+        status: Some(status.to_string()),
+        source: Some(SOURCE.to_string()),
+        estimated_duration: None,
+        current_stage: stage.filter(|s| !s.is_empty()),
+    });
+    if let Err(e) = result {
+        debug!(task_id = %task_id, error = %e, "File activity update failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::event::{CreateKind, DataChange, EventKind, ModifyKind};
+    use vibe_core::{ManualClock, NullSink, ReportRequest};
+
+    const CONFIG: FsActivityConfig =
+        FsActivityConfig { enabled: true, min_writes: 3, window_ms: 10_000, idle_ms: 30_000 };
+
+    fn register(engine: &TaskEngine, id: &str, root: &Path, source: &str) {
+        let req = ReportRequest {
+            task_id: id.to_string(),
+            name: id.to_string(),
+            ide: "cursor".to_string(),
+            window_title: id.to_string(),
+            is_focused: false,
+            project_path: Some(root.to_string_lossy().to_string()),
+            active_file: None,
+        };
+        engine.register(req, source).unwrap();
+    }
+
+    fn write(fs: &mut FsWatcher, root: &Path, file: &str, now: u64) {
+        let kind = EventKind::Modify(ModifyKind::Data(DataChange::Content));
+        fs.on_event(notify::Event::new(kind).add_path(root.join(file)), now);
+    }
+
+    fn status(engine: &TaskEngine, id: &str) -> (String, Option<String>) {
+        let task = engine.get(id).unwrap();
+        (task.status, task.current_stage)
+    }
+
+    #[test]
+    fn burst_runs_and_idle_completes_the_project_task() {
+        // 需要一个存在的目录作为项目；事件是手动注入的，不会真的监听
+        let root = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        let clock = Arc::new(ManualClock::new(1_000));
+        let engine = TaskEngine::new(clock.clone(), Arc::new(NullSink));
+        engine.set_block_plugin_status(true);
+        register(&engine, "editor", &root, "plugin");
+        register(&engine, "agent", &root, "hook");
+
+        let watcher = notify::recommended_watcher(|_| {}).unwrap();
+        let mut fs = FsWatcher { watcher, roots: HashMap::new(), skipped: HashSet::new() };
+        fs.roots.insert(root.clone(), Root::default());
+
+        // 同一文件一秒内的多次事件算一次写入，目录创建和其他项目的文件不算
+        write(&mut fs, &root, "src/a.rs", 1_000);
+        write(&mut fs, &root, "src/a.rs", 1_400);
+        write(&mut fs, &root, "target/debug/out", 1_500);
+        write(&mut fs, Path::new("/elsewhere"), "b.rs", 1_600);
+        fs.on_event(notify::Event::new(EventKind::Create(CreateKind::Folder)).add_path(root.join("src")), 1_700);
+        write(&mut fs, &root, "src/b.rs", 2_000);
+        evaluate(&engine, &mut fs.roots, &CONFIG, 2_000);
+        assert_eq!(status(&engine, "editor").0, "armed");
+
+        clock.set(3_000);
+        write(&mut fs, &root, "src/a.rs", 3_000);
+        evaluate(&engine, &mut fs.roots, &CONFIG, 3_000);
+        assert_eq!(status(&engine, "editor"), ("running".to_string(), Some("a.rs, b.rs".to_string())));
+        assert_eq!(engine.get("editor").unwrap().source, SOURCE);
+        // hook 任务的优先级更高，不受文件活动影响
+        assert_eq!(status(&engine, "agent"), ("armed".to_string(), None));
+
+        // 写入窗口过去后仍在运行，安静到 idle_ms 才完成
+        clock.set(20_000);
+        evaluate(&engine, &mut fs.roots, &CONFIG, 20_000);
+        assert_eq!(status(&engine, "editor").0, "running");
+        clock.set(33_000);
+        evaluate(&engine, &mut fs.roots, &CONFIG, 33_000);
+        assert_eq!(status(&engine, "editor").0, "completed");
+        assert_eq!(status(&engine, "agent").0, "armed");
+    }
+}
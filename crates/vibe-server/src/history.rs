@@ -0,0 +1,127 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tracing::{error, info, warn};
+use vibe_core::{Task, TimelineEvent};
+
+use crate::settings::write_atomic;
+
+/// 最多保留的历史记录条数，超出后丢弃最旧的
+const MAX_HISTORY_ENTRIES: usize = 1000;
+
+/// 已结束任务的记录，保存任务结束时的完整快照
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct HistoryEntry {
+    #[serde(flatten)]
+    pub task: Task,
+    /// 写入历史的时间（毫秒）
+    pub recorded_at: u64,
+    /// 本次运行的事件时间线
+    #[serde(default)]
+    pub timeline: Vec<TimelineEvent>,
+}
+
+#[derive(Default)]
+struct Entries {
+    path: Option<PathBuf>,
+    entries: Vec<HistoryEntry>,
+    /// 每次修改递增，供 estimator 判断缓存是否过期
+    revision: u64,
+}
+
+impl Entries {
+    fn save(&mut self) {
+        self.revision += 1;
+        let Some(path) = &self.path else { return };
+        let result = serde_json::to_string(&self.entries)
+            .map_err(|e| e.to_string())
+            .and_then(|content| write_atomic(path, content.as_bytes()));
+        if let Err(e) = result {
+            error!("Failed to save task history: {}", e);
+        }
+    }
+}
+
+/// 已结束任务的历史记录，没有文件路径时只保存在内存中
+#[derive(Default)]
+pub struct History {
+    entries: Mutex<Entries>,
+}
+
+impl History {
+    /// 加载历史文件（应用数据目录下的 history.json），之后的记录都会写回该文件
+    pub fn load(path: PathBuf) -> Self {
+        let entries = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Task history is corrupted, starting empty: {}", e);
+                Vec::new()
+            }),
+            Err(_) => Vec::new(),
+        };
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        info!(count = entries.len(), "Task history loaded from {:?}", path);
+
+        History {
+            entries: Mutex::new(Entries {
+                path: Some(path),
+                entries,
+                revision: 1,
+            }),
+        }
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.entries.lock().unwrap().revision
+    }
+
+    /// 记录一个已结束的任务；同一任务同一次运行（相同 start_time）重复记录时覆盖旧记录
+    pub fn record(&self, task: Task, timeline: Vec<TimelineEvent>, now: u64) {
+        let mut history = self.entries.lock().unwrap();
+        history
+            .entries
+            .retain(|e| !(e.task.id == task.id && e.task.start_time == task.start_time));
+        history.entries.push(HistoryEntry {
+            task,
+            recorded_at: now,
+            timeline,
+        });
+        let overflow = history.entries.len().saturating_sub(MAX_HISTORY_ENTRIES);
+        history.entries.drain(..overflow);
+        history.save();
+    }
+
+    /// 修改某次运行（task_id + start_time）的历史记录，找不到时忽略
+    pub fn update(&self, task_id: &str, start_time: u64, f: impl FnOnce(&mut HistoryEntry)) {
+        let mut history = self.entries.lock().unwrap();
+        if let Some(entry) = history
+            .entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.task.id == task_id && e.task.start_time == start_time)
+        {
+            f(entry);
+            history.save();
+        }
+    }
+
+    /// 查找任务最近一次运行的记录
+    pub fn latest(&self, task_id: &str) -> Option<HistoryEntry> {
+        let history = self.entries.lock().unwrap();
+        history.entries.iter().rev().find(|e| e.task.id == task_id).cloned()
+    }
+
+    /// 最近的记录在前
+    pub fn entries(&self, limit: Option<usize>) -> Vec<HistoryEntry> {
+        let history = self.entries.lock().unwrap();
+        history
+            .entries
+            .iter()
+            .rev()
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
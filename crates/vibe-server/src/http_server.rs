@@ -0,0 +1,834 @@
+use axum::{
+    extract::{ConnectInfo, MatchedPath, Path, Query, Request, State},
+    http::{header, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tower_http::cors::{Any, CorsLayer};
+use tracing::{error, info};
+
+use vibe_core::status_line::{self, StatusSummary, Template};
+use vibe_core::{
+    CompleteTaskRequest, DeleteTaskRequest, EngineError, MatchCandidate, ReportRequest, ResetRequest, Task,
+    TimelineEvent, UpdateOutcome, UpdateStateByPathRequest, UpdateStateRequest, UpdateTokensRequest,
+};
+
+use crate::claude_hooks::{self, HookEvent};
+use crate::federation::{PushError, RelayPush};
+use crate::logging::{self, LogQuery};
+use crate::server::Server;
+use crate::stats::{self, StatsQuery};
+
+// ============================================================================
+// Response 数据结构
+// ============================================================================
+
+#[derive(Serialize)]
+struct ApiResponse {
+    status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// 按路径匹配到多个任务时列出候选
+    #[serde(skip_serializing_if = "Option::is_none")]
+    candidates: Option<Vec<MatchCandidate>>,
+}
+
+impl ApiResponse {
+    fn ok() -> Self {
+        Self { status: "ok".to_string(), reason: None, error: None, candidates: None }
+    }
+
+    fn ignored(reason: &str) -> Self {
+        Self { status: "ignored".to_string(), reason: Some(reason.to_string()), error: None, candidates: None }
+    }
+
+    fn error(msg: &str) -> Self {
+        Self { status: "error".to_string(), reason: None, error: Some(msg.to_string()), candidates: None }
+    }
+
+    fn ambiguous(msg: &str, candidates: Vec<MatchCandidate>) -> Self {
+        Self { candidates: Some(candidates), ..Self::error(msg) }
+    }
+}
+
+/// 引擎返回结果对应的 HTTP 响应：被忽略的更新仍返回 200，按路径匹配到多个任务时返回 409
+fn engine_response(result: Result<UpdateOutcome, EngineError>) -> (StatusCode, Json<ApiResponse>) {
+    match result {
+        Ok(UpdateOutcome::Applied) => (StatusCode::OK, Json(ApiResponse::ok())),
+        Ok(UpdateOutcome::Ignored(reason)) => (StatusCode::OK, Json(ApiResponse::ignored(reason))),
+        Err(EngineError::BadRequest(msg)) => (StatusCode::BAD_REQUEST, Json(ApiResponse::error(&msg))),
+        Err(EngineError::NotFound(msg)) => (StatusCode::NOT_FOUND, Json(ApiResponse::error(&msg))),
+        Err(EngineError::Ambiguous { message, candidates }) => {
+            (StatusCode::CONFLICT, Json(ApiResponse::ambiguous(&message, candidates)))
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StatusResponse {
+    tasks: Vec<Task>,
+    #[serde(rename = "taskCount")]
+    task_count: usize,
+}
+
+// ============================================================================
+// 宿主应用实现的接口
+// ============================================================================
+
+/// 设置的导入/导出与 profile 切换
+///
+/// HTTP server 不直接持有设置，应用设置还需要同步窗口和事件，因此由宿主应用实现后注册。
+pub trait SettingsBridge: Send + Sync {
+    fn export(&self) -> Result<serde_json::Value, String>;
+    /// `dry_run` 为 true 时只返回差异预览，不应用
+    fn import(&self, settings: serde_json::Value, dry_run: bool) -> Result<serde_json::Value, String>;
+    fn list_profiles(&self) -> Result<serde_json::Value, String>;
+    fn activate_profile(&self, name: &str) -> Result<serde_json::Value, String>;
+}
+
+/// 激活任务所在的 IDE 窗口
+///
+/// 窗口操作依赖桌面环境，由桌面应用实现后注册；无界面运行时 activate 路由返回 503。
+pub trait WindowBridge: Send + Sync {
+    fn activate(&self, task: &Task) -> Result<(), String>;
+}
+
+// ============================================================================
+// API Handlers
+// ============================================================================
+
+async fn get_status(State(server): State<Arc<Server>>) -> Json<StatusResponse> {
+    let tasks_vec = server.merged_tasks();
+
+    Json(StatusResponse {
+        task_count: tasks_vec.len(),
+        tasks: tasks_vec,
+    })
+}
+
+#[derive(Deserialize, Debug)]
+struct StatusLineQuery {
+    #[serde(default)]
+    format: Option<String>,
+    /// text（默认）或 waybar
+    #[serde(default)]
+    output: Option<String>,
+}
+
+/// 一行状态文本，供 tmux、shell 提示符和 waybar/polybar 使用
+async fn get_status_line(State(server): State<Arc<Server>>, Query(query): Query<StatusLineQuery>) -> Response {
+    let format = query.format.as_deref().unwrap_or(status_line::DEFAULT_FORMAT);
+    let template = match Template::parse(format) {
+        Ok(template) => template,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(ApiResponse::error(&e))).into_response(),
+    };
+    let tasks = server.merged_tasks();
+    let summary = StatusSummary::new(&tasks, server.now());
+
+    match query.output.as_deref().unwrap_or("text") {
+        "text" => (
+            [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+            template.render(&summary),
+        )
+            .into_response(),
+        "waybar" => Json(status_line::waybar(&template, &summary)).into_response(),
+        other => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResponse::error(&format!("Unknown output '{}' (expected text or waybar)", other))),
+        )
+            .into_response(),
+    }
+}
+
+async fn report_task(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<ReportRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    engine_response(Ok(server.engine.report(req)))
+}
+
+async fn update_state(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<UpdateStateRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    engine_response(server.engine.update_state(req))
+}
+
+async fn update_tokens(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<UpdateTokensRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    engine_response(server.engine.update_tokens(req).map(|_| UpdateOutcome::Applied))
+}
+
+async fn reset_tasks(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<ResetRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    server.engine.reset(req.task_id.as_deref());
+    (StatusCode::OK, Json(ApiResponse::ok()))
+}
+
+async fn delete_task(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<DeleteTaskRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    engine_response(server.engine.delete(&req.task_id).map(|_| UpdateOutcome::Applied))
+}
+
+#[derive(Deserialize, Debug)]
+struct TaskIdRequest {
+    task_id: String,
+}
+
+async fn reset_task_armed(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<TaskIdRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    engine_response(server.engine.reset_to_armed(&req.task_id).map(|_| UpdateOutcome::Applied))
+}
+
+async fn activate_task(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<TaskIdRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    // 远程任务的项目路径已映射为本机路径
+    let task = server.engine.get(&req.task_id).or_else(|| server.federation.get(&req.task_id, server.now()));
+    let Some(task) = task else {
+        return (StatusCode::NOT_FOUND, Json(ApiResponse::error("Task not found")));
+    };
+    let Some(bridge) = server.window_bridge() else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiResponse::error("Window activation is not available")),
+        );
+    };
+    // 窗口扫描和激活可能较慢，不占用 async 线程
+    match tokio::task::spawn_blocking(move || bridge.activate(&task)).await {
+        Ok(Ok(())) => (StatusCode::OK, Json(ApiResponse::ok())),
+        Ok(Err(e)) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error(&e))),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiResponse::error(&e.to_string()))),
+    }
+}
+
+#[derive(Serialize)]
+struct TimelineResponse {
+    task_id: String,
+    events: Vec<TimelineEvent>,
+}
+
+async fn task_timeline(
+    State(server): State<Arc<Server>>,
+    Path(task_id): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match server.task_timeline(&task_id) {
+        Some(events) => (
+            StatusCode::OK,
+            Json(serde_json::to_value(TimelineResponse { task_id, events }).unwrap_or_default()),
+        ),
+        None => (
+            StatusCode::NOT_FOUND,
+            Json(serde_json::to_value(ApiResponse::error("Task not found")).unwrap_or_default()),
+        ),
+    }
+}
+
+async fn complete_task(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<CompleteTaskRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    engine_response(server.engine.complete(req, "hook").map(|_| UpdateOutcome::Applied))
+}
+
+async fn update_state_by_path(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<UpdateStateByPathRequest>,
+) -> (StatusCode, Json<ApiResponse>) {
+    engine_response(server.engine.update_state_by_path(req))
+}
+
+// ============================================================================
+// Agent Hook / Ingest Handlers
+// ============================================================================
+
+/// 接收 hook 写到 stdin 的原始 JSON；`curl -d @-` 默认以表单类型发送，因此不检查 Content-Type
+async fn claude_code_hook(
+    State(server): State<Arc<Server>>,
+    body: axum::body::Bytes,
+) -> (StatusCode, Json<ApiResponse>) {
+    match serde_json::from_slice::<HookEvent>(&body) {
+        Ok(event) => engine_response(claude_hooks::handle(&server.engine, &server.sessions, event)),
+        Err(e) => (StatusCode::BAD_REQUEST, Json(ApiResponse::error(&format!("Invalid hook payload: {}", e)))),
+    }
+}
+
+/// 按适配器定义转换任意 agent 的事件；同样不检查 Content-Type，请求体可以为空
+async fn ingest_event(
+    State(server): State<Arc<Server>>,
+    Path(adapter): Path<String>,
+    Query(query): Query<HashMap<String, String>>,
+    body: axum::body::Bytes,
+) -> (StatusCode, Json<ApiResponse>) {
+    engine_response(server.adapters.handle(&server.engine, &server.sessions, &adapter, &body, &query))
+}
+
+async fn list_ingest_adapters(State(server): State<Arc<Server>>) -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "adapters": server.adapters.list() }))
+}
+
+// ============================================================================
+// Federation Handler
+// ============================================================================
+
+fn bearer_token(headers: &axum::http::HeaderMap) -> Option<&str> {
+    headers
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+async fn federation_push(
+    State(server): State<Arc<Server>>,
+    headers: axum::http::HeaderMap,
+    Json(push): Json<RelayPush>,
+) -> (StatusCode, Json<ApiResponse>) {
+    match server.federation.receive(bearer_token(&headers), push, server.now()) {
+        Ok(()) => (StatusCode::OK, Json(ApiResponse::ok())),
+        Err(PushError::Disabled) => (
+            StatusCode::FORBIDDEN,
+            Json(ApiResponse::error("Federation is disabled (federationToken is not set)")),
+        ),
+        Err(PushError::Unauthorized) => (StatusCode::UNAUTHORIZED, Json(ApiResponse::error("Invalid token"))),
+        Err(PushError::BadRequest(msg)) => (StatusCode::BAD_REQUEST, Json(ApiResponse::error(&msg))),
+    }
+}
+
+// ============================================================================
+// Settings Handlers
+// ============================================================================
+
+/// 设置、日志、统计和指标路由暴露全部设置、项目路径和窗口标题：拒绝浏览器发起的请求（带 Origin 头），
+/// 设置了 federationToken 时要求同一个 Bearer token，否则只接受本机连接
+async fn require_private_access(State(server): State<Arc<Server>>, req: Request, next: Next) -> Response {
+    if req.headers().contains_key(header::ORIGIN) {
+        let error = ApiResponse::error("This route does not accept browser requests");
+        return (StatusCode::FORBIDDEN, Json(error)).into_response();
+    }
+    let loopback = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .is_some_and(|ConnectInfo(addr)| addr.ip().is_loopback());
+    match server.federation.authorize(bearer_token(req.headers())) {
+        Ok(()) => next.run(req).await,
+        Err(PushError::Disabled) if loopback => next.run(req).await,
+        Err(PushError::Disabled) => {
+            let error = ApiResponse::error("Set federationToken to use this route from another machine");
+            (StatusCode::FORBIDDEN, Json(error)).into_response()
+        }
+        Err(_) => (StatusCode::UNAUTHORIZED, Json(ApiResponse::error("Invalid token"))).into_response(),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct ImportSettingsQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+fn settings_response(result: Option<Result<serde_json::Value, String>>) -> (StatusCode, Json<serde_json::Value>) {
+    match result {
+        Some(Ok(value)) => (StatusCode::OK, Json(value)),
+        Some(Err(e)) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::to_value(ApiResponse::error(&e)).unwrap_or_default()),
+        ),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(serde_json::to_value(ApiResponse::error("Settings are not available")).unwrap_or_default()),
+        ),
+    }
+}
+
+async fn export_settings(State(server): State<Arc<Server>>) -> (StatusCode, Json<serde_json::Value>) {
+    settings_response(server.settings_bridge().map(|b| b.export()))
+}
+
+async fn import_settings(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<ImportSettingsQuery>,
+    Json(settings): Json<serde_json::Value>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    settings_response(server.settings_bridge().map(|b| b.import(settings, query.dry_run)))
+}
+
+async fn list_settings_profiles(State(server): State<Arc<Server>>) -> (StatusCode, Json<serde_json::Value>) {
+    settings_response(server.settings_bridge().map(|b| b.list_profiles()))
+}
+
+async fn activate_settings_profile(
+    State(server): State<Arc<Server>>,
+    Path(name): Path<String>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    settings_response(server.settings_bridge().map(|b| b.activate_profile(&name)))
+}
+
+// ============================================================================
+// Stats Handler
+// ============================================================================
+
+async fn get_stats(
+    State(server): State<Arc<Server>>,
+    Query(query): Query<StatsQuery>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    match stats::query(&server.history, &query) {
+        Ok(result) => (StatusCode::OK, Json(serde_json::to_value(result).unwrap_or_default())),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::to_value(ApiResponse::error(&e)).unwrap_or_default()),
+        ),
+    }
+}
+
+// ============================================================================
+// Logs Handler
+// ============================================================================
+
+async fn get_logs(Query(query): Query<LogQuery>) -> (StatusCode, Json<serde_json::Value>) {
+    match logging::query(&query) {
+        Ok(result) => (StatusCode::OK, Json(serde_json::to_value(result).unwrap_or_default())),
+        Err(e) => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::to_value(ApiResponse::error(&e)).unwrap_or_default()),
+        ),
+    }
+}
+
+// ============================================================================
+// Metrics
+// ============================================================================
+
+async fn get_metrics(State(server): State<Arc<Server>>) -> Response {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")],
+        server.metrics.render(&server.engine.tasks(), server.now()),
+    )
+        .into_response()
+}
+
+/// 按路由模板统计请求数，未匹配的路由统一记为 unmatched 以免标签无限增长
+async fn track_requests(State(server): State<Arc<Server>>, req: Request, next: Next) -> Response {
+    let method = req.method().to_string();
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let response = next.run(req).await;
+    server.metrics.record_request(&method, &route, response.status().as_u16());
+    response
+}
+
+// ============================================================================
+// MCP Handler
+// ============================================================================
+
+#[derive(Deserialize, Debug)]
+struct McpRequest {
+    #[allow(dead_code)]
+    jsonrpc: String,
+    id: Option<serde_json::Value>,
+    method: String,
+    params: Option<serde_json::Value>,
+}
+
+async fn mcp_handler(
+    State(server): State<Arc<Server>>,
+    Json(req): Json<McpRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let result = match req.method.as_str() {
+        "initialize" => {
+            serde_json::json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": { "tools": {} },
+                "serverInfo": {
+                    "name": "vibe-process-bar",
+                    "version": env!("CARGO_PKG_VERSION")
+                },
+                "instructions": "Vibe Process Bar - AI Task Status Tracker."
+            })
+        }
+        "notifications/initialized" => {
+            return (StatusCode::OK, Json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "result": {},
+                "id": req.id
+            })));
+        }
+        "tools/list" => {
+            serde_json::json!({
+                "tools": [
+                    {
+                        "name": "list_tasks",
+                        "description": "Get all IDE windows/tasks",
+                        "inputSchema": { "type": "object", "properties": {}, "required": [] }
+                    },
+                    {
+                        "name": "update_task_status",
+                        "description": "Update a task's status",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "task_id": { "type": "string" },
+                                "status": { "type": "string" }
+                            },
+                            "required": ["task_id", "status"]
+                        }
+                    },
+                    {
+                        "name": "update_task_progress",
+                        "description": "Update task estimated duration and current stage description. Any source can update these fields.",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "task_id": { "type": "string", "description": "The task ID to update" },
+                                "estimated_duration_ms": { "type": "integer", "description": "Estimated total duration in milliseconds" },
+                                "current_stage": { "type": "string", "description": "Current stage description (e.g. 'Analyzing code...', 'Modifying files...')" }
+                            },
+                            "required": ["task_id"]
+                        }
+                    },
+                    {
+                        "name": "complete_task",
+                        "description": "Mark a task as completed and attach a completion report: a markdown summary of what was done, the files changed and artifact links (PR URL, test report, generated docs).",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "task_id": { "type": "string", "description": "The task ID (or pass project_path instead)" },
+                                "project_path": { "type": "string", "description": "Project path used to find the task when task_id is unknown" },
+                                "summary": { "type": "string", "description": "Markdown summary of the work" },
+                                "changed_files": { "type": "array", "items": { "type": "string" } },
+                                "artifacts": {
+                                    "type": "array",
+                                    "items": {
+                                        "type": "object",
+                                        "properties": {
+                                            "kind": { "type": "string", "description": "pr, test_report, doc or link" },
+                                            "label": { "type": "string" },
+                                            "url": { "type": "string", "description": "URL or local file path" }
+                                        },
+                                        "required": ["url"]
+                                    }
+                                }
+                            },
+                            "required": ["summary"]
+                        }
+                    }
+                ]
+            })
+        }
+        "tools/call" => {
+            let params = req.params.unwrap_or(serde_json::json!({}));
+            let tool_name = params.get("name").and_then(|v| v.as_str()).unwrap_or("");
+            let arguments = params.get("arguments").cloned().unwrap_or(serde_json::json!({}));
+
+            match tool_name {
+                "list_tasks" => {
+                    let tasks_vec = server.merged_tasks();
+                    let now = server.now();
+                    let task_list: Vec<serde_json::Value> = tasks_vec.iter().map(|t| {
+                        serde_json::json!({
+                            "id": t.id,
+                            "ide": t.ide,
+                            "window_title": t.window_title,
+                            "project_path": t.project_path,
+                            "active_file": t.active_file,
+                            "status": t.status,
+                            "progress": t.progress(now),
+                            "estimate": t.estimate,
+                            "source": t.source,
+                            "current_stage": t.current_stage,
+                            "overtime": t.overtime,
+                            "possibly_stalled": t.possibly_stalled,
+                            "git": t.git,
+                            "change_summary": t.change_summary,
+                            "completion": t.completion,
+                            "host": t.host,
+                            "stale": t.stale
+                        })
+                    }).collect();
+
+                    serde_json::json!({
+                        "content": [{
+                            "type": "text",
+                            "text": serde_json::to_string_pretty(&task_list).unwrap_or("[]".to_string())
+                        }]
+                    })
+                }
+                "update_task_status" => {
+                    let task_id = arguments.get("task_id").and_then(|v| v.as_str()).unwrap_or("");
+                    let status = arguments.get("status").and_then(|v| v.as_str()).unwrap_or("");
+
+                    let valid_statuses = ["running", "waiting_input", "completed", "error", "cancelled", "armed"];
+                    if !valid_statuses.contains(&status) {
+                        return (StatusCode::OK, Json(serde_json::json!({
+                            "jsonrpc": "2.0",
+                            "error": {"code": -32602, "message": format!("Invalid status '{}'", status)},
+                            "id": req.id
+                        })));
+                    }
+
+                    let old_status = server.engine.get(task_id).map(|t| t.status).unwrap_or_default();
+                    let request = UpdateStateRequest {
+                        task_id: task_id.to_string(),
+                        status: Some(status.to_string()),
+                        source: Some("mcp".to_string()),
+                        ..Default::default()
+                    };
+                    match server.engine.update_state(request) {
+                        Ok(UpdateOutcome::Applied) => serde_json::json!({
+                            "content": [{ "type": "text", "text": format!("Task {} status: {} -> {}", task_id, old_status, status) }]
+                        }),
+                        Ok(UpdateOutcome::Ignored(_)) => {
+                            return (StatusCode::OK, Json(serde_json::json!({
+                                "jsonrpc": "2.0",
+                                "result": {
+                                    "content": [{ "type": "text", "text": "Ignored: higher priority source" }]
+                                },
+                                "id": req.id
+                            })));
+                        }
+                        Err(_) => {
+                            return (StatusCode::OK, Json(serde_json::json!({
+                                "jsonrpc": "2.0",
+                                "error": {"code": -32602, "message": format!("Task not found: {}", task_id)},
+                                "id": req.id
+                            })));
+                        }
+                    }
+                }
+                "update_task_progress" => {
+                    let task_id = arguments.get("task_id").and_then(|v| v.as_str()).unwrap_or("");
+                    let estimated_duration = arguments.get("estimated_duration_ms").and_then(|v| v.as_u64());
+                    let current_stage = arguments.get("current_stage").and_then(|v| v.as_str());
+                    
+                    // 不检查 source 优先级，任何来源都可以更新预估时间和阶段描述
+                    match server.engine.update_progress(task_id, estimated_duration, current_stage) {
+                        Ok(()) => serde_json::json!({
+                            "content": [{
+                                "type": "text",
+                                "text": format!("Updated task {}", task_id)
+                            }]
+                        }),
+                        Err(e) => {
+                            return (StatusCode::OK, Json(serde_json::json!({
+                                "jsonrpc": "2.0",
+                                "error": {"code": -32602, "message": e.to_string()},
+                                "id": req.id
+                            })));
+                        }
+                    }
+                }
+                "complete_task" => {
+                    let request: CompleteTaskRequest = match serde_json::from_value(arguments) {
+                        Ok(r) => r,
+                        Err(e) => {
+                            return (StatusCode::OK, Json(serde_json::json!({
+                                "jsonrpc": "2.0",
+                                "error": {"code": -32602, "message": format!("Invalid arguments: {}", e)},
+                                "id": req.id
+                            })));
+                        }
+                    };
+
+                    match server.engine.complete(request, "mcp") {
+                        Ok(status_changed) => {
+                            let text = if status_changed {
+                                "Task completed, report saved"
+                            } else {
+                                "Report saved (status owned by a higher priority source)"
+                            };
+                            serde_json::json!({
+                                "content": [{ "type": "text", "text": text }]
+                            })
+                        }
+                        Err(e) => {
+                            let message = e.to_string();
+                            let candidates = match e {
+                                EngineError::Ambiguous { candidates, .. } => Some(serde_json::json!(candidates)),
+                                _ => None,
+                            };
+                            return (StatusCode::OK, Json(serde_json::json!({
+                                "jsonrpc": "2.0",
+                                "error": {"code": -32602, "message": message, "data": candidates},
+                                "id": req.id
+                            })));
+                        }
+                    }
+                }
+                _ => {
+                    return (StatusCode::OK, Json(serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "error": {"code": -32601, "message": format!("Unknown tool: {}", tool_name)},
+                        "id": req.id
+                    })));
+                }
+            }
+        }
+        _ => {
+            return (StatusCode::OK, Json(serde_json::json!({
+                "jsonrpc": "2.0",
+                "error": {"code": -32601, "message": format!("Method not found: {}", req.method)},
+                "id": req.id
+            })));
+        }
+    };
+
+    (StatusCode::OK, Json(serde_json::json!({
+        "jsonrpc": "2.0",
+        "result": result,
+        "id": req.id
+    })))
+}
+
+// ============================================================================
+// Server Startup
+// ============================================================================
+
+fn create_cors_layer() -> CorsLayer {
+    CorsLayer::new()
+        .allow_origin(Any)
+        .allow_methods(Any)
+        .allow_headers(Any)
+}
+
+fn create_app(server: Arc<Server>) -> Router {
+    // 这些路由不加 CORS 头，浏览器页面无法跨域读取设置、日志和历史统计
+    let private = Router::new()
+        .route("/api/settings/export", get(export_settings))
+        .route("/api/settings/import", post(import_settings))
+        .route("/api/settings/profiles", get(list_settings_profiles))
+        .route("/api/settings/profiles/{name}/activate", post(activate_settings_profile))
+        .route("/api/stats", get(get_stats))
+        .route("/api/logs", get(get_logs))
+        .route("/metrics", get(get_metrics))
+        .route_layer(middleware::from_fn_with_state(server.clone(), require_private_access));
+
+    Router::new()
+        .route("/api/status", get(get_status))
+        .route("/api/status/line", get(get_status_line))
+        .route("/api/task/report", post(report_task))
+        .route("/api/task/update_state", post(update_state))
+        .route("/api/task/update_state_by_path", post(update_state_by_path))
+        .route("/api/task/token", post(update_tokens))
+        .route("/api/task/delete", post(delete_task))
+        .route("/api/task/complete", post(complete_task))
+        .route("/api/task/reset_armed", post(reset_task_armed))
+        .route("/api/task/activate", post(activate_task))
+        .route("/api/task/{id}/timeline", get(task_timeline))
+        .route("/api/reset", post(reset_tasks))
+        .route("/api/hooks/claude-code", post(claude_code_hook))
+        .route("/api/ingest", get(list_ingest_adapters))
+        .route("/api/ingest/{adapter}", post(ingest_event))
+        .route("/api/federation/push", post(federation_push))
+        .route("/mcp", post(mcp_handler))
+        .layer(create_cors_layer())
+        .merge(private)
+        .layer(middleware::from_fn_with_state(server.clone(), track_requests))
+        .with_state(server)
+}
+
+async fn bind_with_retry(addr: &str) -> std::io::Result<tokio::net::TcpListener> {
+    // 重新绑定到同一端口时，旧 server 可能还没来得及释放监听
+    const ATTEMPTS: u32 = 10;
+    let mut attempt = 1;
+    loop {
+        match tokio::net::TcpListener::bind(addr).await {
+            Ok(listener) => return Ok(listener),
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse && attempt < ATTEMPTS => {
+                attempt += 1;
+                tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+pub async fn start_server(
+    server: Arc<Server>,
+    host: String,
+    port: u16,
+    shutdown: tokio::sync::oneshot::Receiver<()>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let app = create_app(server);
+
+    let addr = format!("{}:{}", host, port);
+    let listener = bind_with_retry(&addr).await?;
+    info!(host = %host, port = %port, "HTTP server listening on {}", addr);
+
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async {
+            let _ = shutdown.await;
+        })
+        .await?;
+    info!(host = %host, port = %port, "HTTP server stopped");
+    Ok(())
+}
+
+struct RunningServer {
+    shutdown: tokio::sync::oneshot::Sender<()>,
+    thread: std::thread::JoinHandle<()>,
+}
+
+/// 实例的 HTTP server，修改地址/端口后可以重新绑定
+#[derive(Default)]
+pub struct HttpServer {
+    running: Mutex<Option<RunningServer>>,
+}
+
+impl HttpServer {
+    /// 在后台线程启动 server；已有 server 在运行时先让其优雅退出（用于修改地址/端口后重新绑定）
+    pub fn start(&self, server: Arc<Server>, host: String, port: u16) {
+        let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+        let mut running = self.running.lock().unwrap();
+        if let Some(previous) = running.take() {
+            info!("Stopping previous HTTP server before rebinding");
+            let _ = previous.shutdown.send(());
+        }
+
+        let thread = std::thread::spawn(move || {
+            let rt = tokio::runtime::Runtime::new().expect("Failed to create Tokio runtime");
+            rt.block_on(async {
+                if let Err(e) = start_server(server, host.clone(), port, shutdown_rx).await {
+                    error!(host = %host, port = %port, error = %e, "HTTP server error");
+                }
+            });
+        });
+        *running = Some(RunningServer { shutdown: shutdown_tx, thread });
+    }
+
+    /// server 是否仍在运行（绑定失败或出错退出后为 false）
+    pub fn is_running(&self) -> bool {
+        self.running
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|server| !server.thread.is_finished())
+    }
+
+    /// 优雅关闭 server，等待处理中的请求完成
+    pub fn stop(&self) {
+        let Some(server) = self.running.lock().unwrap().take() else { return };
+        let _ = server.shutdown.send(());
+        if server.thread.join().is_err() {
+            error!("HTTP server thread panicked");
+        }
+    }
+}
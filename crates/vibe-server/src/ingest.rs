@@ -0,0 +1,509 @@
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tracing::{debug, warn};
+use vibe_core::{EngineError, TaskEngine, UpdateOutcome, UpdateStateRequest, VALID_STATUSES};
+
+use crate::agent_sessions::AgentSessions;
+
+// ============================================================================
+// Ingest 适配器：按声明式的字段映射把任意 agent 的事件 JSON 转换为任务状态
+// ============================================================================
+//
+// 适配器定义是 JSON 文件：内置的随应用发布，用户的放在 <config dir>/adapters/<name>.json，
+// 同名时用户的优先。字段用 `$.a.b[0]['c-d']` 形式的路径从请求体中取值，`$query.name` 取查询参数，
+// 每个字段可以列出多个路径，取第一个非空的值。
+
+const SOURCE: &str = "hook";
+/// 映射为该值时结束会话（见 agent_sessions::end）
+const END_SESSION: &str = "end";
+/// `status` 中匹配任意事件的规则
+const ANY_EVENT: &str = "*";
+pub const UNMAPPED_EVENT: &str = "unmapped_event";
+const MAX_STAGE_LEN: usize = 80;
+const MAX_NAME_LEN: usize = 64;
+
+const BUILTIN_ADAPTERS: [(&str, &str); 4] = [
+    ("codex", include_str!("../adapters/codex.json")),
+    ("gemini", include_str!("../adapters/gemini.json")),
+    ("generic", include_str!("../adapters/generic.json")),
+    ("opencode", include_str!("../adapters/opencode.json")),
+];
+
+// ============================================================================
+// 适配器定义
+// ============================================================================
+
+/// 适配器文件的内容
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default, deny_unknown_fields)]
+pub struct AdapterDefinition {
+    pub description: String,
+    /// 新建任务的 ide，默认为适配器名
+    pub ide: Option<String>,
+    /// 区分会话的值，缺省时用项目路径
+    #[serde(deserialize_with = "one_or_many")]
+    pub task_key: Vec<String>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub project_path: Vec<String>,
+    /// 用于匹配 status 规则的事件名
+    #[serde(deserialize_with = "one_or_many")]
+    pub event: Vec<String>,
+    /// 事件名 -> 任务状态（或 `end`），`*` 匹配其他所有事件
+    pub status: BTreeMap<String, String>,
+    #[serde(deserialize_with = "one_or_many")]
+    pub stage: Vec<String>,
+}
+
+/// 路径字段可以写成单个字符串或字符串数组
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(path) => vec![path],
+        OneOrMany::Many(paths) => paths,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    Key(String),
+    /// 负数从末尾数起
+    Index(i64),
+}
+
+/// 解析后的取值路径
+#[derive(Debug, Clone, PartialEq)]
+enum FieldPath {
+    Body(Vec<Step>),
+    Query(String),
+}
+
+impl FieldPath {
+    fn parse(path: &str) -> Result<Self, String> {
+        if let Some(name) = path.strip_prefix("$query.") {
+            if name.is_empty() {
+                return Err(format!("Empty query parameter in '{}'", path));
+            }
+            return Ok(Self::Query(name.to_string()));
+        }
+        let Some(rest) = path.strip_prefix('$') else {
+            return Err(format!("Path '{}' must start with '$'", path));
+        };
+
+        let mut steps = Vec::new();
+        let mut chars = rest.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '.' => {
+                    let mut key = String::new();
+                    while let Some(&c) = chars.peek() {
+                        if matches!(c, '.' | '[' | ']') {
+                            break;
+                        }
+                        key.push(c);
+                        chars.next();
+                    }
+                    if key.is_empty() {
+                        return Err(format!("Empty key in '{}'", path));
+                    }
+                    steps.push(Step::Key(key));
+                }
+                '[' => {
+                    let step = match chars.peek() {
+                        Some(&quote @ ('\'' | '"')) => {
+                            chars.next();
+                            let key: String = chars.by_ref().take_while(|&c| c != quote).collect();
+                            Step::Key(key)
+                        }
+                        _ => {
+                            let mut index = String::new();
+                            while let Some(&c) = chars.peek() {
+                                if c == ']' {
+                                    break;
+                                }
+                                index.push(c);
+                                chars.next();
+                            }
+                            let parsed = index.trim().parse();
+                            Step::Index(parsed.map_err(|_| format!("Invalid index [{}] in '{}'", index, path))?)
+                        }
+                    };
+                    if chars.next() != Some(']') {
+                        return Err(format!("Unclosed '[' in '{}'", path));
+                    }
+                    steps.push(step);
+                }
+                c => return Err(format!("Unexpected '{}' in '{}'", c, path)),
+            }
+        }
+        Ok(Self::Body(steps))
+    }
+
+    /// 取值：字符串原样返回，数字和布尔转为字符串，其他类型或空字符串返回 None
+    fn select(&self, body: &Value, query: &HashMap<String, String>) -> Option<String> {
+        let value = match self {
+            Self::Query(name) => return query.get(name).map(|v| v.trim().to_string()).filter(|v| !v.is_empty()),
+            Self::Body(steps) => steps.iter().try_fold(body, |value, step| match step {
+                Step::Key(key) => value.get(key),
+                Step::Index(index) => {
+                    let array = value.as_array()?;
+                    let index = if *index < 0 {
+                        array.len().checked_sub(index.unsigned_abs() as usize)?
+                    } else {
+                        *index as usize
+                    };
+                    array.get(index)
+                }
+            })?,
+        };
+        let text = match value {
+            Value::String(s) => s.trim().to_string(),
+            Value::Number(n) => n.to_string(),
+            Value::Bool(b) => b.to_string(),
+            _ => return None,
+        };
+        (!text.is_empty()).then_some(text)
+    }
+}
+
+/// 校验并解析后的适配器
+struct Adapter {
+    ide: String,
+    task_key: Vec<FieldPath>,
+    project_path: Vec<FieldPath>,
+    event: Vec<FieldPath>,
+    status: BTreeMap<String, String>,
+    stage: Vec<FieldPath>,
+}
+
+impl Adapter {
+    fn compile(name: &str, definition: &AdapterDefinition) -> Result<Self, String> {
+        let paths = |paths: &[String]| paths.iter().map(|p| FieldPath::parse(p)).collect::<Result<Vec<_>, _>>();
+        for (event, status) in &definition.status {
+            if status != END_SESSION && !VALID_STATUSES.contains(&status.as_str()) {
+                return Err(format!(
+                    "Invalid status '{}' for event '{}'. Valid: {:?} or \"{}\"",
+                    status, event, VALID_STATUSES, END_SESSION
+                ));
+            }
+        }
+        if definition.task_key.is_empty() && definition.project_path.is_empty() {
+            return Err("taskKey or projectPath is required".to_string());
+        }
+        Ok(Self {
+            ide: definition.ide.clone().filter(|s| !s.trim().is_empty()).unwrap_or_else(|| name.to_string()),
+            task_key: paths(&definition.task_key)?,
+            project_path: paths(&definition.project_path)?,
+            event: paths(&definition.event)?,
+            status: definition.status.clone(),
+            stage: paths(&definition.stage)?,
+        })
+    }
+}
+
+fn first_value(paths: &[FieldPath], body: &Value, query: &HashMap<String, String>) -> Option<String> {
+    paths.iter().find_map(|p| p.select(body, query))
+}
+
+/// 阶段描述只取第一行，过长时截断
+fn clean_stage(stage: &str) -> String {
+    let line = stage.lines().next().unwrap_or_default().trim();
+    if line.chars().count() > MAX_STAGE_LEN {
+        format!("{}…", line.chars().take(MAX_STAGE_LEN - 1).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+// ============================================================================
+// 加载
+// ============================================================================
+
+/// 列表中的一个适配器
+#[derive(Serialize, Debug, Clone)]
+pub struct AdapterInfo {
+    pub name: String,
+    pub description: String,
+    pub builtin: bool,
+    /// 定义无效时的原因
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// 内置适配器和用户适配器（同名覆盖内置）的定义；解析失败的记录错误
+fn load_definitions(dir: Option<&Path>) -> BTreeMap<String, (bool, Result<AdapterDefinition, String>)> {
+    let mut definitions = BTreeMap::new();
+    for (name, content) in BUILTIN_ADAPTERS {
+        definitions.insert(name.to_string(), (true, serde_json::from_str(content).map_err(|e| e.to_string())));
+    }
+
+    let Some(dir) = dir else { return definitions };
+    let Ok(entries) = fs::read_dir(dir) else { return definitions };
+    for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+        if path.extension().is_none_or(|ext| ext != "json") {
+            continue;
+        }
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().to_string()) else { continue };
+        if !valid_name(&name) {
+            warn!(path = ?path, "Ignoring adapter file: name must be letters, digits, '-' or '_'");
+            continue;
+        }
+        let definition = fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str(&content).map_err(|e| e.to_string()));
+        definitions.insert(name, (false, definition));
+    }
+    definitions
+}
+
+/// 加载并编译后的一个适配器
+struct Loaded {
+    builtin: bool,
+    description: String,
+    adapter: Result<Arc<Adapter>, String>,
+}
+
+type LoadedAdapters = Arc<BTreeMap<String, Loaded>>;
+
+fn compile_all(dir: Option<&Path>) -> BTreeMap<String, Loaded> {
+    load_definitions(dir)
+        .into_iter()
+        .map(|(name, (builtin, definition))| {
+            let compiled = definition.and_then(|d| Adapter::compile(&name, &d).map(|a| (d, a)));
+            let loaded = Loaded {
+                builtin,
+                description: compiled.as_ref().map(|(d, _)| d.description.clone()).unwrap_or_default(),
+                adapter: compiled.map(|(_, a)| Arc::new(a)),
+            };
+            (name, loaded)
+        })
+        .collect()
+}
+
+/// 用户适配器目录中各定义文件的名称、修改时间和大小；任何一项变化都需要重新加载
+type DirStamp = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+fn dir_stamp(dir: Option<&Path>) -> DirStamp {
+    let Some(Ok(entries)) = dir.map(fs::read_dir) else { return Vec::new() };
+    let mut stamp: DirStamp = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+        .map(|e| {
+            let metadata = e.metadata().ok();
+            let modified = metadata.as_ref().and_then(|m| m.modified().ok());
+            (e.path(), modified, metadata.map_or(0, |m| m.len()))
+        })
+        .collect();
+    stamp.sort();
+    stamp
+}
+
+// ============================================================================
+// 处理事件
+// ============================================================================
+
+/// 内置适配器和用户适配器目录
+#[derive(Default)]
+pub struct Adapters {
+    /// 用户适配器所在目录（<config dir>/adapters），没有时只有内置适配器
+    dir: Option<PathBuf>,
+    /// 编译结果及其对应的目录状态；每次请求只检查目录，文件有变化时才重新解析
+    cache: Mutex<Option<(DirStamp, LoadedAdapters)>>,
+}
+
+impl Adapters {
+    pub fn new(dir: PathBuf) -> Self {
+        Adapters { dir: Some(dir), cache: Mutex::default() }
+    }
+
+    fn loaded(&self) -> LoadedAdapters {
+        let stamp = dir_stamp(self.dir.as_deref());
+        let mut cache = self.cache.lock().unwrap();
+        if let Some((cached, loaded)) = cache.as_ref() {
+            if *cached == stamp {
+                return loaded.clone();
+            }
+        }
+        let loaded = Arc::new(compile_all(self.dir.as_deref()));
+        *cache = Some((stamp, loaded.clone()));
+        loaded
+    }
+
+    fn load(&self, name: &str) -> Result<Arc<Adapter>, EngineError> {
+        let loaded = self.loaded();
+        let entry = loaded.get(name).ok_or_else(|| EngineError::NotFound(format!("Unknown adapter '{}'", name)))?;
+        entry
+            .adapter
+            .clone()
+            .map_err(|e| EngineError::BadRequest(format!("Adapter '{}' is invalid: {}", name, e)))
+    }
+
+    /// 所有适配器，按名称排序
+    pub fn list(&self) -> Vec<AdapterInfo> {
+        self.loaded()
+            .iter()
+            .map(|(name, loaded)| AdapterInfo {
+                name: name.clone(),
+                description: loaded.description.clone(),
+                builtin: loaded.builtin,
+                error: loaded.adapter.as_ref().err().cloned(),
+            })
+            .collect()
+    }
+
+    /// 按适配器转换一个事件并更新对应任务；请求体为空时只使用查询参数
+    pub fn handle(
+        &self,
+        engine: &TaskEngine,
+        sessions: &AgentSessions,
+        name: &str,
+        body: &[u8],
+        query: &HashMap<String, String>,
+    ) -> Result<UpdateOutcome, EngineError> {
+        let adapter = self.load(name)?;
+        let body: Value = if body.iter().all(u8::is_ascii_whitespace) {
+            Value::Null
+        } else {
+            serde_json::from_slice(body).map_err(|e| EngineError::BadRequest(format!("Invalid JSON body: {}", e)))?
+        };
+
+        let event = first_value(&adapter.event, &body, query);
+        let status = event
+            .as_deref()
+            .and_then(|e| adapter.status.get(e))
+            .or_else(|| adapter.status.get(ANY_EVENT))
+            .cloned();
+        let stage = first_value(&adapter.stage, &body, query).map(|s| clean_stage(&s));
+        if status.is_none() && stage.is_none() {
+            debug!(adapter = %name, event = ?event, "Ignoring unmapped event");
+            return Ok(UpdateOutcome::Ignored(UNMAPPED_EVENT));
+        }
+
+        let project_path = first_value(&adapter.project_path, &body, query);
+        let key = first_value(&adapter.task_key, &body, query)
+            .or_else(|| project_path.clone())
+            .ok_or_else(|| EngineError::BadRequest("Event has no task key or project path".to_string()))?;
+
+        let now = engine.now();
+        sessions.expire(engine, now);
+        if status.as_deref() == Some(END_SESSION) {
+            sessions.end(engine, &adapter.ide, &key);
+            return Ok(UpdateOutcome::Applied);
+        }
+
+        let task_id = sessions.resolve(engine, &adapter.ide, &key, project_path.as_deref(), SOURCE, now)?;
+        debug!(adapter = %name, task_id = %task_id, event = ?event, status = ?status, "Ingested event");
+        engine.update_state(UpdateStateRequest {
+            task_id,
+            status,
+            source: Some(SOURCE.to_string()),
+            estimated_duration: None,
+            current_stage: stage,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn body(steps: &[Step]) -> FieldPath {
+        FieldPath::Body(steps.to_vec())
+    }
+
+    fn key(k: &str) -> Step {
+        Step::Key(k.to_string())
+    }
+
+    #[test]
+    fn parses_field_paths() {
+        let cases = [
+            ("$", body(&[])),
+            ("$.a.b", body(&[key("a"), key("b")])),
+            ("$.a[0]['k']", body(&[key("a"), Step::Index(0), key("k")])),
+            ("$[\"x-y\"][-1]", body(&[key("x-y"), Step::Index(-1)])),
+            ("$.a[ 2 ].b", body(&[key("a"), Step::Index(2), key("b")])),
+            // 引号内的 '.' 和 '[' 属于键名
+            ("$['a.b[c']", body(&[key("a.b[c")])),
+            ("$query.x", FieldPath::Query("x".to_string())),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(FieldPath::parse(path), Ok(expected), "{}", path);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_field_paths() {
+        for path in ["", "a.b", "$query.", "$.", "$.a..b", "$a", "$[x]", "$[1", "$['k'", "$['k'x]", "$.a]"] {
+            assert!(FieldPath::parse(path).is_err(), "{}", path);
+        }
+    }
+
+    #[test]
+    fn selects_values() {
+        let value = json!({"a": [{"k": " v "}, {"k": 3}], "b": true, "c": "", "d": {"e": 1}});
+        let query = HashMap::from([("x".to_string(), " q ".to_string()), ("empty".to_string(), " ".to_string())]);
+        let select = |path: &str| FieldPath::parse(path).unwrap().select(&value, &query);
+        assert_eq!(select("$.a[0]['k']").as_deref(), Some("v"));
+        assert_eq!(select("$.a[-1].k").as_deref(), Some("3"));
+        assert_eq!(select("$.b").as_deref(), Some("true"));
+        assert_eq!(select("$query.x").as_deref(), Some("q"));
+        // 越界、空值、非标量和缺失的参数都视为没有值
+        for path in ["$.a[2].k", "$.a[-3].k", "$.c", "$.d", "$.a.k", "$query.empty", "$query.missing"] {
+            assert_eq!(select(path), None, "{}", path);
+        }
+    }
+
+    fn definition(status: &[(&str, &str)]) -> AdapterDefinition {
+        AdapterDefinition {
+            task_key: vec!["$.session".to_string()],
+            status: status.iter().map(|(e, s)| (e.to_string(), s.to_string())).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn compile_validates_statuses() {
+        let adapter = Adapter::compile("demo", &definition(&[("start", "running"), ("stop", END_SESSION)])).unwrap();
+        assert_eq!(adapter.ide, "demo");
+
+        let err = Adapter::compile("demo", &definition(&[("start", "busy")])).err().unwrap();
+        assert!(err.contains("Invalid status 'busy' for event 'start'"), "{}", err);
+        assert!(Adapter::compile("demo", &definition(&[("start", "Running")])).is_err());
+    }
+
+    #[test]
+    fn compile_requires_a_key_and_valid_paths() {
+        let no_key = AdapterDefinition { task_key: Vec::new(), ..definition(&[]) };
+        assert!(Adapter::compile("demo", &no_key).is_err());
+
+        let bad_path = AdapterDefinition { stage: vec!["stage".to_string()], ..definition(&[]) };
+        let err = Adapter::compile("demo", &bad_path).err().unwrap();
+        assert!(err.contains("must start with '$'"), "{}", err);
+    }
+
+    #[test]
+    fn builtin_adapters_compile() {
+        let adapters = Adapters::default();
+        let list = adapters.list();
+        assert_eq!(list.len(), BUILTIN_ADAPTERS.len());
+        assert!(list.iter().all(|a| a.builtin && a.error.is_none()), "{:?}", list);
+        assert!(Arc::ptr_eq(&adapters.loaded(), &adapters.loaded()));
+    }
+}
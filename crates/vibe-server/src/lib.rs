@@ -0,0 +1,25 @@
+//! Vibe Process Bar 的后台服务：HTTP/MCP server、设置、任务历史、日志与指标
+//!
+//! 桌面应用和无界面守护进程（vibe-processd）共用这些模块，任务逻辑本身在 vibe-core 中。
+
+pub mod agent_sessions;
+pub mod claude_hooks;
+pub mod estimator;
+pub mod federation;
+pub mod focus_tracker;
+pub mod fs_activity;
+pub mod history;
+pub mod http_server;
+pub mod ingest;
+pub mod logging;
+pub mod metrics;
+pub mod process_watcher;
+pub mod server;
+pub mod services;
+pub mod settings;
+pub mod settings_profiles;
+pub mod stats;
+pub mod task_monitor;
+pub mod telemetry;
+pub mod timeline;
+pub mod transcripts;
@@ -0,0 +1,242 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+/// 内存中保留的最近日志条数，供日志查看器使用
+const RING_CAPACITY: usize = 2000;
+/// 日志文件按天滚动，最多保留的文件数
+const MAX_LOG_FILES: usize = 7;
+const LOG_FILE_PREFIX: &str = "vibe-process-bar";
+
+pub const LEVELS: &[&str] = &["trace", "debug", "info", "warn", "error"];
+
+lazy_static::lazy_static! {
+    static ref RING: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::with_capacity(RING_CAPACITY));
+    /// 日志目录确定之前（Tauri setup 之前）为 None，此时只输出到 stdout 和内存
+    static ref LOG_FILE: Mutex<Option<(PathBuf, RollingFileAppender)>> = Mutex::new(None);
+    static ref FILTER_HANDLE: Mutex<Option<reload::Handle<EnvFilter, Registry>>> = Mutex::new(None);
+    /// 启动时设置了 RUST_LOG 则以它为准，忽略设置中的日志级别
+    static ref ENV_OVERRIDE: bool = std::env::var("RUST_LOG").is_ok_and(|v| !v.trim().is_empty());
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogEntry {
+    /// 毫秒时间戳
+    pub timestamp: u64,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// 结构化字段（task_id 等），值统一转为字符串
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty", default)]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+}
+
+fn level_rank(level: &str) -> usize {
+    LEVELS.iter().position(|l| l.eq_ignore_ascii_case(level)).unwrap_or(0)
+}
+
+pub fn is_valid_level(level: &str) -> bool {
+    LEVELS.contains(&level)
+}
+
+// ============================================================================
+// Layers
+// ============================================================================
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.insert(field.name().to_string(), value.into());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let mut text = String::new();
+        let _ = write!(text, "{:?}", value);
+        if field.name() == "message" {
+            self.message = text;
+        } else {
+            self.fields.insert(field.name().to_string(), text.into());
+        }
+    }
+}
+
+/// 把事件写入内存环形缓冲区
+struct RingLayer;
+
+impl<S: Subscriber> Layer<S> for RingLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let entry = LogEntry {
+            timestamp: chrono::Utc::now().timestamp_millis() as u64,
+            level: metadata.level().as_str().to_lowercase(),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+        let mut ring = RING.lock().unwrap();
+        if ring.len() == RING_CAPACITY {
+            ring.pop_front();
+        }
+        ring.push_back(entry);
+    }
+}
+
+/// 写到当前日志文件；日志目录尚未设置时丢弃
+struct LogFileWriter;
+
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match LOG_FILE.lock().unwrap().as_mut() {
+            Some((_, appender)) => appender.write(buf),
+            None => Ok(buf.len()),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match LOG_FILE.lock().unwrap().as_mut() {
+            Some((_, appender)) => appender.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+struct LogFile;
+
+impl<'a> MakeWriter<'a> for LogFile {
+    type Writer = LogFileWriter;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LogFileWriter
+    }
+}
+
+// ============================================================================
+// Setup
+// ============================================================================
+
+fn filter_for(level: &str) -> EnvFilter {
+    EnvFilter::try_new(level).unwrap_or_else(|_| EnvFilter::new("info"))
+}
+
+/// 初始化日志：stdout + 滚动日志文件 + 内存缓冲区，级别可在运行时调整。
+/// 日志文件在 set_log_dir 之后才开始写入。
+pub fn init() {
+    let initial = if *ENV_OVERRIDE {
+        EnvFilter::from_default_env()
+    } else {
+        filter_for("info")
+    };
+    let (filter, handle) = reload::Layer::new(initial);
+    *FILTER_HANDLE.lock().unwrap() = Some(handle);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(LogFile))
+        .with(RingLayer)
+        .init();
+}
+
+/// 设置日志目录（应用日志目录），按天滚动
+pub fn set_log_dir(dir: &Path) -> Result<(), String> {
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix("log")
+        .max_log_files(MAX_LOG_FILES)
+        .build(dir)
+        .map_err(|e| e.to_string())?;
+    *LOG_FILE.lock().unwrap() = Some((dir.to_path_buf(), appender));
+    tracing::info!("Writing logs to {:?}", dir);
+    Ok(())
+}
+
+pub fn log_dir() -> Option<PathBuf> {
+    LOG_FILE.lock().unwrap().as_ref().map(|(dir, _)| dir.clone())
+}
+
+/// 调整日志级别（设置中的 logLevel）
+pub fn set_level(level: &str) {
+    if *ENV_OVERRIDE {
+        tracing::info!(level = %level, "RUST_LOG is set, ignoring log level from settings");
+        return;
+    }
+    if let Some(handle) = FILTER_HANDLE.lock().unwrap().as_ref() {
+        match handle.reload(filter_for(level)) {
+            Ok(()) => tracing::info!(level = %level, "Log level changed"),
+            Err(e) => tracing::error!(error = %e, "Failed to change log level"),
+        }
+    }
+}
+
+// ============================================================================
+// Query
+// ============================================================================
+
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct LogQuery {
+    /// 只返回该时间戳（毫秒）之后的日志
+    #[serde(default)]
+    pub since: Option<u64>,
+    /// 最低级别，默认返回全部
+    #[serde(default)]
+    pub level: Option<String>,
+    /// 最多返回的条数（取最新的），默认 500
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct LogsResponse {
+    pub log_dir: Option<String>,
+    pub entries: Vec<LogEntry>,
+}
+
+/// 最近的日志，按时间顺序（旧的在前）
+pub fn query(query: &LogQuery) -> Result<LogsResponse, String> {
+    let min_rank = match query.level.as_deref() {
+        Some(level) if !is_valid_level(level) => {
+            return Err(format!("Invalid level '{}'. Valid: {:?}", level, LEVELS));
+        }
+        Some(level) => level_rank(level),
+        None => 0,
+    };
+    let limit = query.limit.unwrap_or(500);
+
+    let ring = RING.lock().unwrap();
+    let mut entries: Vec<LogEntry> = ring
+        .iter()
+        .rev()
+        .filter(|e| query.since.is_none_or(|since| e.timestamp > since))
+        .filter(|e| level_rank(&e.level) >= min_rank)
+        .take(limit)
+        .cloned()
+        .collect();
+    entries.reverse();
+
+    Ok(LogsResponse {
+        log_dir: log_dir().map(|d| d.to_string_lossy().to_string()),
+        entries,
+    })
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use vibe_core::Task;
+
+/// 任务时长直方图的桶上界（秒）
+const DURATION_BUCKETS: &[f64] = &[30.0, 60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0];
+
+/// 忽略原因预先注册为 0，没有发生过也会出现在输出中
+const IGNORED_REASONS: &[&str] = &["lower_priority_source", "plugin_status_blocked"];
+
+#[derive(Default)]
+struct Histogram {
+    /// 与 DURATION_BUCKETS 对应的非累计计数
+    buckets: Vec<u64>,
+    count: u64,
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        if self.buckets.is_empty() {
+            self.buckets = vec![0; DURATION_BUCKETS.len()];
+        }
+        if let Some(i) = DURATION_BUCKETS.iter().position(|le| value <= *le) {
+            self.buckets[i] += 1;
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    transitions: BTreeMap<(String, String), u64>,
+    ignored: BTreeMap<String, u64>,
+    http_requests: BTreeMap<(String, String, u16), u64>,
+    durations: BTreeMap<String, Histogram>,
+}
+
+/// 运行期间累计的计数和直方图（只保存在内存中）
+#[derive(Default)]
+pub struct Metrics {
+    counters: Mutex<Counters>,
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+impl Metrics {
+    /// 状态切换（from → to）
+    pub fn record_transition(&self, from: &str, to: &str) {
+        let mut metrics = self.counters.lock().unwrap();
+        *metrics.transitions.entry((from.to_string(), to.to_string())).or_default() += 1;
+    }
+
+    /// 被忽略的更新，reason 同 ApiResponse::ignored
+    pub fn record_ignored(&self, reason: &str) {
+        let mut metrics = self.counters.lock().unwrap();
+        *metrics.ignored.entry(reason.to_string()).or_default() += 1;
+    }
+
+    /// HTTP 请求，route 为匹配到的路由模板（如 /api/task/{id}/timeline）
+    pub fn record_request(&self, method: &str, route: &str, code: u16) {
+        let mut metrics = self.counters.lock().unwrap();
+        *metrics
+            .http_requests
+            .entry((method.to_string(), route.to_string(), code))
+            .or_default() += 1;
+    }
+
+    /// 一次运行结束，按结束状态记录耗时
+    pub fn observe_duration(&self, status: &str, duration_ms: u64) {
+        let mut metrics = self.counters.lock().unwrap();
+        metrics
+            .durations
+            .entry(status.to_string())
+            .or_default()
+            .observe(duration_ms as f64 / 1000.0);
+    }
+
+    /// 生成 Prometheus 文本格式；任务数量和心跳间隔等 gauge 由当前任务列表实时计算
+    pub fn render(&self, tasks: &[Task], now: u64) -> String {
+        let metrics = self.counters.lock().unwrap();
+        let mut out = String::new();
+
+        header(&mut out, "vibe_tasks", "gauge", "Current tasks by status, IDE and source.");
+        let mut by_labels: BTreeMap<(&str, &str, &str), u64> = BTreeMap::new();
+        for task in tasks {
+            *by_labels.entry((&task.status, &task.ide, &task.source)).or_default() += 1;
+        }
+        for ((status, ide, source), count) in by_labels {
+            let _ = writeln!(
+                out,
+                "vibe_tasks{{status=\"{}\",ide=\"{}\",source=\"{}\"}} {}",
+                escape(status),
+                escape(ide),
+                escape(source),
+                count
+            );
+        }
+
+        header(
+            &mut out,
+            "vibe_task_heartbeat_age_seconds",
+            "gauge",
+            "Seconds since the last plugin heartbeat of each task.",
+        );
+        for task in tasks.iter().filter(|t| t.last_heartbeat > 0) {
+            let _ = writeln!(
+                out,
+                "vibe_task_heartbeat_age_seconds{{task_id=\"{}\",ide=\"{}\"}} {:.3}",
+                escape(&task.id),
+                escape(&task.ide),
+                now.saturating_sub(task.last_heartbeat) as f64 / 1000.0
+            );
+        }
+
+        header(&mut out, "vibe_task_transitions_total", "counter", "Task status transitions.");
+        for ((from, to), count) in &metrics.transitions {
+            let _ = writeln!(
+                out,
+                "vibe_task_transitions_total{{from=\"{}\",to=\"{}\"}} {}",
+                escape(from),
+                escape(to),
+                count
+            );
+        }
+
+        header(
+            &mut out,
+            "vibe_ignored_updates_total",
+            "counter",
+            "Updates ignored because of source priority or plugin status blocking.",
+        );
+        let mut ignored: BTreeMap<&str, u64> = IGNORED_REASONS.iter().map(|r| (*r, 0)).collect();
+        for (reason, count) in &metrics.ignored {
+            ignored.insert(reason, *count);
+        }
+        for (reason, count) in ignored {
+            let _ = writeln!(out, "vibe_ignored_updates_total{{reason=\"{}\"}} {}", escape(reason), count);
+        }
+
+        header(&mut out, "vibe_http_requests_total", "counter", "HTTP requests by method, route and status code.");
+        for ((method, route, code), count) in &metrics.http_requests {
+            let _ = writeln!(
+                out,
+                "vibe_http_requests_total{{method=\"{}\",route=\"{}\",code=\"{}\"}} {}",
+                escape(method),
+                escape(route),
+                code,
+                count
+            );
+        }
+
+        header(&mut out, "vibe_task_duration_seconds", "histogram", "Duration of finished task runs by final status.");
+        for (status, histogram) in &metrics.durations {
+            let status = escape(status);
+            let mut cumulative = 0;
+            for (le, count) in DURATION_BUCKETS.iter().zip(&histogram.buckets) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "vibe_task_duration_seconds_bucket{{status=\"{}\",le=\"{}\"}} {}",
+                    status, le, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "vibe_task_duration_seconds_bucket{{status=\"{}\",le=\"+Inf\"}} {}",
+                status, histogram.count
+            );
+            let _ = writeln!(out, "vibe_task_duration_seconds_sum{{status=\"{}\"}} {}", status, histogram.sum);
+            let _ = writeln!(out, "vibe_task_duration_seconds_count{{status=\"{}\"}} {}", status, histogram.count);
+        }
+
+        out
+    }
+}
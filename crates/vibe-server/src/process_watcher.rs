@@ -0,0 +1,379 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+use vibe_core::status_line::last_component;
+use vibe_core::{ReportRequest, TaskEngine, UpdateStateRequest};
+
+// ============================================================================
+// 进程监视：扫描 /proc 发现终端里运行的 agent CLI（仅 Linux）
+// ============================================================================
+//
+// 每个 agent 进程对应一个任务 `process-<pid>`，项目路径为进程的 cwd。进程树的 CPU 占用
+// 区分运行中和空闲（等待输入），进程退出时按退出码标记 completed / error。
+// 来源为 process，优先级最低：hook、MCP 或插件接管同一任务后，这里的状态更新会被忽略。
+
+const SCAN_INTERVAL: Duration = Duration::from_secs(2);
+/// 进程树 CPU 占用达到该百分比视为运行中
+const ACTIVE_CPU_PERCENT: u64 = 3;
+/// 持续低于阈值超过该时长后视为空闲
+const IDLE_AFTER_MS: u64 = 10_000;
+/// 进程退出后任务保留的时长
+const EXITED_TASK_TTL_MS: u64 = 10 * 60 * 1000;
+/// Linux 的 USER_HZ，/proc/<pid>/stat 中 CPU 时间的单位
+const CLOCK_TICKS_PER_SEC: u64 = 100;
+const SOURCE: &str = "process";
+/// 解释器启动的 CLI（`node .../gemini`、`python .../aider`）按脚本名匹配
+const INTERPRETERS: [&str; 5] = ["node", "bun", "deno", "python", "ruby"];
+/// 与 hook 集成使用相同的 ide，便于识别同一会话的重复任务
+const IDE_ALIASES: [(&str, &str); 1] = [("claude", "claude-code")];
+
+pub const DEFAULT_AGENTS: [&str; 6] = ["claude", "codex", "gemini", "aider", "opencode", "goose"];
+
+#[derive(Default)]
+struct WatcherConfig {
+    enabled: bool,
+    agents: Vec<String>,
+}
+
+/// 扫描线程的配置，线程在首次启用时启动，引擎释放后退出
+#[derive(Default)]
+pub struct ProcessWatcher {
+    config: Arc<Mutex<WatcherConfig>>,
+    started: AtomicBool,
+}
+
+impl ProcessWatcher {
+    /// 由设置同步（processWatcher / processWatcherAgents），首次启用时启动扫描线程
+    pub fn configure(&self, engine: &Arc<TaskEngine>, enabled: bool, agents: &[String]) {
+        {
+            let mut config = self.config.lock().unwrap();
+            config.enabled = enabled;
+            config.agents = agents.iter().map(|a| a.trim().to_string()).filter(|a| !a.is_empty()).collect();
+        }
+        if !enabled {
+            return;
+        }
+        if !cfg!(target_os = "linux") {
+            warn!("processWatcher is enabled but only works on Linux");
+            return;
+        }
+        if !self.started.swap(true, Ordering::SeqCst) {
+            info!("Process watcher started");
+            let (engine, config) = (Arc::downgrade(engine), self.config.clone());
+            std::thread::spawn(move || run(engine, config));
+        }
+    }
+}
+
+/// agent 名称是否合法（可执行文件名，不含路径）
+pub fn is_valid_agent(name: &str) -> bool {
+    let name = name.trim();
+    !name.is_empty() && !name.contains(['/', '\\'])
+}
+
+fn ide_for(agent: &str) -> String {
+    IDE_ALIASES
+        .iter()
+        .find(|(name, _)| *name == agent)
+        .map(|(_, ide)| ide.to_string())
+        .unwrap_or_else(|| agent.to_string())
+}
+
+// ============================================================================
+// /proc
+// ============================================================================
+
+struct ProcStat {
+    pid: u32,
+    ppid: u32,
+    state: char,
+    /// 开机后的启动时间，和 pid 一起识别进程（pid 可能被复用）
+    start_time: u64,
+    /// utime + stime
+    cpu_ticks: u64,
+    /// 僵尸进程的 wait 状态
+    exit_code: Option<i32>,
+}
+
+fn read_stat(pid: u32) -> Option<ProcStat> {
+    let content = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // comm 可能包含空格和括号，从最后一个 ')' 之后开始按空格分隔，fields[0] 是第 3 个字段
+    let rest = &content[content.rfind(')')? + 1..];
+    let fields: Vec<&str> = rest.split_whitespace().collect();
+    let field = |n: usize| fields.get(n - 3).and_then(|f| f.parse::<u64>().ok());
+    Some(ProcStat {
+        pid,
+        ppid: field(4)? as u32,
+        state: fields.first()?.chars().next()?,
+        start_time: field(22)?,
+        cpu_ticks: field(14)? + field(15)?,
+        exit_code: field(52).map(|c| c as i32),
+    })
+}
+
+fn read_cmdline(pid: u32) -> Vec<String> {
+    fs::read(format!("/proc/{}/cmdline", pid))
+        .map(|bytes| {
+            bytes
+                .split(|b| *b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 其他用户的进程读不到 cwd，不会被跟踪
+fn read_cwd(pid: u32) -> Option<String> {
+    fs::read_link(format!("/proc/{}/cwd", pid)).ok().map(|p| p.to_string_lossy().to_string())
+}
+
+/// 进程对应的 agent：comm、可执行文件名，或解释器运行的脚本名
+fn match_agent(pid: u32, agents: &[String]) -> Option<&String> {
+    let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap_or_default();
+    let args = read_cmdline(pid);
+    let mut names = vec![comm.trim().to_string()];
+    if let Some(exe) = args.first().map(|a| last_component(a).to_string()) {
+        if INTERPRETERS.iter().any(|i| exe.starts_with(i)) {
+            if let Some(script) = args.iter().skip(1).find(|a| !a.starts_with('-')) {
+                let script = last_component(script);
+                names.push(script.split('.').next().unwrap_or(script).to_string());
+            }
+        }
+        names.push(exe);
+    }
+    agents.iter().find(|agent| names.iter().any(|n| n == *agent))
+}
+
+fn scan_processes() -> HashMap<u32, ProcStat> {
+    let Ok(entries) = fs::read_dir("/proc") else { return HashMap::new() };
+    entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| e.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(read_stat)
+        .map(|stat| (stat.pid, stat))
+        .collect()
+}
+
+/// 进程及其所有子孙进程的 CPU 时间之和
+fn tree_ticks(pid: u32, processes: &HashMap<u32, ProcStat>, children: &HashMap<u32, Vec<u32>>) -> u64 {
+    let mut total = 0;
+    let mut stack = vec![pid];
+    while let Some(pid) = stack.pop() {
+        total += processes.get(&pid).map(|p| p.cpu_ticks).unwrap_or(0);
+        stack.extend(children.get(&pid).into_iter().flatten());
+    }
+    total
+}
+
+/// 进程所有子孙进程的 cwd（不含自身），焦点跟踪用它把终端窗口对应到项目
+pub fn descendant_cwds(pid: u32) -> HashSet<String> {
+    let processes = scan_processes();
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for p in processes.values() {
+        children.entry(p.ppid).or_default().push(p.pid);
+    }
+    let mut cwds = HashSet::new();
+    let mut stack: Vec<u32> = children.get(&pid).cloned().unwrap_or_default();
+    while let Some(pid) = stack.pop() {
+        cwds.extend(read_cwd(pid));
+        stack.extend(children.get(&pid).into_iter().flatten());
+    }
+    cwds
+}
+
+// ============================================================================
+// 扫描
+// ============================================================================
+
+struct Tracked {
+    task_id: String,
+    start_time: u64,
+    last_ticks: u64,
+    /// 最近一次 CPU 占用超过阈值的时间，0 表示还没有活动过
+    last_active: u64,
+    running: bool,
+    /// 退出时间；任务保留到 EXITED_TASK_TTL_MS 后删除
+    exited_at: Option<u64>,
+    /// 已有 hook 等来源的任务对应同一会话，不创建自己的任务
+    suppressed: bool,
+    /// 最近一次看到的僵尸进程退出状态
+    exit_code: Option<i32>,
+}
+
+fn run(engine: Weak<TaskEngine>, config: Arc<Mutex<WatcherConfig>>) {
+    let mut tracked: HashMap<u32, Tracked> = HashMap::new();
+    let mut last_scan = 0;
+    loop {
+        std::thread::sleep(SCAN_INTERVAL);
+        let Some(engine) = engine.upgrade() else { return };
+        let now = engine.now();
+        if last_scan == 0 {
+            last_scan = now;
+        }
+        let (enabled, agents) = {
+            let config = config.lock().unwrap();
+            (config.enabled, config.agents.clone())
+        };
+        if enabled {
+            scan(&engine, &mut tracked, &agents, now, now.saturating_sub(last_scan).max(1));
+        } else if !tracked.is_empty() {
+            for (_, t) in tracked.drain() {
+                let _ = engine.delete(&t.task_id);
+            }
+            info!("Process watcher disabled, removed its tasks");
+        }
+        last_scan = now;
+    }
+}
+
+fn scan(engine: &TaskEngine, tracked: &mut HashMap<u32, Tracked>, agents: &[String], now: u64, elapsed_ms: u64) {
+    let processes = scan_processes();
+    let mut children: HashMap<u32, Vec<u32>> = HashMap::new();
+    for p in processes.values() {
+        children.entry(p.ppid).or_default().push(p.pid);
+    }
+
+    // 只跟踪每棵进程树中最上层的 agent 进程（npm 包装脚本会再启动同名的原生程序）
+    let matched: HashMap<u32, &String> = processes
+        .values()
+        .filter(|p| p.state != 'Z')
+        .filter_map(|p| match_agent(p.pid, agents).map(|agent| (p.pid, agent)))
+        .collect();
+    let is_nested = |pid: u32, agent: &String| {
+        let mut current = processes.get(&pid).map(|p| p.ppid);
+        let mut seen = HashSet::new();
+        while let Some(ppid) = current.filter(|p| *p > 1 && seen.insert(*p)) {
+            if matched.get(&ppid) == Some(&agent) {
+                return true;
+            }
+            current = processes.get(&ppid).map(|p| p.ppid);
+        }
+        false
+    };
+
+    for (&pid, &agent) in &matched {
+        if is_nested(pid, agent) {
+            continue;
+        }
+        let start_time = processes[&pid].start_time;
+        if tracked.get(&pid).is_some_and(|t| t.start_time == start_time) {
+            continue;
+        }
+        // pid 被复用：旧进程的任务还在保留期内
+        if let Some(old) = tracked.remove(&pid) {
+            let _ = engine.delete(&old.task_id);
+        }
+        let Some(cwd) = read_cwd(pid) else { continue };
+        let ide = ide_for(agent);
+        let task_id = format!("process-{}", pid);
+        let suppressed = has_other_task(engine, &task_id, &ide, &cwd);
+        if !suppressed {
+            let name = last_component(&cwd).to_string();
+            let request = ReportRequest {
+                task_id: task_id.clone(),
+                name: name.clone(),
+                ide,
+                window_title: name,
+                is_focused: false,
+                project_path: Some(cwd),
+                active_file: None,
+            };
+            if engine.register(request, SOURCE).is_err() {
+                continue;
+            }
+            info!(pid = pid, agent = %agent, task_id = %task_id, "Agent process detected");
+        }
+        let last_ticks = tree_ticks(pid, &processes, &children);
+        tracked.insert(pid, Tracked {
+            task_id,
+            start_time,
+            last_ticks,
+            last_active: 0,
+            running: false,
+            exited_at: None,
+            suppressed,
+            exit_code: None,
+        });
+    }
+
+    tracked.retain(|pid, t| {
+        if let Some(exited_at) = t.exited_at {
+            let keep = now.saturating_sub(exited_at) < EXITED_TASK_TTL_MS;
+            if !keep {
+                let _ = engine.delete(&t.task_id);
+            }
+            return keep;
+        }
+
+        let alive = processes.get(pid).filter(|p| p.start_time == t.start_time);
+        match alive {
+            Some(p) if p.state != 'Z' => {
+                update_activity(engine, t, tree_ticks(*pid, &processes, &children), now, elapsed_ms);
+                true
+            }
+            zombie => {
+                if let Some(code) = zombie.and_then(|p| p.exit_code) {
+                    t.exit_code = Some(code);
+                }
+                process_exited(engine, *pid, t, now)
+            }
+        }
+    });
+}
+
+/// 同一 ide、同一项目已有其他来源的任务（如 Claude Code hook 新建的会话任务）
+fn has_other_task(engine: &TaskEngine, task_id: &str, ide: &str, cwd: &str) -> bool {
+    engine
+        .tasks()
+        .iter()
+        .any(|t| t.id != task_id && t.source != SOURCE && t.ide == ide && t.project_path.as_deref() == Some(cwd))
+}
+
+fn set_status(engine: &TaskEngine, task_id: &str, status: &str) {
+    let result = engine.update_state(UpdateStateRequest {
+        task_id: task_id.to_string(),
+        status: Some(status.to_string()),
+        source: Some(SOURCE.to_string()),
+        ..Default::default()
+    });
+    if let Err(e) = result {
+        debug!(task_id = %task_id, error = %e, "Process watcher update failed");
+    }
+}
+
+fn update_activity(engine: &TaskEngine, t: &mut Tracked, ticks: u64, now: u64, elapsed_ms: u64) {
+    let delta = ticks.saturating_sub(t.last_ticks);
+    t.last_ticks = ticks;
+    if delta * 1000 * 100 >= ACTIVE_CPU_PERCENT * CLOCK_TICKS_PER_SEC * elapsed_ms {
+        t.last_active = now;
+    }
+    if t.suppressed {
+        return;
+    }
+
+    let running = t.last_active > 0 && now.saturating_sub(t.last_active) < IDLE_AFTER_MS;
+    if running != t.running {
+        t.running = running;
+        // 空闲的交互式 agent 在等待用户输入
+        set_status(engine, &t.task_id, if running { "running" } else { "waiting_input" });
+    }
+}
+
+/// 进程退出：没有运行过的任务直接删除，否则按退出码标记 completed / error 并保留一段时间
+///
+/// 只有在父进程回收之前扫描到僵尸进程时才能拿到退出码，拿不到时视为正常退出。
+fn process_exited(engine: &TaskEngine, pid: u32, t: &mut Tracked, now: u64) -> bool {
+    if t.suppressed || t.last_active == 0 {
+        let _ = engine.delete(&t.task_id);
+        return false;
+    }
+    let failed = t.exit_code.is_some_and(|status| status != 0);
+    info!(pid = pid, task_id = %t.task_id, exit_status = ?t.exit_code, "Agent process exited");
+    set_status(engine, &t.task_id, if failed { "error" } else { "completed" });
+    t.exited_at = Some(now);
+    true
+}
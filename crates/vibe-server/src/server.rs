@@ -0,0 +1,202 @@
+use std::sync::{Arc, Mutex, Weak};
+
+use vibe_core::git_context;
+use vibe_core::{Clock, EngineEvent, EventSink, Task, TaskEngine, TimelineEvent, TimelineEventKind};
+
+use crate::agent_sessions::AgentSessions;
+use crate::estimator::Estimator;
+use crate::federation::{Federation, RelayConfig};
+use crate::focus_tracker::FocusTracker;
+use crate::fs_activity::FsActivity;
+use crate::history::History;
+use crate::http_server::{HttpServer, SettingsBridge, WindowBridge};
+use crate::ingest::Adapters;
+use crate::metrics::Metrics;
+use crate::process_watcher::ProcessWatcher;
+use crate::telemetry::Telemetry;
+use crate::timeline::Timelines;
+use crate::transcripts::Transcripts;
+
+// ============================================================================
+// 服务实例：任务引擎和围绕它的所有服务
+// ============================================================================
+//
+// 宿主应用（桌面应用、vibe-processd）通过 services::start 创建一个实例，HTTP server、后台线程和命令都经由它访问任务，
+// 同一进程内可以有多个互不影响的实例（例如测试）。只有日志是进程级的。
+
+pub struct Server {
+    pub engine: Arc<TaskEngine>,
+    pub timelines: Timelines,
+    pub history: History,
+    pub metrics: Metrics,
+    pub estimator: Estimator,
+    pub telemetry: Telemetry,
+    pub federation: Federation,
+    pub sessions: Arc<AgentSessions>,
+    pub adapters: Adapters,
+    pub process_watcher: ProcessWatcher,
+    pub fs_activity: FsActivity,
+    pub transcripts: Transcripts,
+    pub focus_tracker: FocusTracker,
+    pub http: HttpServer,
+    /// 设置相关路由的实现，由宿主应用注册
+    settings_bridge: Mutex<Option<Arc<dyn SettingsBridge>>>,
+    /// 激活 IDE 窗口的实现，由桌面应用注册
+    window_bridge: Mutex<Option<Arc<dyn WindowBridge>>>,
+}
+
+impl Server {
+    pub fn new(clock: Arc<dyn Clock>, history: History, adapters: Adapters) -> Arc<Self> {
+        Arc::new_cyclic(|server| Server {
+            engine: Arc::new(TaskEngine::new(clock, Arc::new(ServerEventSink(server.clone())))),
+            timelines: Timelines::default(),
+            history,
+            metrics: Metrics::default(),
+            estimator: Estimator::default(),
+            telemetry: Telemetry::default(),
+            federation: Federation::default(),
+            sessions: Arc::new(AgentSessions::default()),
+            adapters,
+            process_watcher: ProcessWatcher::default(),
+            fs_activity: FsActivity::default(),
+            transcripts: Transcripts::default(),
+            focus_tracker: FocusTracker::default(),
+            http: HttpServer::default(),
+            settings_bridge: Mutex::new(None),
+            window_bridge: Mutex::new(None),
+        })
+    }
+
+    pub fn now(&self) -> u64 {
+        self.engine.now()
+    }
+
+    pub fn set_settings_bridge(&self, bridge: Arc<dyn SettingsBridge>) {
+        *self.settings_bridge.lock().unwrap() = Some(bridge);
+    }
+
+    pub fn settings_bridge(&self) -> Option<Arc<dyn SettingsBridge>> {
+        self.settings_bridge.lock().unwrap().clone()
+    }
+
+    pub fn set_window_bridge(&self, bridge: Arc<dyn WindowBridge>) {
+        *self.window_bridge.lock().unwrap() = Some(bridge);
+    }
+
+    pub fn window_bridge(&self) -> Option<Arc<dyn WindowBridge>> {
+        self.window_bridge.lock().unwrap().clone()
+    }
+
+    // ========================================================================
+    // 任务列表
+    // ========================================================================
+
+    /// 本机的任务列表（清理过期任务），并为运行中的任务附上预估
+    pub fn local_tasks(&self) -> Vec<Task> {
+        let now = self.now();
+        self.sessions.expire(&self.engine, now);
+        let mut tasks = self.engine.merged_tasks();
+        for task in tasks.iter_mut().filter(|t| t.status == "running") {
+            task.estimate = self.estimator.estimate(&self.history, &self.timelines, task, now);
+        }
+        tasks
+    }
+
+    /// 合并后的任务列表：本机任务在前，其后是远程实例转发来的任务
+    pub fn merged_tasks(&self) -> Vec<Task> {
+        let mut tasks = self.local_tasks();
+        tasks.extend(self.federation.remote_tasks(self.now()));
+        tasks
+    }
+
+    /// 当前任务的时间线；任务已被清理时返回最近一次历史记录中保存的时间线
+    pub fn task_timeline(&self, task_id: &str) -> Option<Vec<TimelineEvent>> {
+        self.timelines
+            .get(task_id)
+            .or_else(|| self.history.latest(task_id).map(|e| e.timeline))
+    }
+
+    /// 把本机任务转发到上游实例，见 Federation::start_relay
+    pub fn start_relay(self: &Arc<Self>, config: RelayConfig) {
+        let server = Arc::downgrade(self);
+        self.federation.start_relay(config, move || {
+            server.upgrade().map(|s| s.local_tasks()).unwrap_or_default()
+        });
+    }
+
+    // ========================================================================
+    // Background Work
+    // ========================================================================
+
+    /// 在后台线程解析 git 信息（git 命令可能较慢，不能在锁内执行）
+    fn refresh_git_context(&self, task: &Task) {
+        let Some(project_path) = task.project_path.clone() else { return };
+        let task_id = task.id.clone();
+        let start_time = task.start_time;
+        let engine = self.engine.clone();
+
+        std::thread::spawn(move || {
+            let context = git_context::resolve(&project_path);
+            // 解析期间任务可能已重新开始，只写回同一次运行
+            engine.update_run(&task_id, start_time, |task| task.git = context);
+        });
+    }
+
+    /// 任务结束：在后台线程统计改动，写回任务并记录到历史
+    fn finish_task(self: Arc<Self>, snapshot: Task) {
+        std::thread::spawn(move || {
+            let mut snapshot = snapshot;
+            snapshot.change_summary = snapshot.git.as_ref().and_then(|git| {
+                let base = git.head_at_start.as_deref()?;
+                git_context::change_summary(&git.repo_root, base)
+            });
+
+            let change_summary = snapshot.change_summary.clone();
+            if let Some(completion) = self.engine.update_run(&snapshot.id, snapshot.start_time, |task| {
+                task.change_summary = change_summary;
+                task.completion.clone()
+            }) {
+                // 完成报告可能在状态切换之后才提交
+                snapshot.completion = completion;
+            }
+
+            let events = self.timelines.since(&snapshot.id, snapshot.start_time);
+            self.history.record(snapshot.clone(), events.clone(), self.now());
+            self.telemetry.export(&snapshot, &events);
+        });
+    }
+}
+
+/// 把引擎事件接到时间线、指标、git 解析和历史记录上
+struct ServerEventSink(Weak<Server>);
+
+impl EventSink for ServerEventSink {
+    fn emit(&self, event: EngineEvent) {
+        let Some(server) = self.0.upgrade() else { return };
+        match event {
+            EngineEvent::Timeline { task_id, event } => {
+                if let TimelineEventKind::Status { from, to } = &event.kind {
+                    server.metrics.record_transition(from, to);
+                }
+                server.timelines.record(&task_id, event);
+            }
+            EngineEvent::UpdateIgnored { reason } => server.metrics.record_ignored(&reason),
+            EngineEvent::TaskRegistered { task } | EngineEvent::RunStarted { task } => {
+                server.refresh_git_context(&task)
+            }
+            EngineEvent::RunEnded { task } => {
+                let end = task.end_time.unwrap_or(task.start_time);
+                server.metrics.observe_duration(&task.status, end.saturating_sub(task.start_time));
+                server.finish_task(task);
+            }
+            EngineEvent::RunAmended { task } => {
+                // 任务已结束并写入历史，补充完成报告
+                server
+                    .history
+                    .update(&task.id, task.start_time, |entry| entry.task.completion = task.completion.clone());
+            }
+            EngineEvent::TasksRemoved { task_ids } => server.timelines.remove(&task_ids),
+            EngineEvent::TasksCleared => server.timelines.clear(),
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use tracing::{error, info};
+use vibe_core::SystemClock;
+
+use crate::history::History;
+use crate::ingest::Adapters;
+use crate::logging;
+use crate::server::Server;
+use crate::settings::{AppSettings, SettingsChange};
+
+/// 与桌面应用相同的 bundle identifier，守护进程和桌面应用共用同一份设置与历史
+pub const APP_IDENTIFIER: &str = "com.vibe.processbar";
+
+/// 设置、数据和日志目录
+#[derive(Debug, Clone)]
+pub struct AppDirs {
+    pub config_dir: PathBuf,
+    pub data_dir: PathBuf,
+    pub log_dir: PathBuf,
+}
+
+impl AppDirs {
+    /// 与 Tauri 的 app_config_dir / app_data_dir / app_log_dir 相同的默认位置
+    pub fn default_dirs() -> Option<Self> {
+        let config_dir = dirs::config_dir()?.join(APP_IDENTIFIER);
+        let data_dir = dirs::data_dir()?.join(APP_IDENTIFIER);
+        #[cfg(target_os = "macos")]
+        let log_dir = dirs::home_dir()?.join("Library/Logs").join(APP_IDENTIFIER);
+        #[cfg(target_os = "windows")]
+        let log_dir = dirs::data_local_dir()?.join(APP_IDENTIFIER).join("logs");
+        #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+        let log_dir = data_dir.join("logs");
+
+        Some(AppDirs { config_dir, data_dir, log_dir })
+    }
+
+    pub fn settings_path(&self) -> PathBuf {
+        self.config_dir.join("settings.json")
+    }
+
+    pub fn history_path(&self) -> PathBuf {
+        self.data_dir.join("history.json")
+    }
+
+    /// 用户自定义的 ingest 适配器
+    pub fn adapters_dir(&self) -> PathBuf {
+        self.config_dir.join("adapters")
+    }
+}
+
+/// 创建服务实例并按设置启动后台服务：日志文件、任务历史、ingest 适配器、插件屏蔽、OTLP 导出、联邦接收、进程监视、文件活动、会话记录、焦点跟踪和 HTTP server
+pub fn start(settings: &AppSettings, dirs: &AppDirs) -> Arc<Server> {
+    logging::set_level(&settings.log_level);
+    if let Err(e) = logging::set_log_dir(&dirs.log_dir) {
+        error!(error = %e, "Failed to open log file, logging to stdout only");
+    }
+
+    let server = Server::new(
+        Arc::new(SystemClock),
+        History::load(dirs.history_path()),
+        Adapters::new(dirs.adapters_dir()),
+    );
+    let engine = &server.engine;
+    engine.set_block_plugin_status(settings.block_plugin_status);
+    server.telemetry.configure(settings.otlp_enabled, &settings.otlp_endpoint);
+    server.federation.configure(&settings.federation_token, &settings.federation_path_mappings);
+    server.process_watcher.configure(engine, settings.process_watcher, &settings.process_watcher_agents);
+    server.fs_activity.configure(engine, settings.into());
+    server
+        .transcripts
+        .configure(engine, &server.sessions, settings.transcript_watcher, &settings.transcript_dirs);
+    server.focus_tracker.configure(engine, settings.focus_tracking);
+    server.http.start(server.clone(), settings.http_host.clone(), settings.http_port);
+    info!(host = %settings.http_host, port = %settings.http_port, "HTTP server started");
+    server
+}
+
+/// 把设置变化同步到后台服务（屏蔽设置、日志级别、OTLP 导出、联邦接收、进程监视、文件活动、会话记录、焦点跟踪，地址变化时重新绑定）
+pub fn apply_change(server: &Arc<Server>, settings: &AppSettings, change: &SettingsChange) {
+    let engine = &server.engine;
+    if change.changed.contains_key("blockPluginStatus") {
+        engine.set_block_plugin_status(settings.block_plugin_status);
+    }
+
+    if change.changed.contains_key("httpHost") || change.changed.contains_key("httpPort") {
+        info!(host = %settings.http_host, port = %settings.http_port, "HTTP server address changed, rebinding");
+        server.http.start(server.clone(), settings.http_host.clone(), settings.http_port);
+    }
+
+    if change.changed.contains_key("logLevel") {
+        logging::set_level(&settings.log_level);
+    }
+
+    if change.changed.contains_key("otlpEnabled") || change.changed.contains_key("otlpEndpoint") {
+        server.telemetry.configure(settings.otlp_enabled, &settings.otlp_endpoint);
+    }
+
+    if change.changed.contains_key("federationToken") || change.changed.contains_key("federationPathMappings") {
+        server.federation.configure(&settings.federation_token, &settings.federation_path_mappings);
+    }
+
+    if change.changed.contains_key("processWatcher") || change.changed.contains_key("processWatcherAgents") {
+        server.process_watcher.configure(engine, settings.process_watcher, &settings.process_watcher_agents);
+    }
+
+    if change.changed.keys().any(|key| key.starts_with("fsActivity")) {
+        server.fs_activity.configure(engine, settings.into());
+    }
+
+    if change.changed.contains_key("transcriptWatcher") || change.changed.contains_key("transcriptDirs") {
+        server
+            .transcripts
+            .configure(engine, &server.sessions, settings.transcript_watcher, &settings.transcript_dirs);
+    }
+
+    if change.changed.contains_key("focusTracking") {
+        server.focus_tracker.configure(engine, settings.focus_tracking);
+    }
+}
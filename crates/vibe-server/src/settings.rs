@@ -0,0 +1,670 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::federation::PathMapping;
+
+/// 当前设置文件版本，每次调整字段结构时递增并在 MIGRATIONS 中追加一步迁移
+pub const SETTINGS_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// 设置文件版本，缺失时视为 1（加入版本号之前的文件）
+    pub version: u32,
+    pub theme: String,
+    pub opacity: f64,
+    pub font_size: i32,
+    pub always_on_top: bool,
+    pub auto_start: bool,
+    pub sound: bool,
+    pub sound_volume: f64,
+    pub http_host: String,
+    pub http_port: u16,
+    pub window_visible: bool,
+    pub language: String,
+    pub block_plugin_status: bool,
+    pub window_x: Option<f64>,
+    pub window_y: Option<f64>,
+    pub show_only_when_running: bool,
+    /// 运行中任务的超时/卡住告警
+    pub task_alerts: bool,
+    pub overtime_alert_percent: u32,
+    pub stall_alert_minutes: u64,
+    /// 当前使用的命名 profile（profiles/<name>.json）
+    pub active_profile: Option<String>,
+    /// 任务结束时以 OTLP trace 导出
+    pub otlp_enabled: bool,
+    /// OTLP/HTTP collector 地址
+    pub otlp_endpoint: String,
+    /// 日志级别（trace / debug / info / warn / error），启动时设置了 RUST_LOG 则以环境变量为准
+    pub log_level: String,
+    /// 接收远程实例推送任务所需的 Bearer token，为空时不接收
+    pub federation_token: String,
+    /// 远程项目路径到本机路径的映射，按顺序取第一个匹配的前缀
+    pub federation_path_mappings: Vec<PathMapping>,
+    /// 扫描 /proc 自动发现 agent CLI 进程（仅 Linux）
+    pub process_watcher: bool,
+    /// 要识别的 agent 可执行文件名
+    pub process_watcher_agents: Vec<String>,
+    /// 从项目目录的写入频率推断任务状态
+    pub fs_activity: bool,
+    /// 窗口内至少多少次写入视为运行中
+    pub fs_activity_min_writes: u32,
+    pub fs_activity_window_seconds: u64,
+    /// 多少秒没有写入后标记为完成
+    pub fs_activity_idle_seconds: u64,
+    /// 跟随 agent 的 JSONL 会话记录推断任务状态
+    pub transcript_watcher: bool,
+    /// 会话记录所在目录，`~` 表示家目录
+    pub transcript_dirs: Vec<String>,
+    /// 跟踪系统前台窗口来更新任务的焦点状态（仅 Linux）
+    pub focus_tracking: bool,
+    /// 只显示前台 IDE 窗口所在项目的任务
+    pub follow_focus: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            theme: "dark".to_string(),
+            opacity: 0.85,
+            font_size: 14,
+            always_on_top: true,
+            auto_start: false,
+            sound: true,
+            sound_volume: 0.7,
+            http_host: "127.0.0.1".to_string(),
+            http_port: 31415,
+            window_visible: true,
+            language: "en".to_string(),
+            block_plugin_status: true,
+            window_x: None,
+            window_y: None,
+            show_only_when_running: false,
+            task_alerts: true,
+            overtime_alert_percent: 50,
+            stall_alert_minutes: 20,
+            active_profile: None,
+            otlp_enabled: false,
+            otlp_endpoint: "http://127.0.0.1:4318".to_string(),
+            log_level: "info".to_string(),
+            federation_token: String::new(),
+            federation_path_mappings: Vec::new(),
+            process_watcher: true,
+            process_watcher_agents: crate::process_watcher::DEFAULT_AGENTS.iter().map(|a| a.to_string()).collect(),
+            fs_activity: false,
+            fs_activity_min_writes: 5,
+            fs_activity_window_seconds: 10,
+            fs_activity_idle_seconds: 30,
+            transcript_watcher: true,
+            transcript_dirs: crate::transcripts::DEFAULT_DIRS.iter().map(|d| d.to_string()).collect(),
+            focus_tracking: true,
+            follow_focus: false,
+        }
+    }
+}
+
+// ============================================================================
+// Migrations
+// ============================================================================
+
+/// MIGRATIONS[i] 将版本 i+1 的设置迁移到版本 i+2
+const MIGRATIONS: &[fn(&mut Map<String, Value>)] = &[migrate_v1_to_v2];
+
+/// v1 -> v2: 旧版（SQLite 时代）使用 snake_case 键名，并带有已移除功能的字段
+fn migrate_v1_to_v2(map: &mut Map<String, Value>) {
+    const RENAMED: &[(&str, &str)] = &[
+        ("font_size", "fontSize"),
+        ("always_on_top", "alwaysOnTop"),
+        ("auto_start", "autoStart"),
+        ("sound_volume", "soundVolume"),
+        ("http_host", "httpHost"),
+        ("http_port", "httpPort"),
+        ("window_visible", "windowVisible"),
+        ("block_plugin_status", "blockPluginStatus"),
+    ];
+    const REMOVED: &[&str] = &[
+        "notifications",
+        "customColors",
+        "custom_colors",
+        "reminderThreshold",
+        "reminder_threshold",
+        "doNotDisturb",
+        "do_not_disturb",
+        "doNotDisturbStart",
+        "do_not_disturb_start",
+        "doNotDisturbEnd",
+        "do_not_disturb_end",
+    ];
+
+    for (old, new) in RENAMED {
+        if let Some(value) = map.remove(*old) {
+            map.entry(new.to_string()).or_insert(value);
+        }
+    }
+    for key in REMOVED {
+        map.remove(*key);
+    }
+}
+
+pub fn migrate(map: &mut Map<String, Value>, from_version: u32) {
+    let start = from_version.max(1) as usize - 1;
+    for (i, step) in MIGRATIONS.iter().enumerate().skip(start) {
+        step(map);
+        info!("Settings migrated from v{} to v{}", i + 1, i + 2);
+    }
+}
+
+// ============================================================================
+// Loading
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SettingsWarningKind {
+    /// 启动加载时字段无法解析，已回退为默认值
+    Reset,
+    /// 外部修改的文件未通过校验，未被应用
+    Rejected,
+}
+
+/// 设置告警，通过 `settings-warning` 事件告知用户
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsWarning {
+    pub kind: SettingsWarningKind,
+    pub keys: Vec<String>,
+    pub backup_path: Option<String>,
+}
+
+/// load_from_file 的附加结果
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    /// 原文件的版本（低于当前版本时说明发生了迁移）
+    pub file_version: Option<u32>,
+    /// 无法解析或取值非法、已回退为默认值的字段
+    pub reset_keys: Vec<String>,
+}
+
+impl LoadReport {
+    fn needs_rewrite(&self) -> bool {
+        !self.reset_keys.is_empty() || self.file_version.is_some_and(|v| v < SETTINGS_VERSION)
+    }
+}
+
+impl AppSettings {
+    /// 取值范围检查，返回非法字段的键名（camelCase）
+    pub fn invalid_keys(&self) -> Vec<&'static str> {
+        let mut keys = Vec::new();
+        if !(0.0..=1.0).contains(&self.opacity) {
+            keys.push("opacity");
+        }
+        if !(8..=48).contains(&self.font_size) {
+            keys.push("fontSize");
+        }
+        if !(0.0..=1.0).contains(&self.sound_volume) {
+            keys.push("soundVolume");
+        }
+        if self.http_host.trim().is_empty() {
+            keys.push("httpHost");
+        }
+        if self.http_port == 0 {
+            keys.push("httpPort");
+        }
+        if self.stall_alert_minutes == 0 {
+            keys.push("stallAlertMinutes");
+        }
+        if !(self.otlp_endpoint.starts_with("http://") || self.otlp_endpoint.starts_with("https://")) {
+            keys.push("otlpEndpoint");
+        }
+        if !crate::logging::is_valid_level(&self.log_level) {
+            keys.push("logLevel");
+        }
+        if !self.federation_path_mappings.iter().all(PathMapping::is_valid) {
+            keys.push("federationPathMappings");
+        }
+        if !self.process_watcher_agents.iter().all(|a| crate::process_watcher::is_valid_agent(a)) {
+            keys.push("processWatcherAgents");
+        }
+        if self.fs_activity_min_writes == 0 {
+            keys.push("fsActivityMinWrites");
+        }
+        if self.fs_activity_window_seconds == 0 {
+            keys.push("fsActivityWindowSeconds");
+        }
+        if self.fs_activity_idle_seconds == 0 {
+            keys.push("fsActivityIdleSeconds");
+        }
+        if self.transcript_dirs.iter().any(|d| d.trim().is_empty()) {
+            keys.push("transcriptDirs");
+        }
+        keys
+    }
+
+    /// 逐字段恢复：在默认值之上逐个应用文件中的字段，保留所有合法取值
+    pub fn from_value_lenient(value: Value) -> (Self, LoadReport) {
+        let defaults = match serde_json::to_value(Self::default()) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+
+        let mut map = match value {
+            Value::Object(map) => map,
+            other => {
+                warn!("Settings root is not an object: {}", other);
+                let report = LoadReport {
+                    file_version: None,
+                    reset_keys: defaults.keys().cloned().collect(),
+                };
+                return (Self::default(), report);
+            }
+        };
+
+        let file_version = map
+            .get("version")
+            .and_then(Value::as_u64)
+            .map(|v| v as u32)
+            .unwrap_or(1);
+        migrate(&mut map, file_version);
+        map.remove("version");
+
+        let mut report = LoadReport {
+            file_version: Some(file_version),
+            reset_keys: Vec::new(),
+        };
+        let mut merged = defaults.clone();
+        for (key, value) in map {
+            if !defaults.contains_key(&key) {
+                debug!(key = %key, "Dropping unknown settings key");
+                continue;
+            }
+            let mut candidate = merged.clone();
+            candidate.insert(key.clone(), value.clone());
+            match serde_json::from_value::<Self>(Value::Object(candidate)) {
+                Ok(_) => {
+                    merged.insert(key, value);
+                }
+                Err(e) => {
+                    warn!(key = %key, error = %e, "Invalid settings value, using default");
+                    report.reset_keys.push(key);
+                }
+            }
+        }
+
+        let mut settings: Self = serde_json::from_value(Value::Object(merged)).unwrap_or_default();
+        let invalid = settings.invalid_keys();
+        if !invalid.is_empty() {
+            let mut fixed = serde_json::to_value(&settings).unwrap_or(Value::Null);
+            for key in invalid {
+                warn!(key = %key, "Settings value out of range, using default");
+                if let (Some(obj), Some(default)) = (fixed.as_object_mut(), defaults.get(key)) {
+                    obj.insert(key.to_string(), default.clone());
+                }
+                report.reset_keys.push(key.to_string());
+            }
+            settings = serde_json::from_value(fixed).unwrap_or_default();
+        }
+
+        settings.version = SETTINGS_VERSION;
+        (settings, report)
+    }
+
+    pub fn load_from_file(path: &PathBuf) -> (Self, LoadReport) {
+        if !path.exists() {
+            return (Self::default(), LoadReport::default());
+        }
+
+        match fs::read_to_string(path) {
+            Ok(content) => match serde_json::from_str::<Value>(&content) {
+                Ok(value) => Self::from_value_lenient(value),
+                Err(e) => {
+                    error!("Failed to parse settings JSON: {}", e);
+                    Self::from_value_lenient(Value::Null)
+                }
+            },
+            Err(e) => {
+                error!("Failed to read settings file: {}", e);
+                (Self::default(), LoadReport::default())
+            }
+        }
+    }
+
+    /// 原子写入：先写临时文件并 fsync，再 rename 覆盖，避免写到一半崩溃损坏文件
+    pub fn save_to_file(&self, path: &PathBuf) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+
+        let content = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        write_atomic(path, content.as_bytes())?;
+        info!("Settings saved to {:?}", path);
+        Ok(())
+    }
+}
+
+/// 先写入同目录下的 <文件名>.tmp 并落盘，再重命名覆盖，中途崩溃或断电不会留下写了一半的文件
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    let file_name = path.file_name().ok_or_else(|| format!("Invalid file path {:?}", path))?;
+    let tmp_path = path.with_file_name(format!("{}.tmp", file_name.to_string_lossy()));
+    {
+        let mut file = fs::File::create(&tmp_path).map_err(|e| e.to_string())?;
+        file.write_all(content).map_err(|e| e.to_string())?;
+        file.sync_all().map_err(|e| e.to_string())?;
+    }
+    fs::rename(&tmp_path, path).map_err(|e| e.to_string())?;
+
+    // rename 本身也需要落盘
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().and_then(|p| fs::File::open(p).ok()) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// 覆盖前备份原文件：settings.json -> settings.json.<时间戳>.bak
+fn backup_settings_file(path: &Path) -> Option<PathBuf> {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let file_name = path.file_name()?.to_string_lossy();
+    let backup = path.with_file_name(format!("{}.{}.bak", file_name, stamp));
+    match fs::copy(path, &backup) {
+        Ok(_) => {
+            info!("Settings backed up to {:?}", backup);
+            Some(backup)
+        }
+        Err(e) => {
+            error!("Failed to back up settings file: {}", e);
+            None
+        }
+    }
+}
+
+/// 一次设置变更的结果，也是 `settings-changed` 事件的内容：只包含实际变化的键
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsChange {
+    pub revision: u64,
+    /// 变化的键（camelCase）及其新值
+    pub changed: Map<String, Value>,
+}
+
+/// 当前设置及其修订号，前端以此作为 patch 的 base_revision
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsSnapshot {
+    pub revision: u64,
+    pub settings: AppSettings,
+}
+
+/// RFC 7386 JSON Merge Patch：null 表示删除（即恢复默认值），对象递归合并，其余直接替换
+pub fn merge_patch(target: &mut Value, patch: &Value) {
+    let Value::Object(patch_map) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target_map) = target {
+        for (key, value) in patch_map {
+            if value.is_null() {
+                target_map.remove(key);
+            } else {
+                merge_patch(target_map.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+struct SettingsInner {
+    settings: AppSettings,
+    /// 每次实际变更递增
+    revision: u64,
+    /// 每个键最后一次变更时的修订号，用于按键检测冲突
+    key_revisions: HashMap<String, u64>,
+}
+
+pub struct SettingsState {
+    inner: Mutex<SettingsInner>,
+    pub path: PathBuf,
+    /// 启动时的加载告警，前端启动后通过 take_settings_warning 取走
+    pub load_warning: Mutex<Option<SettingsWarning>>,
+}
+
+impl SettingsState {
+    /// 加载设置文件（应用配置目录下的 settings.json），无法解析的键重置为默认值并备份原文件
+    pub fn load(path: PathBuf) -> Self {
+        let (settings, report) = AppSettings::load_from_file(&path);
+        info!("Settings loaded from {:?}", path);
+
+        let mut load_warning = None;
+        if report.needs_rewrite() {
+            let backup = backup_settings_file(&path);
+            if let Err(e) = settings.save_to_file(&path) {
+                error!("Failed to rewrite settings file: {}", e);
+            }
+            if !report.reset_keys.is_empty() {
+                warn!(keys = ?report.reset_keys, "Settings keys reset to defaults");
+                load_warning = Some(SettingsWarning {
+                    kind: SettingsWarningKind::Reset,
+                    keys: report.reset_keys,
+                    backup_path: backup.map(|p| p.to_string_lossy().to_string()),
+                });
+            }
+        }
+
+        Self {
+            inner: Mutex::new(SettingsInner {
+                settings,
+                revision: 0,
+                key_revisions: HashMap::new(),
+            }),
+            path,
+            load_warning: Mutex::new(load_warning),
+        }
+    }
+
+    pub fn get_settings(&self) -> AppSettings {
+        self.inner.lock().unwrap().settings.clone()
+    }
+
+    pub fn snapshot(&self) -> SettingsSnapshot {
+        let inner = self.inner.lock().unwrap();
+        SettingsSnapshot {
+            revision: inner.revision,
+            settings: inner.settings.clone(),
+        }
+    }
+
+    pub fn take_load_warning(&self) -> Option<SettingsWarning> {
+        self.load_warning.lock().unwrap().take()
+    }
+
+    /// 在锁内原子地应用一个 JSON merge patch
+    ///
+    /// 指定 `base_revision` 时，如果 patch 中的任一键在该修订之后被其他窗口改过，则拒绝并返回冲突。
+    /// `persist` 为 false 时不写文件（用于应用外部修改过的 settings.json）。
+    pub fn patch(
+        &self,
+        patch: &Value,
+        base_revision: Option<u64>,
+        persist: bool,
+    ) -> Result<SettingsChange, String> {
+        let mut inner = self
+            .inner
+            .lock()
+            .map_err(|_| "Failed to lock settings mutex")?;
+
+        if let (Some(base), Value::Object(patch_map)) = (base_revision, patch) {
+            let conflicts: Vec<&String> = patch_map
+                .keys()
+                .filter(|key| inner.key_revisions.get(*key).is_some_and(|rev| *rev > base))
+                .collect();
+            if !conflicts.is_empty() {
+                return Err(format!(
+                    "Settings conflict: {:?} changed since revision {} (current {})",
+                    conflicts, base, inner.revision
+                ));
+            }
+        }
+
+        let old_value = serde_json::to_value(&inner.settings).map_err(|e| e.to_string())?;
+        let mut new_value = old_value.clone();
+        merge_patch(&mut new_value, patch);
+
+        let mut new_settings: AppSettings =
+            serde_json::from_value(new_value).map_err(|e| format!("Invalid settings: {}", e))?;
+        new_settings.version = SETTINGS_VERSION;
+        let invalid = new_settings.invalid_keys();
+        if !invalid.is_empty() {
+            return Err(format!("Invalid settings values: {:?}", invalid));
+        }
+
+        let new_value = serde_json::to_value(&new_settings).map_err(|e| e.to_string())?;
+        let changed = changed_keys(&old_value, &new_value);
+
+        if changed.is_empty() {
+            return Ok(SettingsChange {
+                revision: inner.revision,
+                changed,
+            });
+        }
+
+        // 先落盘再提交到内存，写文件失败时内存中的设置保持不变
+        if persist {
+            new_settings.save_to_file(&self.path)?;
+        }
+
+        inner.revision += 1;
+        let revision = inner.revision;
+        for key in changed.keys() {
+            inner.key_revisions.insert(key.clone(), revision);
+        }
+        inner.settings = new_settings;
+
+        Ok(SettingsChange { revision, changed })
+    }
+}
+
+// ============================================================================
+// External Edits
+// ============================================================================
+
+/// new 中与 old 不同的顶层键及其新值
+fn changed_keys(old: &Value, new: &Value) -> Map<String, Value> {
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => new
+            .iter()
+            .filter(|(key, value)| old.get(*key) != Some(*value))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect(),
+        _ => Map::new(),
+    }
+}
+
+/// settings.json 被外部修改后的校验结果
+pub enum ExternalChange {
+    /// 与当前设置不同的键，作为 patch 应用，不覆盖同时在应用内修改的其他键
+    Valid(Map<String, Value>),
+    Invalid(SettingsWarning),
+}
+
+/// 校验外部修改后的文件；只要有字段非法就整体拒绝，不改写用户正在编辑的文件
+fn read_external_change(path: &Path, current: &AppSettings) -> Option<ExternalChange> {
+    let content = fs::read_to_string(path).ok()?;
+    let value = match serde_json::from_str::<Value>(&content) {
+        Ok(value) => value,
+        Err(e) => {
+            warn!("Ignoring external settings edit - invalid JSON: {}", e);
+            return Some(ExternalChange::Invalid(SettingsWarning {
+                kind: SettingsWarningKind::Rejected,
+                keys: Vec::new(),
+                backup_path: None,
+            }));
+        }
+    };
+
+    let (settings, report) = AppSettings::from_value_lenient(value);
+    if report.reset_keys.is_empty() {
+        let (old, new) = (serde_json::to_value(current).ok()?, serde_json::to_value(&settings).ok()?);
+        Some(ExternalChange::Valid(changed_keys(&old, &new)))
+    } else {
+        warn!(keys = ?report.reset_keys, "Ignoring external settings edit - invalid keys");
+        Some(ExternalChange::Invalid(SettingsWarning {
+            kind: SettingsWarningKind::Rejected,
+            keys: report.reset_keys,
+            backup_path: None,
+        }))
+    }
+}
+
+/// 监听 settings.json 的外部修改（手动编辑、dotfile 管理工具等）
+///
+/// 监听的是所在目录而不是文件本身：原子写入会替换文件，直接监听文件会在第一次 rename 后失效。
+/// 内容与当前设置一致的变更（包括本程序自己的写入）会被忽略。
+pub fn watch_settings_file<F>(path: PathBuf, current: impl Fn() -> AppSettings + Send + 'static, on_change: F)
+where
+    F: Fn(ExternalChange) + Send + 'static,
+{
+    use notify::{RecursiveMode, Watcher};
+
+    const DEBOUNCE: Duration = Duration::from_millis(300);
+
+    let Some(dir) = path.parent().map(Path::to_path_buf) else {
+        return;
+    };
+    let file_name = path.file_name().map(|n| n.to_os_string());
+    let touches_settings = move |event: &notify::Event| {
+        (event.kind.is_create() || event.kind.is_modify())
+            && event.paths.iter().any(|p| p.file_name() == file_name.as_deref())
+    };
+
+    std::thread::spawn(move || {
+        let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+        let mut watcher = match notify::recommended_watcher(tx) {
+            Ok(w) => w,
+            Err(e) => {
+                error!("Failed to create settings watcher: {}", e);
+                return;
+            }
+        };
+        if let Err(e) = fs::create_dir_all(&dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| watcher.watch(&dir, RecursiveMode::NonRecursive).map_err(|e| e.to_string()))
+        {
+            error!("Failed to watch settings dir {:?}: {}", dir, e);
+            return;
+        }
+        info!("Watching {:?} for external changes", path);
+
+        while let Ok(res) = rx.recv() {
+            let Ok(event) = res else { continue };
+            if !touches_settings(&event) {
+                continue;
+            }
+            // 编辑器保存往往产生多次事件，等待安静下来再读取
+            while rx.recv_timeout(DEBOUNCE).is_ok() {}
+
+            match read_external_change(&path, &current()) {
+                Some(ExternalChange::Valid(patch)) => {
+                    if patch.is_empty() {
+                        debug!("Settings file change matches current settings, skipping");
+                        continue;
+                    }
+                    info!(keys = ?patch.keys().collect::<Vec<_>>(), "Applying external settings change");
+                    on_change(ExternalChange::Valid(patch));
+                }
+                Some(invalid) => on_change(invalid),
+                None => {}
+            }
+        }
+    });
+}
@@ -0,0 +1,198 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+use crate::settings::{AppSettings, SETTINGS_VERSION};
+
+/// 与本机相关的键，不参与导入/导出和 profile 切换（窗口位置不应随团队配置漂移，token 不应随配置外传）
+pub const LOCAL_KEYS: &[&str] = &[
+    "windowX",
+    "windowY",
+    "windowVisible",
+    "activeProfile",
+    "federationToken",
+    "federationPathMappings",
+];
+
+const MAX_PROFILE_NAME_LEN: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingDiff {
+    pub key: String,
+    pub current: Value,
+    pub incoming: Value,
+}
+
+/// 导入预览：实际会变化的键，以及被忽略/拒绝的键
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreview {
+    pub changes: Vec<SettingDiff>,
+    /// 取值非法，导入时跳过
+    pub rejected_keys: Vec<String>,
+    /// 未知键或本机相关的键，导入时忽略
+    pub ignored_keys: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProfileList {
+    pub profiles: Vec<String>,
+    pub active: Option<String>,
+}
+
+/// 导出内容：当前设置去掉本机相关的键
+pub fn export_value(settings: &AppSettings) -> Value {
+    let mut value = serde_json::to_value(settings).unwrap_or(Value::Null);
+    if let Value::Object(map) = &mut value {
+        for key in LOCAL_KEYS {
+            map.remove(*key);
+        }
+    }
+    value
+}
+
+pub fn write_settings_file(settings: &AppSettings, path: &Path) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(&export_value(settings)).map_err(|e| e.to_string())?;
+    fs::write(path, content).map_err(|e| e.to_string())?;
+    info!("Settings exported to {:?}", path);
+    Ok(())
+}
+
+pub fn read_settings_file(path: &Path) -> Result<Value, String> {
+    let content = fs::read_to_string(path).map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Invalid settings JSON: {}", e))
+}
+
+/// 校验导入内容，返回可直接交给 SettingsState::patch 的 patch 及预览
+///
+/// 导入文件中缺失的键保持当前值不变；非法的键被跳过而不是回退为默认值。
+pub fn prepare_import(current: &AppSettings, incoming: Value) -> Result<(Value, ImportPreview), String> {
+    let Value::Object(mut map) = incoming else {
+        return Err("Settings import must be a JSON object".to_string());
+    };
+
+    let version = map.get("version").and_then(Value::as_u64).unwrap_or(1) as u32;
+    if version > SETTINGS_VERSION {
+        return Err(format!(
+            "Settings file version {} is newer than supported version {}",
+            version, SETTINGS_VERSION
+        ));
+    }
+    crate::settings::migrate(&mut map, version);
+    map.remove("version");
+
+    let current_value = serde_json::to_value(current).map_err(|e| e.to_string())?;
+    let Value::Object(current_map) = &current_value else {
+        return Err("Failed to serialize current settings".to_string());
+    };
+
+    let mut preview = ImportPreview::default();
+    let mut patch = Map::new();
+    for (key, value) in map {
+        if LOCAL_KEYS.contains(&key.as_str()) || !current_map.contains_key(&key) {
+            preview.ignored_keys.push(key);
+            continue;
+        }
+
+        let mut candidate = current_value.clone();
+        if let Value::Object(candidate_map) = &mut candidate {
+            candidate_map.insert(key.clone(), value.clone());
+        }
+        let valid = serde_json::from_value::<AppSettings>(candidate)
+            .map(|s| !s.invalid_keys().contains(&key.as_str()))
+            .unwrap_or(false);
+        if !valid {
+            warn!(key = %key, "Rejected invalid imported settings value");
+            preview.rejected_keys.push(key);
+            continue;
+        }
+
+        let current = current_map.get(&key).cloned().unwrap_or(Value::Null);
+        if current != value {
+            preview.changes.push(SettingDiff {
+                key: key.clone(),
+                current,
+                incoming: value.clone(),
+            });
+        }
+        patch.insert(key, value);
+    }
+
+    Ok((Value::Object(patch), preview))
+}
+
+// ============================================================================
+// Named Profiles
+// ============================================================================
+
+/// profile 保存在 settings.json 同级的 profiles/ 目录下
+pub fn profiles_dir(settings_path: &Path) -> PathBuf {
+    settings_path
+        .parent()
+        .map(|p| p.join("profiles"))
+        .unwrap_or_else(|| PathBuf::from("profiles"))
+}
+
+fn validate_profile_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_PROFILE_NAME_LEN
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid profile name '{}': use letters, digits, spaces, '-' or '_' (max {} chars)",
+            name, MAX_PROFILE_NAME_LEN
+        ))
+    }
+}
+
+pub fn profile_path(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    validate_profile_name(name)?;
+    Ok(dir.join(format!("{}.json", name)))
+}
+
+pub fn list_profiles(dir: &Path) -> Vec<String> {
+    let mut profiles: Vec<String> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.extension().is_some_and(|ext| ext == "json"))
+                .filter_map(|p| p.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+    profiles.sort();
+    profiles
+}
+
+pub fn save_profile(dir: &Path, name: &str, settings: &AppSettings) -> Result<(), String> {
+    let path = profile_path(dir, name)?;
+    write_settings_file(settings, &path)?;
+    info!(profile = %name, "Settings profile saved");
+    Ok(())
+}
+
+pub fn load_profile(dir: &Path, name: &str) -> Result<Value, String> {
+    let path = profile_path(dir, name)?;
+    if !path.exists() {
+        return Err(format!("Profile not found: {}", name));
+    }
+    read_settings_file(&path)
+}
+
+pub fn delete_profile(dir: &Path, name: &str) -> Result<(), String> {
+    let path = profile_path(dir, name)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete profile {}: {}", name, e))?;
+    info!(profile = %name, "Settings profile deleted");
+    Ok(())
+}
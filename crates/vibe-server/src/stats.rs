@@ -0,0 +1,323 @@
+use chrono::{Datelike, Local, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use vibe_core::Task;
+
+use crate::history::{History, HistoryEntry};
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+
+/// 查询参数：group_by 为逗号分隔的维度（project / ide / source / day / week），
+/// from / to 为毫秒时间戳，按任务开始时间过滤 [from, to)
+#[derive(Deserialize, Debug, Default, Clone)]
+pub struct StatsQuery {
+    #[serde(default)]
+    pub group_by: Option<String>,
+    #[serde(default)]
+    pub from: Option<u64>,
+    #[serde(default)]
+    pub to: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Dimension {
+    Project,
+    Ide,
+    Source,
+    Day,
+    Week,
+}
+
+impl Dimension {
+    fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "project" => Ok(Self::Project),
+            "ide" => Ok(Self::Ide),
+            "source" => Ok(Self::Source),
+            "day" => Ok(Self::Day),
+            "week" => Ok(Self::Week),
+            other => Err(format!("Unknown group_by dimension: {} (expected project, ide, source, day or week)", other)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Project => "project",
+            Self::Ide => "ide",
+            Self::Source => "source",
+            Self::Day => "day",
+            Self::Week => "week",
+        }
+    }
+
+    /// 时间维度按本地时间、以任务开始时间归属
+    fn value(self, task: &Task) -> String {
+        match self {
+            Self::Project => project_of(task),
+            Self::Ide => task.ide.clone(),
+            Self::Source => task.source.clone(),
+            Self::Day => local(task.start_time).map(|t| t.format("%Y-%m-%d").to_string()).unwrap_or_default(),
+            Self::Week => local(task.start_time)
+                .map(|t| {
+                    let week = t.iso_week();
+                    format!("{}-W{:02}", week.year(), week.week())
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+fn local(ms: u64) -> Option<chrono::DateTime<Local>> {
+    Local.timestamp_millis_opt(ms as i64).single()
+}
+
+fn project_of(task: &Task) -> String {
+    task.git
+        .as_ref()
+        .map(|g| g.repo_root.as_str())
+        .or(task.project_path.as_deref())
+        .map(|p| p.trim_end_matches(['/', '\\']).to_string())
+        .unwrap_or_default()
+}
+
+/// 一组运行的汇总
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct GroupStats {
+    /// 分组维度 → 值，不分组时为空
+    pub key: BTreeMap<String, String>,
+    pub runs: u32,
+    pub completed: u32,
+    pub errors: u32,
+    pub cancelled: u32,
+    /// agent 运行的总时长（毫秒）
+    pub active_ms: u64,
+    /// 成功完成的运行耗时的中位数 / p90
+    pub median_ms: Option<u64>,
+    pub p90_ms: Option<u64>,
+}
+
+/// 本地时间某个小时（0-23）内的运行时长
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct HourStats {
+    pub hour: u32,
+    pub active_ms: u64,
+    /// 在这个小时内开始的运行数
+    pub runs_started: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Stats {
+    pub from: Option<u64>,
+    pub to: Option<u64>,
+    /// 历史中最早 / 最晚一次已结束运行的开始时间，与 from / to 无关；
+    /// 历史只保留最近 MAX_HISTORY_ENTRIES 条记录，早于 history_from 的运行不在统计内
+    pub history_from: Option<u64>,
+    pub history_to: Option<u64>,
+    pub group_by: Vec<String>,
+    pub total: GroupStats,
+    pub groups: Vec<GroupStats>,
+    /// 24 个小时，按 hour 排序
+    pub busiest_hours: Vec<HourStats>,
+}
+
+#[derive(Default)]
+struct Accumulator {
+    stats: GroupStats,
+    durations: Vec<u64>,
+}
+
+impl Accumulator {
+    fn add(&mut self, task: &Task, duration: u64) {
+        self.stats.runs += 1;
+        self.stats.active_ms += duration;
+        match task.status.as_str() {
+            "completed" => {
+                self.stats.completed += 1;
+                self.durations.push(duration);
+            }
+            "error" => self.stats.errors += 1,
+            "cancelled" => self.stats.cancelled += 1,
+            _ => {}
+        }
+    }
+
+    fn finish(mut self) -> GroupStats {
+        self.durations.sort_unstable();
+        self.stats.median_ms = percentile(&self.durations, 50);
+        self.stats.p90_ms = percentile(&self.durations, 90);
+        self.stats
+    }
+}
+
+/// 最近秩法的百分位数
+fn percentile(sorted: &[u64], p: usize) -> Option<u64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = (p * sorted.len()).div_ceil(100).max(1);
+    Some(sorted[rank - 1])
+}
+
+/// 把一次运行按本地小时拆分累加到 hours
+fn add_hours(hours: &mut [HourStats], start: u64, end: u64) {
+    if let Some(t) = local(start) {
+        hours[t.hour() as usize].runs_started += 1;
+    }
+    let mut at = start;
+    while at < end {
+        let Some(t) = local(at) else { break };
+        let into_hour = (t.minute() as u64 * 60 + t.second() as u64) * 1000 + at % 1000;
+        let next = (at + HOUR_MS - into_hour).min(end);
+        hours[t.hour() as usize].active_ms += next - at;
+        at = next;
+    }
+}
+
+/// 对历史记录做统计，只统计已结束（completed / error / cancelled）的运行
+pub fn compute(entries: &[HistoryEntry], query: &StatsQuery) -> Result<Stats, String> {
+    let dimensions = query
+        .group_by
+        .as_deref()
+        .unwrap_or("")
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(Dimension::parse)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut total = Accumulator::default();
+    let mut groups: BTreeMap<Vec<String>, Accumulator> = BTreeMap::new();
+    let mut hours: Vec<HourStats> = (0..24).map(|hour| HourStats { hour, ..Default::default() }).collect();
+    let (mut history_from, mut history_to) = (None::<u64>, None::<u64>);
+
+    for entry in entries {
+        let task = &entry.task;
+        if !matches!(task.status.as_str(), "completed" | "error" | "cancelled") || task.start_time == 0 {
+            continue;
+        }
+        history_from = Some(history_from.map_or(task.start_time, |t| t.min(task.start_time)));
+        history_to = Some(history_to.map_or(task.start_time, |t| t.max(task.start_time)));
+        if query.from.is_some_and(|from| task.start_time < from) || query.to.is_some_and(|to| task.start_time >= to) {
+            continue;
+        }
+        let end = task.end_time.unwrap_or(task.start_time).max(task.start_time);
+        let duration = end - task.start_time;
+
+        total.add(task, duration);
+        if !dimensions.is_empty() {
+            let key = dimensions.iter().map(|d| d.value(task)).collect();
+            groups.entry(key).or_default().add(task, duration);
+        }
+        add_hours(&mut hours, task.start_time, end);
+    }
+
+    let groups = groups
+        .into_iter()
+        .map(|(values, acc)| {
+            let mut stats = acc.finish();
+            stats.key = dimensions.iter().map(|d| d.name().to_string()).zip(values).collect();
+            stats
+        })
+        .collect();
+
+    Ok(Stats {
+        from: query.from,
+        to: query.to,
+        history_from,
+        history_to,
+        group_by: dimensions.iter().map(|d| d.name().to_string()).collect(),
+        total: total.finish(),
+        groups,
+        busiest_hours: hours,
+    })
+}
+
+pub fn query(history: &History, query: &StatsQuery) -> Result<Stats, String> {
+    compute(&history.entries(None), query)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> u64 {
+        Local.with_ymd_and_hms(year, month, day, hour, minute, 0).single().unwrap().timestamp_millis() as u64
+    }
+
+    fn entry(project: &str, status: &str, start: u64, minutes: u64) -> HistoryEntry {
+        let task = json!({
+            "id": project,
+            "name": project,
+            "is_focused": false,
+            "ide": "cursor",
+            "window_title": project,
+            "project_path": format!("/work/{}", project),
+            "active_file": null,
+            "status": status,
+            "source": "hook",
+            "start_time": start,
+            "end_time": start + minutes * 60_000,
+        });
+        HistoryEntry { task: serde_json::from_value(task).unwrap(), recorded_at: start, timeline: Vec::new() }
+    }
+
+    #[test]
+    fn percentile_uses_nearest_rank() {
+        let samples: Vec<u64> = (1..=10).map(|n| n * 10).collect();
+        assert_eq!(percentile(&samples, 50), Some(50));
+        assert_eq!(percentile(&samples, 90), Some(90));
+        assert_eq!(percentile(&samples, 100), Some(100));
+        assert_eq!(percentile(&[1, 2], 50), Some(1));
+        assert_eq!(percentile(&[1, 2], 90), Some(2));
+        assert_eq!(percentile(&[7], 0), Some(7));
+        assert_eq!(percentile(&[], 50), None);
+    }
+
+    #[test]
+    fn weeks_follow_iso_numbering() {
+        let week = |year, month, day| {
+            Dimension::Week.value(&entry("a", "completed", at(year, month, day, 12, 0), 1).task)
+        };
+        // ISO 周从周一开始，跨年的一周属于包含周四的那一年
+        assert_eq!(week(2024, 12, 29), "2024-W52");
+        assert_eq!(week(2024, 12, 30), "2025-W01");
+        assert_eq!(week(2025, 1, 5), "2025-W01");
+        assert_eq!(week(2025, 1, 6), "2025-W02");
+        assert_eq!(week(2021, 1, 3), "2020-W53");
+        assert_eq!(Dimension::Day.value(&entry("a", "completed", at(2025, 1, 6, 0, 5), 1).task), "2025-01-06");
+    }
+
+    #[test]
+    fn runs_split_across_local_hours() {
+        let mut hours: Vec<HourStats> = (0..24).map(|hour| HourStats { hour, ..Default::default() }).collect();
+        add_hours(&mut hours, at(2025, 1, 6, 10, 30), at(2025, 1, 6, 12, 15));
+        let active: Vec<(u32, u64, u32)> =
+            hours.iter().filter(|h| h.active_ms > 0).map(|h| (h.hour, h.active_ms / 60_000, h.runs_started)).collect();
+        assert_eq!(active, [(10, 30, 1), (11, 60, 0), (12, 15, 0)]);
+    }
+
+    #[test]
+    fn compute_groups_finished_runs_and_reports_history_range() {
+        let entries = [
+            entry("app", "completed", at(2025, 1, 6, 9, 0), 10),
+            entry("app", "completed", at(2025, 1, 7, 9, 0), 30),
+            entry("app", "error", at(2025, 1, 8, 9, 0), 5),
+            entry("web", "cancelled", at(2025, 1, 13, 9, 0), 20),
+            entry("web", "running", at(2025, 1, 14, 9, 0), 20),
+        ];
+        let query =
+            StatsQuery { group_by: Some("week, project".to_string()), from: Some(at(2025, 1, 7, 0, 0)), to: None };
+        let stats = compute(&entries, &query).unwrap();
+
+        assert_eq!((stats.history_from, stats.history_to), (Some(at(2025, 1, 6, 9, 0)), Some(at(2025, 1, 13, 9, 0))));
+        assert_eq!((stats.total.runs, stats.total.completed, stats.total.errors, stats.total.cancelled), (3, 1, 1, 1));
+        assert_eq!(stats.total.active_ms, 55 * 60_000);
+        assert_eq!(stats.total.median_ms, Some(30 * 60_000));
+        let keys: Vec<Vec<&str>> = stats.groups.iter().map(|g| g.key.values().map(String::as_str).collect()).collect();
+        assert_eq!(keys, [["/work/app", "2025-W02"], ["/work/web", "2025-W03"]]);
+        assert_eq!(stats.groups[0].runs, 2);
+
+        assert!(compute(&entries, &StatsQuery { group_by: Some("month".to_string()), ..Default::default() }).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::info;
+use vibe_core::{Task, TaskEngine};
+
+use crate::server::Server;
+use crate::settings::AppSettings;
+
+/// 后台扫描间隔
+const MONITOR_INTERVAL: Duration = Duration::from_secs(15);
+
+/// 告警阈值，每轮扫描时从设置中读取，修改后无需重启
+#[derive(Debug, Clone, Copy)]
+pub struct MonitorConfig {
+    pub enabled: bool,
+    /// 运行时间超出预估时长多少百分比后视为超时
+    pub overtime_percent: u32,
+    /// 多少分钟没有状态/阶段更新后视为可能卡住
+    pub stall_minutes: u64,
+}
+
+impl From<&AppSettings> for MonitorConfig {
+    fn from(settings: &AppSettings) -> Self {
+        MonitorConfig {
+            enabled: settings.task_alerts,
+            overtime_percent: settings.overtime_alert_percent,
+            stall_minutes: settings.stall_alert_minutes,
+        }
+    }
+}
+
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AlertKind {
+    Overtime,
+    PossiblyStalled,
+}
+
+/// 告警事件，通过 `task-alert` 事件推送到前端
+#[derive(Serialize, Clone, Debug)]
+pub struct TaskAlert {
+    pub kind: AlertKind,
+    pub task_id: String,
+    pub ide: String,
+    pub project_path: Option<String>,
+    pub current_stage: Option<String>,
+    pub elapsed_ms: u64,
+    /// 判断超时所用的预估：agent 给出的 estimated_duration，没有时为由历史学到的默认时长
+    pub estimated_duration: Option<u64>,
+    /// 距最近一次更新的时长（毫秒）
+    pub idle_ms: u64,
+}
+
+fn is_overtime(task: &Task, estimated: Option<u64>, now: u64, overtime_percent: u32) -> bool {
+    match estimated {
+        Some(estimated) if estimated > 0 && task.start_time > 0 => {
+            let elapsed = now.saturating_sub(task.start_time);
+            let limit = estimated.saturating_mul(100 + overtime_percent as u64) / 100;
+            elapsed > limit
+        }
+        _ => false,
+    }
+}
+
+fn is_stalled(task: &Task, now: u64, stall_minutes: u64) -> bool {
+    let last = task.last_update.max(task.start_time);
+    last > 0 && now.saturating_sub(last) > stall_minutes * 60 * 1000
+}
+
+/// 刷新所有任务的 overtime / possibly_stalled 标记，返回本轮新置位的告警
+///
+/// agent 没有给出 estimated_duration 的任务按 `learned` 返回的默认时长判断超时。
+pub fn scan(engine: &TaskEngine, config: &MonitorConfig, learned: impl Fn(&Task) -> Option<u64>) -> Vec<TaskAlert> {
+    let now = engine.now();
+    // 在引擎锁外计算，预估需要读取历史和时间线
+    let learned: HashMap<String, u64> = engine
+        .tasks()
+        .iter()
+        .filter(|t| config.enabled && t.status == "running" && t.estimated_duration.is_none_or(|d| d == 0))
+        .filter_map(|t| learned(t).map(|duration| (t.id.clone(), duration)))
+        .collect();
+    engine.with_tasks_mut(|tasks| {
+        let mut alerts = Vec::new();
+
+        for task in tasks.iter_mut() {
+            let running = config.enabled && task.status == "running";
+            let estimated = task.estimated_duration.filter(|d| *d > 0).or_else(|| learned.get(&task.id).copied());
+            let overtime = running && is_overtime(task, estimated, now, config.overtime_percent);
+            let stalled = running && is_stalled(task, now, config.stall_minutes);
+
+            let mut raised = Vec::new();
+            if overtime && !task.overtime {
+                raised.push(AlertKind::Overtime);
+            }
+            if stalled && !task.possibly_stalled {
+                raised.push(AlertKind::PossiblyStalled);
+            }
+            task.overtime = overtime;
+            task.possibly_stalled = stalled;
+
+            for kind in raised {
+                info!(task_id = %task.id, kind = ?kind, "Task alert raised");
+                alerts.push(TaskAlert {
+                    kind,
+                    task_id: task.id.clone(),
+                    ide: task.ide.clone(),
+                    project_path: task.project_path.clone(),
+                    current_stage: task.current_stage.clone(),
+                    elapsed_ms: now.saturating_sub(task.start_time),
+                    estimated_duration: estimated,
+                    idle_ms: now.saturating_sub(task.last_update.max(task.start_time)),
+                });
+            }
+        }
+
+        alerts
+    })
+}
+
+/// 启动后台监控线程；每轮通过 `config` 读取阈值，新告警交给 `on_alert`（应用中推送 `task-alert` 事件）。
+/// 服务实例释放后线程退出
+pub fn start_monitor<C, F>(server: &Arc<Server>, config: C, on_alert: F)
+where
+    C: Fn() -> MonitorConfig + Send + 'static,
+    F: Fn(TaskAlert) + Send + 'static,
+{
+    let server = Arc::downgrade(server);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(MONITOR_INTERVAL);
+        let Some(server) = server.upgrade() else { return };
+
+        let learned = |task: &Task| server.estimator.learned_duration(&server.history, &server.timelines, task);
+        for alert in scan(&server.engine, &config(), learned) {
+            on_alert(alert);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vibe_core::{ManualClock, NullSink, ReportRequest, UpdateStateRequest};
+
+    const CONFIG: MonitorConfig = MonitorConfig { enabled: true, overtime_percent: 50, stall_minutes: 60 };
+
+    fn running_task(estimated_duration: Option<u64>) -> (Arc<ManualClock>, TaskEngine) {
+        let clock = Arc::new(ManualClock::new(1_000));
+        let engine = TaskEngine::new(clock.clone(), Arc::new(NullSink));
+        let report = ReportRequest {
+            task_id: "a".to_string(),
+            name: "app".to_string(),
+            ide: "cursor".to_string(),
+            window_title: "app".to_string(),
+            is_focused: false,
+            project_path: None,
+            active_file: None,
+        };
+        engine.register(report, "hook").unwrap();
+        let request = UpdateStateRequest {
+            task_id: "a".to_string(),
+            status: Some("running".to_string()),
+            source: Some("hook".to_string()),
+            estimated_duration,
+            ..Default::default()
+        };
+        engine.update_state(request).unwrap();
+        (clock, engine)
+    }
+
+    #[test]
+    fn learned_duration_raises_overtime_without_agent_estimate() {
+        let (clock, engine) = running_task(None);
+        let learned = |_: &Task| Some(100_000);
+
+        clock.advance(150_000);
+        assert!(scan(&engine, &CONFIG, learned).is_empty());
+
+        clock.advance(1);
+        let alerts = scan(&engine, &CONFIG, learned);
+        assert_eq!(alerts.len(), 1);
+        assert_eq!((&alerts[0].kind, alerts[0].estimated_duration), (&AlertKind::Overtime, Some(100_000)));
+        assert!(engine.get("a").unwrap().overtime);
+
+        // 标记保持期间不重复告警
+        assert!(scan(&engine, &CONFIG, learned).is_empty());
+    }
+
+    #[test]
+    fn agent_estimate_takes_precedence_over_learned_duration() {
+        let (clock, engine) = running_task(Some(1_000_000));
+        clock.advance(200_000);
+        assert!(scan(&engine, &CONFIG, |_| Some(100_000)).is_empty());
+        assert!(!engine.get("a").unwrap().overtime);
+    }
+
+    #[test]
+    fn no_estimate_means_no_overtime() {
+        let (clock, engine) = running_task(None);
+        clock.advance(10 * 60 * 1000);
+        assert!(scan(&engine, &CONFIG, |_| None).is_empty());
+    }
+}
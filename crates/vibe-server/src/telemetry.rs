@@ -0,0 +1,194 @@
+use serde_json::{json, Value};
+use std::hash::{Hash, Hasher};
+use std::sync::Mutex;
+use std::time::Duration;
+use tracing::{debug, warn};
+use vibe_core::{Task, TimelineEvent, TimelineEventKind};
+
+const SERVICE_NAME: &str = "vibe-process-bar";
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 把已结束的运行导出到 OTLP collector
+#[derive(Default)]
+pub struct Telemetry {
+    /// 启用导出时为 OTLP/HTTP 地址，关闭时为 None
+    endpoint: Mutex<Option<String>>,
+}
+
+impl Telemetry {
+    /// 由设置同步（otlpEnabled / otlpEndpoint）
+    pub fn configure(&self, enabled: bool, endpoint: &str) {
+        let endpoint = endpoint.trim();
+        *self.endpoint.lock().unwrap() = (enabled && !endpoint.is_empty()).then(|| traces_url(endpoint));
+    }
+
+    /// 导出一次已结束的运行；未启用时直接返回。会阻塞等待 collector 响应，应在后台线程调用
+    pub fn export(&self, task: &Task, events: &[TimelineEvent]) {
+        let Some(url) = self.endpoint.lock().unwrap().clone() else { return };
+        if task.start_time == 0 {
+            return;
+        }
+        let body = build_trace(task, events).to_string();
+        let result = ureq::post(&url)
+            .timeout(EXPORT_TIMEOUT)
+            .set("Content-Type", "application/json")
+            .send_string(&body);
+        match result {
+            Ok(_) => debug!(task_id = %task.id, "Task trace exported to {}", url),
+            Err(e) => warn!(task_id = %task.id, error = %e, "Failed to export task trace to {}", url),
+        }
+    }
+}
+
+/// 允许填写 collector 根地址（http://127.0.0.1:4318）或完整的 /v1/traces 地址
+fn traces_url(endpoint: &str) -> String {
+    let endpoint = endpoint.trim_end_matches('/');
+    if endpoint.ends_with("/v1/traces") {
+        endpoint.to_string()
+    } else {
+        format!("{}/v1/traces", endpoint)
+    }
+}
+
+/// 由 task id、开始时间和序号派生 id，同一次运行重复导出时 id 不变
+fn derive_id(task: &Task, salt: u64, bytes: usize) -> String {
+    let mut id = String::new();
+    let mut round = 0u64;
+    while id.len() < bytes * 2 {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        (&task.id, task.start_time, salt, round).hash(&mut hasher);
+        id.push_str(&format!("{:016x}", hasher.finish()));
+        round += 1;
+    }
+    id.truncate(bytes * 2);
+    id
+}
+
+fn nanos(ms: u64) -> String {
+    (ms as u128 * 1_000_000).to_string()
+}
+
+fn attr(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+/// 任务的公共属性：ide、project、source
+fn task_attributes(task: &Task, source: &str) -> Vec<Value> {
+    let mut attributes = vec![attr("vibe.ide", &task.ide), attr("vibe.source", source)];
+    if let Some(project) = &task.project_path {
+        attributes.push(attr("vibe.project", project));
+    }
+    attributes
+}
+
+/// 把一次已结束的运行转换为 OTLP JSON：任务为根 span，阶段为子 span，状态切换等为根 span 上的事件
+pub fn build_trace(task: &Task, events: &[TimelineEvent]) -> Value {
+    let trace_id = derive_id(task, 0, 16);
+    let root_id = derive_id(task, u64::MAX, 8);
+    let end = task.end_time.unwrap_or(task.start_time).max(task.start_time);
+
+    let mut source = events
+        .iter()
+        .find_map(|e| match &e.kind {
+            TimelineEventKind::Registered { source } => Some(source.clone()),
+            TimelineEventKind::Source { from, .. } => Some(from.clone()),
+            _ => None,
+        })
+        .unwrap_or_else(|| task.source.clone());
+
+    let mut span_events = Vec::new();
+    let mut stage_spans = Vec::new();
+    let mut open_stage: Option<(String, u64)> = None;
+    let close_stage = |open: &mut Option<(String, u64)>, at: u64, spans: &mut Vec<Value>| {
+        if let Some((name, started)) = open.take() {
+            spans.push(json!({
+                "traceId": trace_id,
+                "spanId": derive_id(task, spans.len() as u64 + 1, 8),
+                "parentSpanId": root_id,
+                "name": name,
+                "kind": 1,
+                "startTimeUnixNano": nanos(started),
+                "endTimeUnixNano": nanos(at.max(started)),
+                "attributes": [attr("vibe.stage", &name)],
+            }));
+        }
+    };
+
+    for event in events {
+        let mut attributes = Vec::new();
+        let name = match &event.kind {
+            TimelineEventKind::Stage { stage } => {
+                close_stage(&mut open_stage, event.at, &mut stage_spans);
+                if let Some(stage) = stage.as_deref().filter(|s| *s != "__completed__") {
+                    open_stage = Some((stage.to_string(), event.at));
+                }
+                continue;
+            }
+            TimelineEventKind::Registered { .. } => continue,
+            TimelineEventKind::Source { from, to } => {
+                source = to.clone();
+                attributes.push(attr("vibe.source.from", from));
+                "source_changed"
+            }
+            TimelineEventKind::Status { from, to } => {
+                attributes.push(attr("vibe.status.from", from));
+                attributes.push(attr("vibe.status.to", to));
+                "status"
+            }
+            TimelineEventKind::Ignored { source: ignored_source, reason, .. } => {
+                attributes.push(attr("vibe.ignored.source", ignored_source));
+                attributes.push(attr("vibe.ignored.reason", reason));
+                "update_ignored"
+            }
+            TimelineEventKind::HeartbeatGap { gap_ms } => {
+                attributes.push(json!({ "key": "vibe.gap_ms", "value": { "intValue": gap_ms.to_string() } }));
+                "heartbeat_gap"
+            }
+            TimelineEventKind::Completion { .. } => "completion_report",
+        };
+        attributes.extend(task_attributes(task, &source));
+        span_events.push(json!({
+            "timeUnixNano": nanos(event.at),
+            "name": name,
+            "attributes": attributes,
+        }));
+    }
+    close_stage(&mut open_stage, end, &mut stage_spans);
+
+    // STATUS_CODE_OK = 1, STATUS_CODE_ERROR = 2；取消的运行不设置状态
+    let status = match task.status.as_str() {
+        "completed" => json!({ "code": 1 }),
+        "error" => json!({ "code": 2, "message": task.current_stage.clone().unwrap_or_default() }),
+        _ => json!({}),
+    };
+
+    let mut root_attributes = task_attributes(task, &task.source);
+    root_attributes.push(attr("vibe.task.id", &task.id));
+    root_attributes.push(attr("vibe.status", &task.status));
+    if let Some(branch) = task.git.as_ref().and_then(|g| g.branch.as_deref()) {
+        root_attributes.push(attr("vcs.ref.head.name", branch));
+    }
+
+    let mut spans = vec![json!({
+        "traceId": trace_id,
+        "spanId": root_id,
+        "name": if task.name.is_empty() { &task.id } else { &task.name },
+        "kind": 1,
+        "startTimeUnixNano": nanos(task.start_time),
+        "endTimeUnixNano": nanos(end),
+        "attributes": root_attributes,
+        "events": span_events,
+        "status": status,
+    })];
+    spans.extend(stage_spans);
+
+    json!({
+        "resourceSpans": [{
+            "resource": { "attributes": [attr("service.name", SERVICE_NAME)] },
+            "scopeSpans": [{
+                "scope": { "name": SERVICE_NAME, "version": env!("CARGO_PKG_VERSION") },
+                "spans": spans,
+            }],
+        }],
+    })
+}
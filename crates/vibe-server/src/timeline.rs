@@ -0,0 +1,56 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+pub use vibe_core::{TimelineEvent, TimelineEventKind};
+
+/// 每个任务最多保留的事件数，超出后丢弃最旧的
+const MAX_EVENTS_PER_TASK: usize = 500;
+
+/// 各任务的事件时间线（只保存在内存中）
+#[derive(Default)]
+pub struct Timelines {
+    events: Mutex<HashMap<String, Vec<TimelineEvent>>>,
+}
+
+impl Timelines {
+    /// 追加一条事件（只追加，不修改已有事件）
+    pub fn record(&self, task_id: &str, event: TimelineEvent) {
+        let mut timelines = self.events.lock().unwrap();
+        let events = timelines.entry(task_id.to_string()).or_default();
+        // 被忽略的更新往往会连续重复发送，只记录第一次
+        if matches!(event.kind, TimelineEventKind::Ignored { .. })
+            && events.last().is_some_and(|e| e.kind == event.kind)
+        {
+            return;
+        }
+        events.push(event);
+        let overflow = events.len().saturating_sub(MAX_EVENTS_PER_TASK);
+        events.drain(..overflow);
+    }
+
+    pub fn get(&self, task_id: &str) -> Option<Vec<TimelineEvent>> {
+        self.events.lock().unwrap().get(task_id).cloned()
+    }
+
+    /// 某次运行（since 之后）的事件，写入历史时使用
+    pub fn since(&self, task_id: &str, since: u64) -> Vec<TimelineEvent> {
+        self.events
+            .lock()
+            .unwrap()
+            .get(task_id)
+            .map(|events| events.iter().filter(|e| e.at >= since).cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// 任务被移除后丢弃其时间线，已结束的运行仍保存在历史中
+    pub fn remove(&self, task_ids: &[String]) {
+        let mut timelines = self.events.lock().unwrap();
+        for id in task_ids {
+            timelines.remove(id);
+        }
+    }
+
+    pub fn clear(&self) {
+        self.events.lock().unwrap().clear();
+    }
+}
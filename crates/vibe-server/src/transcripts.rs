@@ -0,0 +1,624 @@
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, UNIX_EPOCH};
+use tracing::{debug, info};
+use vibe_core::{can_update_source, TaskEngine, UpdateStateRequest, UpdateTokensRequest};
+
+use crate::agent_sessions::{self, AgentSessions};
+use crate::claude_hooks;
+
+// ============================================================================
+// Transcript：跟随 agent 写在磁盘上的 JSONL 会话记录，推断状态、阶段和 token 用量
+// ============================================================================
+//
+// 支持 Claude Code（`~/.claude/projects/<slug>/<session>.jsonl`）和 Codex CLI
+// （`~/.codex/sessions/**/rollout-*.jsonl`），按每行的结构识别格式。会话经 agent_sessions 映射到任务，
+// 与 hook / ingest 使用相同的 (ide, session) 键。来源为 transcript，仅次于 hook。
+
+const SOURCE: &str = "transcript";
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// 每隔多少次轮询重新扫描目录中的新文件
+const DISCOVER_EVERY_POLLS: u32 = 10;
+const MAX_DEPTH: usize = 4;
+/// 只跟随最近修改过的文件；更早的文件在再次被写入前不打开
+const RECENT_FILE_MS: u64 = 10 * 60 * 1000;
+/// 单次读取的上限，首次读取超过时只读末尾
+const MAX_READ_BYTES: u64 = 8 * 1024 * 1024;
+/// 没有 stop_reason 的纯文本回复之后安静这么久，视为本轮结束
+const TURN_END_QUIET_MS: u64 = 5_000;
+/// 去重 usage 时记住的最近消息数（同一条消息的每个内容块各占一行，usage 相同）
+const SEEN_MESSAGES: usize = 64;
+
+pub const DEFAULT_DIRS: [&str; 2] = ["~/.claude/projects", "~/.codex/sessions"];
+
+#[derive(Default)]
+struct WatcherConfig {
+    enabled: bool,
+    dirs: Vec<PathBuf>,
+}
+
+/// 轮询线程的配置，线程在首次启用时启动，引擎释放后退出
+#[derive(Default)]
+pub struct Transcripts {
+    config: Arc<Mutex<WatcherConfig>>,
+    started: AtomicBool,
+}
+
+impl Transcripts {
+    /// 由设置同步（transcriptWatcher / transcriptDirs），首次启用时启动轮询线程
+    pub fn configure(&self, engine: &Arc<TaskEngine>, sessions: &Arc<AgentSessions>, enabled: bool, dirs: &[String]) {
+        {
+            let mut config = self.config.lock().unwrap();
+            config.enabled = enabled;
+            config.dirs = dirs.iter().filter_map(|d| expand_home(d.trim())).collect();
+        }
+        if enabled && !self.started.swap(true, Ordering::SeqCst) {
+            info!("Transcript watcher started");
+            let (engine, sessions, config) = (Arc::downgrade(engine), Arc::downgrade(sessions), self.config.clone());
+            std::thread::spawn(move || run(engine, sessions, config));
+        }
+    }
+}
+
+fn expand_home(dir: &str) -> Option<PathBuf> {
+    match dir.strip_prefix("~") {
+        Some(rest) => Some(dirs::home_dir()?.join(rest.trim_start_matches(['/', '\\']))),
+        None if dir.is_empty() => None,
+        None => Some(PathBuf::from(dir)),
+    }
+}
+
+// ============================================================================
+// 解析
+// ============================================================================
+
+/// 一行记录中和状态有关的内容
+#[derive(Debug, PartialEq)]
+enum Entry {
+    Session(String),
+    Cwd(String),
+    /// 用户发起新一轮
+    Prompt,
+    ToolUse(String),
+    /// agent 请求用户批准命令或改动
+    ApprovalRequest,
+    ToolResult,
+    /// 回复的文本（是否结束取决于后续内容）
+    Text,
+    TurnEnd,
+    Aborted,
+    /// 其他说明仍在工作的内容（思考、子 agent 记录等）
+    Activity,
+    /// 单条消息的 token 数，按 message id 去重
+    MessageTokens(Option<String>, u64),
+    /// 会话累计 token 数
+    TotalTokens(u64),
+}
+
+fn str_field<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(Value::as_str).filter(|s| !s.is_empty())
+}
+
+fn parse_line(value: &Value) -> (Option<&'static str>, Vec<Entry>) {
+    if value.get("payload").is_some() {
+        (Some("codex"), parse_codex(value))
+    } else if value.get("sessionId").is_some() || value.get("message").is_some() {
+        (Some("claude-code"), parse_claude(value))
+    } else {
+        (None, Vec::new())
+    }
+}
+
+fn parse_claude(value: &Value) -> Vec<Entry> {
+    let mut entries = Vec::new();
+    if let Some(id) = str_field(value, "sessionId") {
+        entries.push(Entry::Session(id.to_string()));
+    }
+    if let Some(cwd) = str_field(value, "cwd") {
+        entries.push(Entry::Cwd(cwd.to_string()));
+    }
+    let message = value.get("message");
+    let content = message.and_then(|m| m.get("content"));
+    let blocks = content.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+    let has_block = |kind: &str| blocks.iter().any(|b| str_field(b, "type") == Some(kind));
+
+    if value.get("isSidechain").and_then(Value::as_bool) == Some(true) {
+        entries.push(Entry::Activity);
+        return entries;
+    }
+    match str_field(value, "type") {
+        Some("user") => {
+            if value.get("isMeta").and_then(Value::as_bool) == Some(true) {
+                return entries;
+            }
+            if has_block("tool_result") {
+                entries.push(Entry::ToolResult);
+            } else {
+                // 本地斜杠命令的输入输出也记为 user，但不会有回复
+                let text = content.and_then(Value::as_str).unwrap_or_default();
+                if !text.starts_with("<command-name>") && !text.starts_with("<local-command") {
+                    entries.push(Entry::Prompt);
+                }
+            }
+        }
+        Some("assistant") => {
+            let message = message.unwrap_or(&Value::Null);
+            if let Some(usage) = message.get("usage") {
+                let tokens = ["input_tokens", "output_tokens", "cache_creation_input_tokens", "cache_read_input_tokens"]
+                    .iter()
+                    .filter_map(|k| usage.get(k).and_then(Value::as_u64))
+                    .sum();
+                entries.push(Entry::MessageTokens(str_field(message, "id").map(str::to_string), tokens));
+            }
+            for block in blocks.iter().filter(|b| str_field(b, "type") == Some("tool_use")) {
+                let name = str_field(block, "name").unwrap_or("tool");
+                entries.push(Entry::ToolUse(claude_hooks::tool_stage(name, block.get("input"))));
+            }
+            match str_field(message, "stop_reason") {
+                Some("end_turn") | Some("stop_sequence") if !has_block("tool_use") => entries.push(Entry::TurnEnd),
+                // 后面还有工具调用
+                Some("tool_use") if !has_block("tool_use") => entries.push(Entry::Activity),
+                None if has_block("text") && !has_block("tool_use") => entries.push(Entry::Text),
+                _ if !has_block("tool_use") => entries.push(Entry::Activity),
+                _ => {}
+            }
+        }
+        _ => {}
+    }
+    entries
+}
+
+fn parse_codex(value: &Value) -> Vec<Entry> {
+    let payload = &value["payload"];
+    let mut entries = Vec::new();
+    match (str_field(value, "type"), str_field(payload, "type")) {
+        (Some("session_meta"), _) => {
+            if let Some(id) = str_field(payload, "id") {
+                entries.push(Entry::Session(id.to_string()));
+            }
+            if let Some(cwd) = str_field(payload, "cwd") {
+                entries.push(Entry::Cwd(cwd.to_string()));
+            }
+        }
+        (Some("turn_context"), _) => {
+            if let Some(cwd) = str_field(payload, "cwd") {
+                entries.push(Entry::Cwd(cwd.to_string()));
+            }
+        }
+        (Some("event_msg"), Some("user_message" | "task_started")) => entries.push(Entry::Prompt),
+        (Some("event_msg"), Some("task_complete")) => entries.push(Entry::TurnEnd),
+        (Some("event_msg"), Some("turn_aborted")) => entries.push(Entry::Aborted),
+        (Some("event_msg"), Some("exec_approval_request" | "apply_patch_approval_request")) => {
+            entries.push(Entry::ApprovalRequest)
+        }
+        (Some("event_msg"), Some("token_count")) => {
+            let total = payload.pointer("/info/total_token_usage/total_tokens").and_then(Value::as_u64);
+            entries.extend(total.map(Entry::TotalTokens));
+        }
+        (Some("response_item"), Some("function_call" | "custom_tool_call" | "local_shell_call")) => {
+            entries.push(Entry::ToolUse(str_field(payload, "name").unwrap_or("shell").to_string()));
+        }
+        (Some("response_item"), Some(kind)) if kind.ends_with("_output") => entries.push(Entry::ToolResult),
+        (Some("response_item" | "event_msg"), _) => entries.push(Entry::Activity),
+        _ => {}
+    }
+    entries
+}
+
+/// 记录时间（毫秒），没有或无法解析时为 None
+fn entry_time(value: &Value) -> Option<u64> {
+    let timestamp = str_field(value, "timestamp")?;
+    chrono::DateTime::parse_from_rfc3339(timestamp).ok().map(|t| t.timestamp_millis().max(0) as u64)
+}
+
+// ============================================================================
+// 会话状态
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    Running,
+    AwaitingApproval,
+    Replied,
+    Done,
+    Aborted,
+}
+
+#[derive(Default)]
+struct Transcript {
+    offset: u64,
+    partial: Vec<u8>,
+    ide: Option<&'static str>,
+    session: Option<String>,
+    cwd: Option<String>,
+    phase: Option<Phase>,
+    /// 最近一条记录的时间，用于本轮结束的判断
+    last_entry: u64,
+    stage: Option<String>,
+    total_tokens: u64,
+    turn_start_tokens: u64,
+    seen_messages: VecDeque<String>,
+    /// 最近一次写入任务的状态、阶段和 token 数
+    applied: Option<(String, Option<String>, u64)>,
+    last_change: u64,
+}
+
+impl Transcript {
+    fn apply(&mut self, entry: Entry, time: u64) {
+        self.last_entry = self.last_entry.max(time);
+        let phase = match entry {
+            Entry::Session(id) => {
+                self.session.get_or_insert(id);
+                return;
+            }
+            Entry::Cwd(cwd) => {
+                self.cwd = Some(cwd);
+                return;
+            }
+            Entry::MessageTokens(id, tokens) => {
+                if let Some(id) = id {
+                    if self.seen_messages.contains(&id) {
+                        return;
+                    }
+                    self.seen_messages.push_back(id);
+                    if self.seen_messages.len() > SEEN_MESSAGES {
+                        self.seen_messages.pop_front();
+                    }
+                }
+                self.total_tokens += tokens;
+                return;
+            }
+            Entry::TotalTokens(total) => {
+                self.total_tokens = total;
+                return;
+            }
+            Entry::Prompt => {
+                if !matches!(self.phase, Some(Phase::Running | Phase::AwaitingApproval)) {
+                    self.turn_start_tokens = self.total_tokens;
+                    self.stage = None;
+                }
+                Phase::Running
+            }
+            Entry::ToolUse(stage) => {
+                self.stage = Some(stage);
+                Phase::Running
+            }
+            Entry::ApprovalRequest => Phase::AwaitingApproval,
+            Entry::ToolResult => Phase::Running,
+            Entry::Text => Phase::Replied,
+            Entry::TurnEnd => Phase::Done,
+            Entry::Aborted => Phase::Aborted,
+            // 批准之后的记录（命令开始执行等）说明已经不在等待
+            Entry::Activity => match self.phase {
+                Some(Phase::Done | Phase::Aborted) | None => return,
+                _ => Phase::Running,
+            },
+        };
+        self.phase = Some(phase);
+    }
+
+    fn status(&self, now: u64) -> Option<&'static str> {
+        let quiet = now.saturating_sub(self.last_entry);
+        match self.phase? {
+            Phase::Running => Some("running"),
+            // 工具执行时间不定，安静不代表在等待确认，只认明确的批准请求
+            Phase::AwaitingApproval => Some("waiting_input"),
+            Phase::Replied if quiet >= TURN_END_QUIET_MS => Some("completed"),
+            Phase::Replied => Some("running"),
+            Phase::Done => Some("completed"),
+            Phase::Aborted => Some("cancelled"),
+        }
+    }
+
+    /// 读取新追加的内容；文件被截断时从头读取
+    fn read(&mut self, path: &Path, now: u64) -> bool {
+        let Ok(mut file) = File::open(path) else { return false };
+        let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+        if len < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        if len == self.offset {
+            return false;
+        }
+        let start = self.offset.max(len.saturating_sub(MAX_READ_BYTES));
+        let skipped = start > self.offset;
+        let mut buf = Vec::new();
+        if file.seek(SeekFrom::Start(start)).is_err() || file.read_to_end(&mut buf).is_err() {
+            return false;
+        }
+        self.offset = start + buf.len() as u64;
+        if skipped {
+            // 跳过了中间的内容，丢弃第一行不完整的部分
+            self.partial.clear();
+            let first_line = buf.iter().position(|b| *b == b'\n').map(|i| i + 1).unwrap_or(buf.len());
+            buf.drain(..first_line);
+        }
+        self.partial.extend_from_slice(&buf);
+        let Some(end) = self.partial.iter().rposition(|b| *b == b'\n') else { return false };
+        let lines: Vec<u8> = self.partial.drain(..=end).collect();
+        self.apply_lines(&lines, now);
+        self.last_change = now;
+        true
+    }
+
+    /// 解析完整的若干行记录
+    fn apply_lines(&mut self, lines: &[u8], now: u64) {
+        for line in lines.split(|b| *b == b'\n').filter(|l| !l.is_empty()) {
+            let Ok(value) = serde_json::from_slice::<Value>(line) else { continue };
+            let (ide, entries) = parse_line(&value);
+            if ide.is_none() {
+                continue;
+            }
+            self.ide = self.ide.or(ide);
+            let time = entry_time(&value).map(|t| t.min(now)).unwrap_or(now);
+            for entry in entries {
+                self.apply(entry, time);
+            }
+        }
+    }
+
+    /// 要写入任务的状态、阶段和本轮 token 数
+    fn state(&self, status: &str) -> (String, Option<String>, u64) {
+        let stage = match status {
+            "running" | "waiting_input" => self.stage.clone(),
+            _ => None,
+        };
+        (status.to_string(), stage, self.total_tokens.saturating_sub(self.turn_start_tokens))
+    }
+
+    /// 首次发现时已经结束的会话视为已同步，不再为它创建任务
+    fn skip_finished(&mut self, now: u64) {
+        if let Some(status) = self.status(now).filter(|s| *s != "running" && *s != "waiting_input") {
+            self.applied = Some(self.state(status));
+        }
+    }
+}
+
+// ============================================================================
+// 轮询
+// ============================================================================
+
+fn modified_ms(path: &Path) -> u64 {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// 目录下的 .jsonl 文件；Claude Code 子 agent 的记录（subagents/）与主会话共用 session id，跳过
+fn discover(dirs: &[PathBuf]) -> HashSet<PathBuf> {
+    let mut files = HashSet::new();
+    let mut stack: Vec<(PathBuf, usize)> = dirs.iter().map(|d| (d.clone(), 0)).collect();
+    while let Some((dir, depth)) = stack.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else { continue };
+        for entry in entries.filter_map(|e| e.ok()) {
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else { continue };
+            if file_type.is_dir() {
+                if depth < MAX_DEPTH && entry.file_name() != "subagents" {
+                    stack.push((path, depth + 1));
+                }
+            } else if path.extension().is_some_and(|e| e == "jsonl") {
+                files.insert(path);
+            }
+        }
+    }
+    files
+}
+
+fn run(engine: Weak<TaskEngine>, sessions: Weak<AgentSessions>, config: Arc<Mutex<WatcherConfig>>) {
+    let mut transcripts: HashMap<PathBuf, Transcript> = HashMap::new();
+    let mut polls = 0u32;
+    loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let (Some(engine), Some(sessions)) = (engine.upgrade(), sessions.upgrade()) else { return };
+        let (enabled, dirs) = {
+            let config = config.lock().unwrap();
+            (config.enabled, config.dirs.clone())
+        };
+        if !enabled {
+            transcripts.clear();
+            polls = 0;
+            continue;
+        }
+        let now = engine.now();
+
+        if polls.is_multiple_of(DISCOVER_EVERY_POLLS) {
+            let files = discover(&dirs);
+            transcripts.retain(|path, t| {
+                files.contains(path) && now.saturating_sub(t.last_change) < agent_sessions::SESSION_TTL_MS
+            });
+            for path in files {
+                // 旧文件（包括因长时间没有变化被移除的）先看修改时间，再次被写入后才开始跟随
+                if transcripts.contains_key(&path) || now.saturating_sub(modified_ms(&path)) > RECENT_FILE_MS {
+                    continue;
+                }
+                // 读取已有内容以恢复当前状态
+                let mut transcript = Transcript { last_change: now, ..Default::default() };
+                transcript.read(&path, now);
+                transcript.skip_finished(now);
+                debug!(path = ?path, "Following transcript");
+                transcripts.insert(path, transcript);
+            }
+        }
+        polls = polls.wrapping_add(1);
+
+        for (path, transcript) in transcripts.iter_mut() {
+            transcript.read(path, now);
+            sync_task(&engine, &sessions, path, transcript, now);
+        }
+    }
+}
+
+/// 把推断的状态写入会话对应的任务，只在变化时更新
+fn sync_task(engine: &TaskEngine, sessions: &AgentSessions, path: &Path, t: &mut Transcript, now: u64) {
+    let (Some(ide), Some(status)) = (t.ide, t.status(now)) else { return };
+    let state = t.state(status);
+    if t.applied.as_ref() == Some(&state) {
+        return;
+    }
+    let status_changed = t.applied.as_ref().is_none_or(|(s, st, _)| *s != state.0 || *st != state.1);
+    let (stage, tokens) = (state.1.clone(), state.2);
+    t.applied = Some(state);
+
+    let key = t.session.clone().or_else(|| path.file_stem().map(|s| s.to_string_lossy().to_string()));
+    let Some(key) = key else { return };
+    let task_id = match sessions.resolve(engine, ide, &key, t.cwd.as_deref(), SOURCE, now) {
+        Ok(task_id) => task_id,
+        Err(e) => {
+            debug!(path = ?path, error = %e, "Failed to map transcript session");
+            return;
+        }
+    };
+    // hook 接管的会话不再更新，避免每次都产生一条被忽略的记录
+    if engine.get(&task_id).is_none_or(|task| !can_update_source(&task.source, SOURCE)) {
+        return;
+    }
+    if status_changed {
+        let _ = engine.update_state(UpdateStateRequest {
+            task_id: task_id.clone(),
+            status: Some(status.to_string()),
+            source: Some(SOURCE.to_string()),
+            estimated_duration: None,
+            current_stage: stage,
+        });
+    }
+    if tokens > 0 {
+        let _ = engine.update_tokens(UpdateTokensRequest { task_id, tokens, increment: false });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vibe_core::{ManualClock, NullSink};
+
+    const NOW: u64 = 1_800_000_000_000;
+
+    fn engine() -> TaskEngine {
+        TaskEngine::new(Arc::new(ManualClock::new(NOW)), Arc::new(NullSink))
+    }
+
+    /// 按首次发现的方式读取一段记录
+    fn discovered(lines: &[&str]) -> Transcript {
+        let mut transcript = Transcript { last_change: NOW, ..Default::default() };
+        transcript.apply_lines(format!("{}\n", lines.join("\n")).as_bytes(), NOW);
+        transcript.skip_finished(NOW);
+        transcript
+    }
+
+    fn transcript(lines: &[&str]) -> Transcript {
+        let mut transcript = Transcript::default();
+        transcript.apply_lines(format!("{}\n", lines.join("\n")).as_bytes(), NOW);
+        transcript
+    }
+
+    const CLAUDE_PROMPT: &str = r#"{"type":"user","sessionId":"s1","message":{"content":"fix it"}}"#;
+    const CLAUDE_TOOL: &str = r#"{"type":"assistant","sessionId":"s1","message":{"id":"m1","content":[{"type":"tool_use","name":"Bash","input":{"command":"cargo test"}}],"stop_reason":"tool_use"}}"#;
+    const CLAUDE_TOOL_RESULT: &str =
+        r#"{"type":"user","sessionId":"s1","message":{"content":[{"type":"tool_result","content":"ok"}]}}"#;
+    const CLAUDE_TEXT: &str =
+        r#"{"type":"assistant","sessionId":"s1","message":{"id":"m2","content":[{"type":"text","text":"Done."}]}}"#;
+    const CLAUDE_END_TURN: &str = r#"{"type":"assistant","sessionId":"s1","message":{"id":"m3","content":[{"type":"text","text":"Done."}],"stop_reason":"end_turn"}}"#;
+    const CLAUDE_SIDECHAIN: &str =
+        r#"{"type":"user","sessionId":"s1","isSidechain":true,"message":{"content":"explore the repo"}}"#;
+    const CODEX_META: &str = r#"{"type":"session_meta","payload":{"id":"c1","cwd":"/tmp/app"}}"#;
+    const CODEX_PROMPT: &str = r#"{"type":"event_msg","payload":{"type":"user_message","message":"fix it"}}"#;
+    const CODEX_CALL: &str = r#"{"type":"response_item","payload":{"type":"function_call","name":"shell"}}"#;
+    const CODEX_APPROVAL: &str = r#"{"type":"event_msg","payload":{"type":"exec_approval_request"}}"#;
+    const CODEX_EXEC_BEGIN: &str = r#"{"type":"event_msg","payload":{"type":"exec_command_begin"}}"#;
+    const CODEX_COMPLETE: &str = r#"{"type":"event_msg","payload":{"type":"task_complete"}}"#;
+    const CODEX_ABORTED: &str = r#"{"type":"event_msg","payload":{"type":"turn_aborted"}}"#;
+
+    #[test]
+    fn status_follows_transcript_entries() {
+        // (记录, 最后一条记录之后安静的时间, 期望状态)
+        let cases: [(&[&str], u64, Option<&str>); 13] = [
+            (&[], 0, None),
+            (&[CLAUDE_PROMPT], 60_000, Some("running")),
+            // 工具执行再久也不算等待确认
+            (&[CLAUDE_PROMPT, CLAUDE_TOOL], 600_000, Some("running")),
+            (&[CLAUDE_PROMPT, CLAUDE_TOOL, CLAUDE_TOOL_RESULT], 0, Some("running")),
+            (&[CLAUDE_PROMPT, CLAUDE_TEXT], TURN_END_QUIET_MS - 1, Some("running")),
+            (&[CLAUDE_PROMPT, CLAUDE_TEXT], TURN_END_QUIET_MS, Some("completed")),
+            (&[CLAUDE_PROMPT, CLAUDE_END_TURN], 0, Some("completed")),
+            // 子 agent 的记录不会开始新的一轮
+            (&[CLAUDE_PROMPT, CLAUDE_END_TURN, CLAUDE_SIDECHAIN], 0, Some("completed")),
+            (&[CODEX_META, CODEX_PROMPT, CODEX_CALL], 600_000, Some("running")),
+            (&[CODEX_META, CODEX_PROMPT, CODEX_CALL, CODEX_APPROVAL], 0, Some("waiting_input")),
+            (&[CODEX_META, CODEX_PROMPT, CODEX_CALL, CODEX_APPROVAL, CODEX_EXEC_BEGIN], 0, Some("running")),
+            (&[CODEX_META, CODEX_PROMPT, CODEX_COMPLETE], 0, Some("completed")),
+            (&[CODEX_META, CODEX_PROMPT, CODEX_ABORTED], 0, Some("cancelled")),
+        ];
+        for (lines, quiet, expected) in cases {
+            assert_eq!(transcript(lines).status(NOW + quiet), expected, "{:?} after {} ms", lines, quiet);
+        }
+    }
+
+    #[test]
+    fn session_and_format_come_from_the_records() {
+        let claude = transcript(&[CLAUDE_PROMPT]);
+        assert_eq!((claude.ide, claude.session.as_deref()), (Some("claude-code"), Some("s1")));
+
+        let codex = transcript(&[CODEX_META, CODEX_PROMPT]);
+        assert_eq!(
+            (codex.ide, codex.session.as_deref(), codex.cwd.as_deref()),
+            (Some("codex"), Some("c1"), Some("/tmp/app"))
+        );
+    }
+
+    #[test]
+    fn tool_call_becomes_the_stage() {
+        let transcript = transcript(&[CLAUDE_PROMPT, CLAUDE_TOOL, CLAUDE_TOOL_RESULT]);
+        assert_eq!(transcript.state("running").1.as_deref(), Some("Bash"));
+        // 结束后不再显示阶段
+        assert_eq!(transcript.state("completed").1, None);
+    }
+
+    #[test]
+    fn usage_is_counted_once_per_message() {
+        // 同一条消息的每个内容块各占一行，usage 相同
+        let block = |kind: &str| {
+            format!(
+                r#"{{"type":"assistant","sessionId":"s1","message":{{"id":"m1","content":[{{"type":"{}"}}],"usage":{{"input_tokens":100,"output_tokens":20,"cache_read_input_tokens":30}}}}}}"#,
+                kind
+            )
+        };
+        let (thinking, text) = (block("thinking"), block("text"));
+        assert_eq!(transcript(&[CLAUDE_PROMPT, &thinking, &text]).total_tokens, 150);
+
+        let codex = r#"{"type":"event_msg","payload":{"type":"token_count","info":{"total_token_usage":{"total_tokens":4200}}}}"#;
+        assert_eq!(transcript(&[CODEX_META, codex, codex]).total_tokens, 4200);
+    }
+
+    #[test]
+    fn finished_session_found_on_startup_creates_no_task() {
+        let (engine, sessions) = (engine(), AgentSessions::default());
+        let mut transcript = discovered(&[
+            r#"{"type":"user","sessionId":"s1","cwd":"/tmp/app","message":{"content":"hi"}}"#,
+            r#"{"type":"assistant","sessionId":"s1","message":{"id":"m1","content":[{"type":"text","text":"done"}],"stop_reason":"end_turn","usage":{"input_tokens":10,"output_tokens":5}}}"#,
+        ]);
+
+        sync_task(&engine, &sessions, Path::new("/tmp/s1.jsonl"), &mut transcript, NOW);
+        assert!(engine.tasks().is_empty());
+    }
+
+    #[test]
+    fn running_session_found_on_startup_creates_a_task() {
+        let (engine, sessions) = (engine(), AgentSessions::default());
+        let mut transcript =
+            discovered(&[r#"{"type":"user","sessionId":"s1","cwd":"/tmp/app","message":{"content":"hi"}}"#]);
+
+        sync_task(&engine, &sessions, Path::new("/tmp/s1.jsonl"), &mut transcript, NOW);
+        let task = engine.get("claude-code-s1").unwrap();
+        assert_eq!((task.status.as_str(), task.source.as_str()), ("running", SOURCE));
+    }
+}
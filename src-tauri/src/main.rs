@@ -1,16 +1,23 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 use serde_json::json;
+use std::sync::Arc;
 use tauri::menu::MenuItem;
 use tauri::tray::TrayIconBuilder;
 use tauri::{Emitter, Manager, Runtime, WindowEvent};
 use tracing::info;
+use vibe_core::Task;
 
-mod http_server;
-mod settings;
 mod window_manager;
 
-use settings::{AppSettings, SettingsState};
+use settings::{
+    AppSettings, ExternalChange, SettingsChange, SettingsSnapshot, SettingsState, SettingsWarning,
+};
+use settings_profiles::{ImportPreview, ProfileList};
+use vibe_server::server::Server;
+use vibe_server::services::{self, AppDirs};
+use vibe_server::task_monitor::{self, MonitorConfig};
+use vibe_server::{history, http_server, logging, settings, settings_profiles, stats, timeline};
 use window_manager::IdeWindow;
 
 // ============================================================================
@@ -41,9 +48,23 @@ async fn activate_ide_window<R: Runtime>(
     )
 }
 
+/// HTTP server 的 /api/task/activate 路由通过它激活 IDE 窗口，参数与 activate_ide_window 命令一致
+struct AppWindowBridge;
+
+impl http_server::WindowBridge for AppWindowBridge {
+    fn activate(&self, task: &Task) -> Result<(), String> {
+        window_manager::activate_ide(
+            &task.ide,
+            Some(task.window_title.as_str()).filter(|t| !t.is_empty()),
+            task.project_path.as_deref(),
+            task.active_file.as_deref(),
+        )
+    }
+}
+
 #[tauri::command]
-async fn reset_task_to_armed(task_id: String) -> Result<(), String> {
-    http_server::reset_task_to_armed(&task_id)
+async fn reset_task_to_armed(server: tauri::State<'_, Arc<Server>>, task_id: String) -> Result<(), String> {
+    server.engine.reset_to_armed(&task_id).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -88,8 +109,82 @@ fn open_settings_window<R: Runtime>(app: tauri::AppHandle<R>) -> Result<(), Stri
 // ============================================================================
 
 #[tauri::command]
-async fn get_tasks() -> Result<Vec<http_server::Task>, String> {
-    Ok(http_server::get_merged_tasks())
+async fn get_tasks(server: tauri::State<'_, Arc<Server>>) -> Result<Vec<Task>, String> {
+    Ok(server.merged_tasks())
+}
+
+/// 任务详情：优先返回当前任务，已被清理时返回最近一次的历史记录
+#[tauri::command]
+async fn get_task_detail(server: tauri::State<'_, Arc<Server>>, task_id: String) -> Result<Option<Task>, String> {
+    let live = server.merged_tasks().into_iter().find(|t| t.id == task_id);
+    Ok(live.or_else(|| server.history.latest(&task_id).map(|e| e.task)))
+}
+
+/// 打开任务详情窗口；窗口已存在时切换到指定任务
+#[tauri::command]
+fn open_task_detail_window<R: Runtime>(app: tauri::AppHandle<R>, task_id: String) -> Result<(), String> {
+    if let Some(window) = app.get_webview_window("task-detail") {
+        window.emit("task-detail-selected", &task_id).map_err(|e| e.to_string())?;
+        let _ = window.show();
+        let _ = window.set_focus();
+        return Ok(());
+    }
+
+    let url = format!("index.html?type=task&id={}", encode_query_value(&task_id));
+    tauri::WebviewWindowBuilder::new(&app, "task-detail", tauri::WebviewUrl::App(url.into()))
+        .title("Task Details")
+        .inner_size(520.0, 560.0)
+        .decorations(true)
+        .transparent(false)
+        .visible(true)
+        .focused(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn encode_query_value(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[tauri::command]
+async fn get_task_timeline(
+    server: tauri::State<'_, Arc<Server>>,
+    task_id: String,
+) -> Result<Vec<timeline::TimelineEvent>, String> {
+    server.task_timeline(&task_id).ok_or_else(|| format!("Task not found: {}", task_id))
+}
+
+/// 已结束任务的历史记录（最近的在前）
+#[tauri::command]
+async fn get_task_history(
+    server: tauri::State<'_, Arc<Server>>,
+    limit: Option<usize>,
+) -> Result<Vec<history::HistoryEntry>, String> {
+    Ok(server.history.entries(limit))
+}
+
+/// 最近的日志，参数同 GET /api/logs
+#[tauri::command]
+async fn get_logs(since: Option<u64>, level: Option<String>, limit: Option<usize>) -> Result<logging::LogsResponse, String> {
+    logging::query(&logging::LogQuery { since, level, limit })
+}
+
+/// 历史统计，参数同 GET /api/stats
+#[tauri::command]
+async fn get_task_stats(
+    server: tauri::State<'_, Arc<Server>>,
+    group_by: Option<String>,
+    from: Option<u64>,
+    to: Option<u64>,
+) -> Result<stats::Stats, String> {
+    stats::query(&server.history, &stats::StatsQuery { group_by, from, to })
 }
 
 // ============================================================================
@@ -103,23 +198,196 @@ async fn get_app_settings(
     Ok(state.get_settings())
 }
 
+/// 取走启动时的设置加载告警（被重置的字段），只返回一次
 #[tauri::command]
-async fn update_app_settings<R: Runtime>(
-    app: tauri::AppHandle<R>,
+async fn take_settings_warning(
     state: tauri::State<'_, SettingsState>,
-    new_settings: AppSettings,
-) -> Result<(), String> {
-    // 更新 HTTP server 的屏蔽设置
-    http_server::set_block_plugin_status(new_settings.block_plugin_status);
+) -> Result<Option<SettingsWarning>, String> {
+    Ok(state.take_load_warning())
+}
 
-    // 保存设置
-    state.update_settings(new_settings.clone())?;
+#[tauri::command]
+async fn get_settings_snapshot(
+    state: tauri::State<'_, SettingsState>,
+) -> Result<SettingsSnapshot, String> {
+    Ok(state.snapshot())
+}
+
+/// 应用设置 patch：在锁内合并并保存，同步后台服务（屏蔽设置、地址变化时重新绑定等），
+/// 并广播只包含变化键的 settings-changed 事件
+///
+/// `persist` 为 false 时不写文件，用于应用外部修改过的 settings.json。
+fn apply_settings_patch<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    patch: &serde_json::Value,
+    base_revision: Option<u64>,
+    persist: bool,
+) -> Result<SettingsChange, String> {
+    let state = app.state::<SettingsState>();
+    let change = state.patch(patch, base_revision, persist)?;
+    if change.changed.is_empty() {
+        return Ok(change);
+    }
+
+    if let Some(server) = app.try_state::<Arc<Server>>() {
+        services::apply_change(&server, &state.get_settings(), &change);
+    }
 
     // 通过 emit 发送到所有窗口
-    app.emit("settings-changed", &new_settings)
+    app.emit("settings-changed", &change)
         .map_err(|e| e.to_string())?;
 
-    Ok(())
+    Ok(change)
+}
+
+/// 只修改 patch 中的键；base_revision 用于检测其他窗口的并发修改
+#[tauri::command]
+async fn patch_app_settings<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    patch: serde_json::Value,
+    base_revision: Option<u64>,
+) -> Result<SettingsChange, String> {
+    apply_settings_patch(&app, &patch, base_revision, true)
+}
+
+// ============================================================================
+// Settings Import/Export & Profiles
+// ============================================================================
+
+/// 校验导入内容并生成差异预览；`dry_run` 为 false 时应用。
+/// 从 profile 导入时同时记录 activeProfile。
+fn import_settings_value<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    value: serde_json::Value,
+    dry_run: bool,
+    profile: Option<&str>,
+) -> Result<ImportPreview, String> {
+    let current = app.state::<SettingsState>().get_settings();
+    let (mut patch, preview) = settings_profiles::prepare_import(&current, value)?;
+    if !dry_run {
+        if let Some(name) = profile {
+            patch["activeProfile"] = json!(name);
+        }
+        apply_settings_patch(app, &patch, None, true)?;
+        info!(changed = preview.changes.len(), profile = ?profile, "Settings imported");
+    }
+    Ok(preview)
+}
+
+fn profiles_dir<R: Runtime>(app: &tauri::AppHandle<R>) -> std::path::PathBuf {
+    settings_profiles::profiles_dir(&app.state::<SettingsState>().path)
+}
+
+fn list_profiles_internal<R: Runtime>(app: &tauri::AppHandle<R>) -> ProfileList {
+    ProfileList {
+        profiles: settings_profiles::list_profiles(&profiles_dir(app)),
+        active: app.state::<SettingsState>().get_settings().active_profile,
+    }
+}
+
+fn activate_profile_internal<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    name: &str,
+) -> Result<ImportPreview, String> {
+    let value = settings_profiles::load_profile(&profiles_dir(app), name)?;
+    import_settings_value(app, value, false, Some(name))
+}
+
+/// 导出当前设置到文件，未指定路径时写入下载目录；返回实际写入的路径
+#[tauri::command]
+async fn export_settings<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let path = match path {
+        Some(p) => std::path::PathBuf::from(p),
+        None => app
+            .path()
+            .download_dir()
+            .map_err(|e| e.to_string())?
+            .join("vibe-process-bar-settings.json"),
+    };
+    let settings = app.state::<SettingsState>().get_settings();
+    settings_profiles::write_settings_file(&settings, &path)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn preview_settings_import<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    settings: serde_json::Value,
+) -> Result<ImportPreview, String> {
+    import_settings_value(&app, settings, true, None)
+}
+
+#[tauri::command]
+async fn import_settings<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    settings: serde_json::Value,
+) -> Result<ImportPreview, String> {
+    import_settings_value(&app, settings, false, None)
+}
+
+#[tauri::command]
+async fn list_settings_profiles<R: Runtime>(app: tauri::AppHandle<R>) -> Result<ProfileList, String> {
+    Ok(list_profiles_internal(&app))
+}
+
+/// 将当前设置保存为命名 profile（同名覆盖），并设为当前 profile
+#[tauri::command]
+async fn save_settings_profile<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    name: String,
+) -> Result<ProfileList, String> {
+    let settings = app.state::<SettingsState>().get_settings();
+    settings_profiles::save_profile(&profiles_dir(&app), &name, &settings)?;
+    apply_settings_patch(&app, &json!({ "activeProfile": name }), None, true)?;
+    Ok(list_profiles_internal(&app))
+}
+
+#[tauri::command]
+async fn switch_settings_profile<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    name: String,
+) -> Result<ImportPreview, String> {
+    activate_profile_internal(&app, &name)
+}
+
+#[tauri::command]
+async fn delete_settings_profile<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    name: String,
+) -> Result<ProfileList, String> {
+    settings_profiles::delete_profile(&profiles_dir(&app), &name)?;
+    if app.state::<SettingsState>().get_settings().active_profile.as_deref() == Some(name.as_str()) {
+        apply_settings_patch(&app, &json!({ "activeProfile": null }), None, true)?;
+    }
+    Ok(list_profiles_internal(&app))
+}
+
+/// HTTP server 的 /api/settings/* 路由通过它读取和应用设置
+struct AppSettingsBridge<R: Runtime>(tauri::AppHandle<R>);
+
+impl<R: Runtime> http_server::SettingsBridge for AppSettingsBridge<R> {
+    fn export(&self) -> Result<serde_json::Value, String> {
+        Ok(settings_profiles::export_value(
+            &self.0.state::<SettingsState>().get_settings(),
+        ))
+    }
+
+    fn import(&self, settings: serde_json::Value, dry_run: bool) -> Result<serde_json::Value, String> {
+        let preview = import_settings_value(&self.0, settings, dry_run, None)?;
+        serde_json::to_value(preview).map_err(|e| e.to_string())
+    }
+
+    fn list_profiles(&self) -> Result<serde_json::Value, String> {
+        serde_json::to_value(list_profiles_internal(&self.0)).map_err(|e| e.to_string())
+    }
+
+    fn activate_profile(&self, name: &str) -> Result<serde_json::Value, String> {
+        let preview = activate_profile_internal(&self.0, name)?;
+        serde_json::to_value(preview).map_err(|e| e.to_string())
+    }
 }
 
 #[tauri::command]
@@ -132,12 +400,9 @@ async fn get_window_visibility(
 #[tauri::command]
 async fn set_window_visibility<R: Runtime>(
     app: tauri::AppHandle<R>,
-    state: tauri::State<'_, SettingsState>,
     visible: bool,
 ) -> Result<(), String> {
-    let mut settings = state.get_settings();
-    settings.window_visible = visible;
-    state.update_settings(settings)?;
+    apply_settings_patch(&app, &json!({ "windowVisible": visible }), None, true)?;
 
     app.emit("window-visibility-changed", visible)
         .map_err(|e| e.to_string())?;
@@ -320,8 +585,8 @@ fn open_url(url: String) -> Result<(), String> {
 }
 
 #[tauri::command]
-async fn start_http_server<R: Runtime>(_app: tauri::AppHandle<R>, port: u16) -> Result<(), String> {
-    http_server::start_server_background("127.0.0.1".to_string(), port);
+async fn start_http_server(server: tauri::State<'_, Arc<Server>>, port: u16) -> Result<(), String> {
+    server.http.start(server.inner().clone(), "127.0.0.1".to_string(), port);
     Ok(())
 }
 
@@ -416,8 +681,11 @@ fn update_tray_menu<R: Runtime>(app: &tauri::AppHandle<R>) {
 // ============================================================================
 
 #[tauri::command]
-async fn emit_tasks_updated<R: Runtime>(app: tauri::AppHandle<R>) -> Result<(), String> {
-    let tasks = http_server::get_merged_tasks();
+async fn emit_tasks_updated<R: Runtime>(
+    app: tauri::AppHandle<R>,
+    server: tauri::State<'_, Arc<Server>>,
+) -> Result<(), String> {
+    let tasks = server.merged_tasks();
     app.emit("tasks-updated", &tasks).map_err(|e| e.to_string())
 }
 
@@ -426,7 +694,7 @@ async fn emit_tasks_updated<R: Runtime>(app: tauri::AppHandle<R>) -> Result<(),
 // ============================================================================
 
 fn main() {
-    tracing_subscriber::fmt::init();
+    logging::init();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_shell::init())
@@ -466,19 +734,41 @@ fn main() {
             get_ide_windows,
             open_settings_window,
             get_app_settings,
-            update_app_settings,
+            get_settings_snapshot,
+            take_settings_warning,
+            patch_app_settings,
+            export_settings,
+            preview_settings_import,
+            import_settings,
+            list_settings_profiles,
+            save_settings_profile,
+            switch_settings_profile,
+            delete_settings_profile,
             get_window_visibility,
             set_window_visibility,
             update_tray_translations,
             get_current_language,
             get_tasks,
+            get_task_history,
+            get_task_stats,
+            get_logs,
+            get_task_detail,
+            get_task_timeline,
+            open_task_detail_window,
             emit_tasks_updated,
         ])
         .setup(|app| {
             let app_handle = app.app_handle().clone();
 
+            let path = app_handle.path();
+            let dirs = AppDirs {
+                config_dir: path.app_config_dir()?,
+                data_dir: path.app_data_dir()?,
+                log_dir: path.app_log_dir()?,
+            };
+
             // 初始化设置 (JSON文件存储)
-            let settings_state = SettingsState::new(&app_handle);
+            let settings_state = SettingsState::load(dirs.settings_path());
             let current_settings = settings_state.get_settings();
             app.manage(settings_state);
 
@@ -507,15 +797,48 @@ fn main() {
                 let _ = apply_blur(&window, Some((18, 18, 18, 200)));
             }
 
-            // 初始化 HTTP server 的屏蔽设置
-            http_server::set_block_plugin_status(current_settings.block_plugin_status);
+            // 启动日志文件、任务历史和 HTTP server
+            let server = services::start(&current_settings, &dirs);
+            server.set_settings_bridge(Arc::new(AppSettingsBridge(app_handle.clone())));
+            server.set_window_bridge(Arc::new(AppWindowBridge));
+            app.manage(server.clone());
 
-            // 启动 HTTP server
-            http_server::start_server_background(
-                current_settings.http_host.clone(),
-                current_settings.http_port,
-            );
-            info!(host = %current_settings.http_host, port = %current_settings.http_port, "HTTP server started");
+            // 启动超时/卡住监控，告警通过 task-alert 事件推送
+            {
+                let config_handle = app_handle.clone();
+                let alert_handle = app_handle.clone();
+                task_monitor::start_monitor(
+                    &server,
+                    move || MonitorConfig::from(&config_handle.state::<SettingsState>().get_settings()),
+                    move |alert| {
+                        if let Err(e) = alert_handle.emit("task-alert", &alert) {
+                            tracing::warn!(error = %e, "Failed to emit task-alert");
+                        }
+                    },
+                );
+            }
+
+            // 监听 settings.json 的外部修改
+            {
+                let settings_path = app.state::<SettingsState>().path.clone();
+                let current_handle = app_handle.clone();
+                let change_handle = app_handle.clone();
+                settings::watch_settings_file(
+                    settings_path,
+                    move || current_handle.state::<SettingsState>().get_settings(),
+                    move |change| match change {
+                        ExternalChange::Valid(patch) => {
+                            let patch = serde_json::Value::Object(patch);
+                            if let Err(e) = apply_settings_patch(&change_handle, &patch, None, false) {
+                                tracing::error!(error = %e, "Failed to apply external settings change");
+                            }
+                        }
+                        ExternalChange::Invalid(warning) => {
+                            let _ = change_handle.emit("settings-warning", &warning);
+                        }
+                    },
+                );
+            }
 
             // 创建托盘
             let trans = get_tray_translations_internal();
@@ -550,22 +873,17 @@ fn main() {
                 .on_menu_event(move |app, event| {
                     match event.id.as_ref() {
                         "toggle-window" => {
-                            let settings_state = app.state::<SettingsState>();
                             let window = app.get_webview_window("main").unwrap();
                             let is_visible = window.is_visible().unwrap_or(true);
 
                             if is_visible {
                                 let _ = window.hide();
-                                let mut settings = settings_state.get_settings();
-                                settings.window_visible = false;
-                                let _ = settings_state.update_settings(settings);
+                                let _ = apply_settings_patch(app, &json!({ "windowVisible": false }), None, true);
                                 let _ = app.tray_by_id("main-tray").unwrap()
                                     .set_tooltip(Some("Vibe Process Bar (Hidden)"));
                             } else {
                                 let _ = window.show();
-                                let mut settings = settings_state.get_settings();
-                                settings.window_visible = true;
-                                let _ = settings_state.update_settings(settings);
+                                let _ = apply_settings_patch(app, &json!({ "windowVisible": true }), None, true);
                                 let _ = app.tray_by_id("main-tray").unwrap()
                                     .set_tooltip(Some("Vibe Process Bar"));
                             }
@@ -590,10 +908,7 @@ fn main() {
             window.on_window_event(move |event| {
                 if let WindowEvent::CloseRequested { api, .. } = event {
                     api.prevent_close();
-                    let settings_state = app_handle_clone.state::<SettingsState>();
-                    let mut settings = settings_state.get_settings();
-                    settings.window_visible = false;
-                    let _ = settings_state.update_settings(settings);
+                    let _ = apply_settings_patch(&app_handle_clone, &json!({ "windowVisible": false }), None, true);
                     let _ = window_clone.hide();
                 }
             });
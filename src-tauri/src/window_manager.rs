@@ -1,7 +1,7 @@
-use serde::{Deserialize, Serialize};
 use std::process::Command;
 use tauri::Runtime;
 use tracing::info;
+pub use vibe_core::window_match::{find_best_match, IdeWindow, MatchCriteria};
 
 #[cfg(target_os = "windows")]
 use std::os::windows::process::CommandExt;
@@ -19,16 +19,6 @@ pub const IDE_BUNDLES: &[(&str, &str, &str)] = &[
 
 ];
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct IdeWindow {
-    pub bundle_id: String,
-    pub ide: String,
-    pub app_name: String,
-    pub window_title: String,
-    pub window_index: i32,
-    pub pid: String,
-}
-
 /// Scan all IDE windows using AppleScript
 #[cfg(target_os = "macos")]
 pub fn scan_ide_windows() -> Vec<IdeWindow> {
@@ -225,125 +215,6 @@ pub fn scan_ide_windows() -> Vec<IdeWindow> {
     Vec::new()
 }
 
-/// Match criteria for finding the best window
-#[derive(Debug)]
-pub struct MatchCriteria {
-    pub ide: Option<String>,
-    pub workspace: Option<String>,
-    pub file: Option<String>,
-}
-
-/// Find the best matching window based on criteria
-/// Priority: ide > workspace > file
-/// 当同一IDE有多个窗口时，优先匹配workspace，然后file
-pub fn find_best_match<'a>(windows: &'a [IdeWindow], criteria: &MatchCriteria) -> Option<&'a IdeWindow> {
-    let mut candidates: Vec<(&IdeWindow, u32)> = Vec::new();
-
-    info!("=== Window Matching Debug ===");
-    info!("Criteria: ide={:?}, workspace={:?}, file={:?}", criteria.ide, criteria.workspace, criteria.file);
-    info!("Available windows: {}", windows.len());
-
-    for window in windows {
-        let mut score = 0u32;
-        let mut match_reasons: Vec<String> = Vec::new();
-
-        info!("  Checking window: ide={}, title=\"{}\"", window.ide, window.window_title);
-
-        // IDE match (required if specified)
-        if let Some(ref ide) = criteria.ide {
-            if window.ide.to_lowercase() == ide.to_lowercase() {
-                score += 100;
-                match_reasons.push(format!("ide_match(+100)"));
-            } else {
-                info!("    -> SKIP: IDE mismatch ({} != {})", window.ide, ide);
-                continue; // IDE must match if specified
-            }
-        }
-
-        // Workspace/window_title match (high priority)
-        if let Some(ref workspace) = criteria.workspace {
-            info!("    Comparing workspace \"{}\" with window_title \"{}\"", workspace, window.window_title);
-            
-            // 精确匹配窗口标题
-            if window.window_title == *workspace {
-                score += 80;
-                match_reasons.push(format!("exact_title_match(+80)"));
-            }
-            // 窗口标题包含workspace
-            else if window.window_title.contains(workspace) {
-                score += 50;
-                match_reasons.push(format!("title_contains_workspace(+50)"));
-            }
-            // workspace包含窗口标题
-            else if workspace.contains(&window.window_title) {
-                score += 45;
-                match_reasons.push(format!("workspace_contains_title(+45)"));
-            }
-            // workspace包含在窗口标题的某部分（处理 "file — Project" 格式）
-            else {
-                let parts: Vec<&str> = window.window_title.split(" — ").collect();
-                let mut found = false;
-                for part in &parts {
-                    let part = part.trim();
-                    if part.contains(workspace) {
-                        score += 40;
-                        match_reasons.push(format!("part_contains_workspace(+40): \"{}\"", part));
-                        found = true;
-                        break;
-                    }
-                    if workspace.contains(part) && part.len() > 3 {
-                        score += 35;
-                        match_reasons.push(format!("workspace_contains_part(+35): \"{}\"", part));
-                        found = true;
-                        break;
-                    }
-                }
-                if !found {
-                    info!("    -> No workspace match found in parts: {:?}", parts);
-                }
-            }
-        }
-
-        // File match (lower priority)
-        if let Some(ref file) = criteria.file {
-            if window.window_title.contains(file) {
-                score += 20;
-                match_reasons.push(format!("file_match(+20)"));
-            }
-        }
-
-        info!("    -> Score: {}, Reasons: {:?}", score, match_reasons);
-
-        // 只有IDE匹配时也加入候选（score >= 100）
-        if score >= 100 {
-            candidates.push((window, score));
-        }
-    }
-
-    // Sort by score descending, then by window_index for stability
-    candidates.sort_by(|a, b| {
-        let score_cmp = b.1.cmp(&a.1);
-        if score_cmp == std::cmp::Ordering::Equal {
-            a.0.window_index.cmp(&b.0.window_index)
-        } else {
-            score_cmp
-        }
-    });
-    
-    info!("=== Candidates (sorted) ===");
-    for (i, (window, score)) in candidates.iter().enumerate() {
-        info!("  {}: \"{}\" (score: {})", i + 1, window.window_title, score);
-    }
-    
-    if let Some((window, score)) = candidates.first() {
-        info!("=== Best match: \"{}\" (score: {}) ===", window.window_title, score);
-    } else {
-        info!("=== No match found ===");
-    }
-    
-    candidates.first().map(|(w, _)| *w)
-}
-
 /// Activate a specific IDE window
 #[cfg(target_os = "macos")]
 pub fn activate_ide_window(window: &IdeWindow) -> Result<(), String> {
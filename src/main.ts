@@ -1,6 +1,7 @@
 import { createApp } from 'vue';
 import { createPinia } from 'pinia';
 import App from './App.vue';
+import TaskDetail from './components/TaskDetail.vue';
 import { i18n, initI18n } from './utils/i18n';
 import { useProgressStore } from './stores/progressStore';
 import './index.css';
@@ -10,7 +11,9 @@ import './App.css';
 document.documentElement.setAttribute('data-theme', 'dark');
 
 async function bootstrap() {
-    const app = createApp(App);
+    // 任务详情窗口 (index.html?type=task&id=...)
+    const isTaskDetail = new URLSearchParams(window.location.search).get('type') === 'task';
+    const app = createApp(isTaskDetail ? TaskDetail : App);
     const pinia = createPinia();
 
     app.use(pinia);
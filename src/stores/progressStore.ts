@@ -19,7 +19,7 @@ export interface ProgressTask {
   id: string;
   name: string;
   tokens: number;
-  status: 'armed' | 'running' | 'completed' | 'idle' | 'error' | 'cancelled';
+  status: 'armed' | 'running' | 'waiting_input' | 'completed' | 'idle' | 'error' | 'cancelled';
   is_focused?: boolean;
   start_time: number;
   end_time?: number;
@@ -30,9 +30,73 @@ export interface ProgressTask {
   active_file?: string;
   estimated_duration?: number; // 预估总时长（毫秒）
   current_stage?: string; // 当前阶段描述
+  overtime?: boolean; // 运行时间超出预估
+  possibly_stalled?: boolean; // 长时间无更新
+  git?: GitContext | null;
+  change_summary?: ChangeSummary | null; // 任务结束时相对开始 commit 的改动
+  completion?: CompletionReport | null; // agent 提交的完成报告
+  estimate?: Estimate | null; // 由历史学习的预估，优先于 estimated_duration
+  host?: string | null; // 远程实例转发来的任务所在主机
+  stale?: boolean; // 远程实例已断开
 }
 
+export interface Estimate {
+  duration_ms: number;
+  low_ms: number;
+  high_ms: number;
+  samples: number;
+  basis: 'agent' | 'agent_corrected' | 'history';
+}
+
+// 实际使用的预估时长：历史修正后的预估优先，其次是 agent 给出的原始值
+export function effectiveEstimate(task: ProgressTask): number | undefined {
+  return task.estimate?.duration_ms ?? task.estimated_duration;
+}
+
+export interface Artifact {
+  kind: string;
+  label: string | null;
+  url: string;
+}
+
+export interface CompletionReport {
+  summary: string;
+  changed_files: string[];
+  artifacts: Artifact[];
+  submitted_at: number;
+}
+
+export interface GitContext {
+  repo_root: string;
+  branch: string | null;
+  worktree: string | null;
+  head_at_start: string | null;
+  dirty_files: number;
+}
+
+export interface ChangeSummary {
+  base_commit: string;
+  head_commit: string | null;
+  commits: number;
+  files_changed: number;
+  insertions: number;
+  deletions: number;
+  untracked_files: number;
+}
+
+// 任务时间线事件（只追加），见 GET /api/task/{id}/timeline
+export type TimelineEvent = { at: number } & (
+  | { type: 'registered'; source: string }
+  | { type: 'status'; from: string; to: string }
+  | { type: 'stage'; stage: string | null }
+  | { type: 'source'; from: string; to: string }
+  | { type: 'ignored'; source: string; reason: string; status?: string }
+  | { type: 'heartbeat_gap'; gap_ms: number }
+  | { type: 'completion'; source: string }
+);
+
 export interface AppSettings {
+  version: number;
   language: SupportedLanguage;
   theme: 'dark' | 'purple' | 'ocean' | 'forest' | 'midnight';
   fontSize: number;
@@ -48,9 +112,51 @@ export interface AppSettings {
   windowX: number | null;
   windowY: number | null;
   showOnlyWhenRunning: boolean;
+  taskAlerts: boolean;
+  overtimeAlertPercent: number;
+  stallAlertMinutes: number;
+  activeProfile: string | null;
+  otlpEnabled: boolean;
+  otlpEndpoint: string;
+  logLevel: LogLevel;
+  federationToken: string;
+  federationPathMappings: PathMapping[];
+  processWatcher: boolean;
+  processWatcherAgents: string[];
+  fsActivity: boolean;
+  fsActivityMinWrites: number;
+  fsActivityWindowSeconds: number;
+  fsActivityIdleSeconds: number;
+  transcriptWatcher: boolean;
+  transcriptDirs: string[];
+  focusTracking: boolean;
+  followFocus: boolean;
+}
+
+// 远程项目路径前缀到本机路径的映射，host 为空时对所有主机生效
+export interface PathMapping {
+  host: string;
+  remote: string;
+  local: string;
+}
+
+export type LogLevel = 'trace' | 'debug' | 'info' | 'warn' | 'error';
+
+export interface LogEntry {
+  timestamp: number;
+  level: LogLevel;
+  target: string;
+  message: string;
+  fields?: Record<string, string>;
+}
+
+export interface LogsResponse {
+  log_dir: string | null;
+  entries: LogEntry[];
 }
 
 const defaultSettings: AppSettings = {
+  version: 2,
   language: 'en',
   theme: 'dark',
   fontSize: 14,
@@ -66,12 +172,96 @@ const defaultSettings: AppSettings = {
   windowX: null,
   windowY: null,
   showOnlyWhenRunning: false,
+  taskAlerts: true,
+  overtimeAlertPercent: 50,
+  stallAlertMinutes: 20,
+  activeProfile: null,
+  otlpEnabled: false,
+  otlpEndpoint: 'http://127.0.0.1:4318',
+  logLevel: 'info',
+  federationToken: '',
+  federationPathMappings: [],
+  processWatcher: true,
+  processWatcherAgents: ['claude', 'codex', 'gemini', 'aider', 'opencode', 'goose'],
+  fsActivity: false,
+  fsActivityMinWrites: 5,
+  fsActivityWindowSeconds: 10,
+  fsActivityIdleSeconds: 30,
+  transcriptWatcher: true,
+  transcriptDirs: ['~/.claude/projects', '~/.codex/sessions'],
+  focusTracking: true,
+  followFocus: false,
 };
 
+export interface SettingsChange {
+  revision: number;
+  changed: Partial<AppSettings>;
+}
+
+export interface SettingsSnapshot {
+  revision: number;
+  settings: AppSettings;
+}
+
+export interface SettingsWarning {
+  kind: 'reset' | 'rejected';
+  keys: string[];
+  backupPath: string | null;
+}
+
+export interface SettingDiff {
+  key: string;
+  current: unknown;
+  incoming: unknown;
+}
+
+export interface ImportPreview {
+  changes: SettingDiff[];
+  rejectedKeys: string[];
+  ignoredKeys: string[];
+}
+
+export interface ProfileList {
+  profiles: string[];
+  active: string | null;
+}
+
+export type StatsDimension = 'project' | 'ide' | 'source' | 'day' | 'week';
+
+export interface GroupStats {
+  key: Partial<Record<StatsDimension, string>>;
+  runs: number;
+  completed: number;
+  errors: number;
+  cancelled: number;
+  active_ms: number;
+  median_ms: number | null;
+  p90_ms: number | null;
+}
+
+export interface HourStats {
+  hour: number;
+  active_ms: number;
+  runs_started: number;
+}
+
+export interface TaskStats {
+  from: number | null;
+  to: number | null;
+  history_from: number | null;
+  history_to: number | null;
+  group_by: StatsDimension[];
+  total: GroupStats;
+  groups: GroupStats[];
+  busiest_hours: HourStats[];
+}
+
 export const useProgressStore = defineStore('progress', () => {
   const tasks = ref<ProgressTask[]>([]);
   const history = ref<ProgressTask[]>([]);
   const settings = ref<AppSettings>({ ...defaultSettings });
+  // 最近一次看到的设置修订号，作为 patch 的 base_revision
+  const settingsRevision = ref(0);
   
   let unlistenTasks: (() => void) | null = null;
   let unlistenSettings: (() => void) | null = null;
@@ -112,10 +302,10 @@ export const useProgressStore = defineStore('progress', () => {
         tasks.value = event.payload;
       });
 
-      // 监听设置更新事件
-      unlistenSettings = await listen<AppSettings>('settings-changed', (event) => {
-        debug('Received settings-changed event');
-        applySettings(event.payload);
+      // 监听设置更新事件（只包含变化的键）
+      unlistenSettings = await listen<SettingsChange>('settings-changed', (event) => {
+        debug('Received settings-changed event', { revision: event.payload.revision, keys: Object.keys(event.payload.changed) });
+        applySettingsChange(event.payload);
       });
 
       debug('Event listeners initialized');
@@ -151,18 +341,80 @@ export const useProgressStore = defineStore('progress', () => {
     }
   }
 
+  function applySettingsChange(change: SettingsChange) {
+    if (change.revision < settingsRevision.value) return;
+    settingsRevision.value = change.revision;
+    applySettings({ ...settings.value, ...change.changed });
+  }
+
   // 加载设置
   async function loadSettings() {
     try {
-      const loadedSettings = await safeInvoke<AppSettings>('get_app_settings');
-      if (loadedSettings) {
-        applySettings(loadedSettings);
+      const snapshot = await safeInvoke<SettingsSnapshot>('get_settings_snapshot');
+      if (snapshot) {
+        settingsRevision.value = snapshot.revision;
+        applySettings(snapshot.settings);
       }
     } catch (err) {
       error('Failed to load settings', { error: String(err) });
     }
   }
 
+  // 只提交变化的键；与其他窗口的修改冲突时重新加载
+  async function patchSettings(patch: Partial<AppSettings>) {
+    try {
+      const change = await safeInvoke<SettingsChange>('patch_app_settings', {
+        patch,
+        baseRevision: settingsRevision.value,
+      });
+      if (change) {
+        applySettingsChange(change);
+      }
+    } catch (err) {
+      error('Failed to patch settings', { error: String(err), keys: Object.keys(patch) });
+      await loadSettings();
+    }
+  }
+
+  // 导入/导出与命名 profile；应用后的变化通过 settings-changed 事件同步
+  async function exportSettings(path?: string) {
+    return safeInvoke<string>('export_settings', { path: path ?? null });
+  }
+
+  async function previewSettingsImport(imported: unknown) {
+    return safeInvoke<ImportPreview>('preview_settings_import', { settings: imported });
+  }
+
+  async function importSettings(imported: unknown) {
+    return safeInvoke<ImportPreview>('import_settings', { settings: imported });
+  }
+
+  async function listProfiles() {
+    return safeInvoke<ProfileList>('list_settings_profiles');
+  }
+
+  async function saveProfile(name: string) {
+    return safeInvoke<ProfileList>('save_settings_profile', { name });
+  }
+
+  async function switchProfile(name: string) {
+    return safeInvoke<ImportPreview>('switch_settings_profile', { name });
+  }
+
+  async function deleteProfile(name: string) {
+    return safeInvoke<ProfileList>('delete_settings_profile', { name });
+  }
+
+  // 后端最近的日志，since 为毫秒时间戳，level 为最低级别
+  async function getLogs(since?: number, level?: LogLevel, limit?: number) {
+    return safeInvoke<LogsResponse>('get_logs', { since, level, limit });
+  }
+
+  // 任务历史统计，groupBy 为逗号分隔的维度
+  async function getStats(groupBy?: string, from?: number, to?: number) {
+    return safeInvoke<TaskStats>('get_task_stats', { groupBy, from, to });
+  }
+
   // 从Rust层获取任务 (使用command而非HTTP API)
   async function fetchTasks() {
     try {
@@ -249,11 +501,7 @@ export const useProgressStore = defineStore('progress', () => {
 
   async function updateSettingAndSync<K extends keyof AppSettings>(key: K, value: AppSettings[K]) {
     settings.value[key] = value;
-    try {
-      await safeInvoke('update_app_settings', { newSettings: settings.value });
-    } catch (err) {
-      error('Failed to update settings', { error: String(err) });
-    }
+    await patchSettings({ [key]: value } as Partial<AppSettings>);
   }
 
   function setLanguage(language: SupportedLanguage) {
@@ -321,13 +569,93 @@ export const useProgressStore = defineStore('progress', () => {
     updateSettingAndSync('showOnlyWhenRunning', value);
   }
 
+  function setTaskAlerts(value: boolean) {
+    updateSettingAndSync('taskAlerts', value);
+  }
+
+  function setOvertimeAlertPercent(value: number) {
+    updateSettingAndSync('overtimeAlertPercent', Math.max(0, Math.min(1000, value)));
+  }
+
+  function setStallAlertMinutes(value: number) {
+    updateSettingAndSync('stallAlertMinutes', Math.max(1, Math.min(1440, value)));
+  }
+
+  function setOtlpEnabled(value: boolean) {
+    updateSettingAndSync('otlpEnabled', value);
+  }
+
+  function setOtlpEndpoint(value: string) {
+    updateSettingAndSync('otlpEndpoint', value.trim());
+  }
+
+  function setProcessWatcher(value: boolean) {
+    updateSettingAndSync('processWatcher', value);
+  }
+
+  // 逗号分隔的可执行文件名
+  function setProcessWatcherAgents(value: string) {
+    const agents = value.split(',').map(a => a.trim()).filter(a => a.length > 0);
+    updateSettingAndSync('processWatcherAgents', agents);
+  }
+
+  function setFsActivity(value: boolean) {
+    updateSettingAndSync('fsActivity', value);
+  }
+
+  function setFsActivityMinWrites(value: number) {
+    updateSettingAndSync('fsActivityMinWrites', Math.max(1, Math.min(1000, value)));
+  }
+
+  function setFsActivityWindowSeconds(value: number) {
+    updateSettingAndSync('fsActivityWindowSeconds', Math.max(1, Math.min(600, value)));
+  }
+
+  function setFsActivityIdleSeconds(value: number) {
+    updateSettingAndSync('fsActivityIdleSeconds', Math.max(1, Math.min(3600, value)));
+  }
+
+  function setTranscriptWatcher(value: boolean) {
+    updateSettingAndSync('transcriptWatcher', value);
+  }
+
+  // 逗号分隔的目录
+  function setTranscriptDirs(value: string) {
+    const dirs = value.split(',').map(d => d.trim()).filter(d => d.length > 0);
+    updateSettingAndSync('transcriptDirs', dirs);
+  }
+
+  function setFocusTracking(value: boolean) {
+    updateSettingAndSync('focusTracking', value);
+  }
+
+  function setFollowFocus(value: boolean) {
+    updateSettingAndSync('followFocus', value);
+  }
+
+  function setLogLevel(value: LogLevel) {
+    updateSettingAndSync('logLevel', value);
+  }
+
+  function setFederationToken(value: string) {
+    updateSettingAndSync('federationToken', value.trim());
+  }
+
+  // 未填完整的映射不保存（后端会拒绝）
+  function setFederationPathMappings(value: PathMapping[]) {
+    const mappings = value
+      .map(m => ({ host: m.host.trim(), remote: m.remote.trim(), local: m.local.trim() }))
+      .filter(m => m.remote && m.local);
+    updateSettingAndSync('federationPathMappings', mappings);
+  }
+
   function setWindowPosition(x: number, y: number) {
     settings.value.windowX = x;
     settings.value.windowY = y;
     // 移动主窗口到新位置
     safeInvoke('set_main_window_position', { x, y });
     // 保存设置
-    safeInvoke('update_app_settings', { newSettings: settings.value });
+    patchSettings({ windowX: x, windowY: y });
   }
 
   // 只更新位置数值，不保存到文件（用于拖动时实时显示）
@@ -338,18 +666,13 @@ export const useProgressStore = defineStore('progress', () => {
 
   // 保存当前位置到配置文件
   async function saveWindowPositionToFile() {
-    try {
-      await safeInvoke('update_app_settings', { newSettings: settings.value });
-    } catch (err) {
-      error('Failed to save window position', { error: String(err) });
-    }
+    await patchSettings({ windowX: settings.value.windowX, windowY: settings.value.windowY });
   }
 
   async function setWindowVisible(value: boolean) {
     settings.value.windowVisible = value;
     try {
       await safeInvoke('set_window_visibility', { visible: value });
-      await safeInvoke('update_app_settings', { newSettings: settings.value });
     } catch (err) {
       error('Failed to set window visibility', { error: String(err) });
     }
@@ -371,8 +694,19 @@ export const useProgressStore = defineStore('progress', () => {
     tasks,
     history,
     settings,
+    settingsRevision,
     loadSettings,
+    patchSettings,
     refreshSettings,
+    exportSettings,
+    previewSettingsImport,
+    importSettings,
+    listProfiles,
+    saveProfile,
+    switchProfile,
+    deleteProfile,
+    getStats,
+    getLogs,
     setSettings,
     addTask,
     removeTask,
@@ -392,6 +726,24 @@ export const useProgressStore = defineStore('progress', () => {
     setHttpPort,
     setBlockPluginStatus,
     setShowOnlyWhenRunning,
+    setTaskAlerts,
+    setOvertimeAlertPercent,
+    setStallAlertMinutes,
+    setOtlpEnabled,
+    setOtlpEndpoint,
+    setProcessWatcher,
+    setProcessWatcherAgents,
+    setFsActivity,
+    setFsActivityMinWrites,
+    setFsActivityWindowSeconds,
+    setFsActivityIdleSeconds,
+    setTranscriptWatcher,
+    setTranscriptDirs,
+    setFocusTracking,
+    setFollowFocus,
+    setLogLevel,
+    setFederationToken,
+    setFederationPathMappings,
     setWindowPosition,
     updateWindowPositionDisplay,
     saveWindowPositionToFile,
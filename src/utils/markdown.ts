@@ -0,0 +1,85 @@
+// 轻量 markdown 渲染，用于任务完成摘要
+// 只支持常用子集：标题、列表、代码块、行内代码、粗体/斜体、链接；所有文本先转义
+
+function escapeHtml(text: string): string {
+  return text
+    .replace(/&/g, '&amp;')
+    .replace(/</g, '&lt;')
+    .replace(/>/g, '&gt;')
+    .replace(/"/g, '&quot;')
+    .replace(/'/g, '&#39;');
+}
+
+function renderInline(text: string): string {
+  return escapeHtml(text)
+    .replace(/`([^`]+)`/g, '<code>$1</code>')
+    .replace(/\*\*([^*]+)\*\*/g, '<strong>$1</strong>')
+    .replace(/(^|[^*])\*([^*]+)\*/g, '$1<em>$2</em>')
+    .replace(/\[([^\]]+)\]\((https?:\/\/[^)\s]+)\)/g, '<a href="$2" data-external="true">$1</a>');
+}
+
+export function renderMarkdown(source: string): string {
+  const lines = source.replace(/\r\n/g, '\n').split('\n');
+  const html: string[] = [];
+  let listType: 'ul' | 'ol' | null = null;
+  let inCode = false;
+  let paragraph: string[] = [];
+
+  const flushParagraph = () => {
+    if (paragraph.length) {
+      html.push(`<p>${paragraph.map(renderInline).join('<br>')}</p>`);
+      paragraph = [];
+    }
+  };
+  const closeList = () => {
+    if (listType) {
+      html.push(`</${listType}>`);
+      listType = null;
+    }
+  };
+
+  for (const line of lines) {
+    if (line.trim().startsWith('```')) {
+      flushParagraph();
+      closeList();
+      html.push(inCode ? '</code></pre>' : '<pre><code>');
+      inCode = !inCode;
+      continue;
+    }
+    if (inCode) {
+      html.push(escapeHtml(line) + '\n');
+      continue;
+    }
+
+    const heading = line.match(/^(#{1,4})\s+(.*)$/);
+    const bullet = line.match(/^\s*[-*+]\s+(.*)$/);
+    const numbered = line.match(/^\s*\d+[.)]\s+(.*)$/);
+
+    if (heading) {
+      flushParagraph();
+      closeList();
+      const level = heading[1].length + 2; // 摘要内的标题从 h3 开始
+      html.push(`<h${level}>${renderInline(heading[2])}</h${level}>`);
+    } else if (bullet || numbered) {
+      flushParagraph();
+      const type = bullet ? 'ul' : 'ol';
+      if (listType !== type) {
+        closeList();
+        html.push(`<${type}>`);
+        listType = type;
+      }
+      html.push(`<li>${renderInline((bullet ?? numbered)![1])}</li>`);
+    } else if (line.trim() === '') {
+      flushParagraph();
+      closeList();
+    } else {
+      closeList();
+      paragraph.push(line);
+    }
+  }
+
+  flushParagraph();
+  closeList();
+  if (inCode) html.push('</code></pre>');
+  return html.join('');
+}
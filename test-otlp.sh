@@ -0,0 +1,75 @@
+#!/bin/bash
+# 用本地 OTLP collector 替身验证任务 trace 导出
+#
+# 1. 在设置中开启 OTLP 导出，地址填 http://127.0.0.1:4318（默认值）
+# 2. 运行本脚本：它在 4318 端口启动一个打印请求体的替身 collector，
+#    然后通过 API 跑一个带阶段的任务，任务结束后打印收到的 trace
+
+echo "=== Vibe Process Bar OTLP Export Test ==="
+echo ""
+
+API_URL="http://localhost:31415"
+COLLECTOR_PORT="${COLLECTOR_PORT:-4318}"
+TASK_ID="otlp-test-$(date +%s)"
+OUTPUT=$(mktemp)
+
+python3 - "$COLLECTOR_PORT" > "$OUTPUT" <<'PY' &
+import json, sys
+from http.server import BaseHTTPRequestHandler, HTTPServer
+
+class Collector(BaseHTTPRequestHandler):
+    def do_POST(self):
+        body = self.rfile.read(int(self.headers.get("Content-Length", 0)))
+        print(f"POST {self.path}", flush=True)
+        print(json.dumps(json.loads(body), indent=2), flush=True)
+        self.send_response(200)
+        self.send_header("Content-Type", "application/json")
+        self.end_headers()
+        self.wfile.write(b"{}")
+
+    def log_message(self, *args):
+        pass
+
+HTTPServer(("127.0.0.1", int(sys.argv[1])), Collector).serve_forever()
+PY
+COLLECTOR_PID=$!
+trap 'kill $COLLECTOR_PID 2>/dev/null; rm -f "$OUTPUT"' EXIT
+sleep 1
+
+echo "Task ID: $TASK_ID"
+echo ""
+
+post() {
+  curl -s -X POST "$API_URL$1" -H "Content-Type: application/json" -d "$2"
+  echo ""
+}
+
+echo "1. Registering task..."
+post /api/task/report "{\"task_id\": \"$TASK_ID\", \"name\": \"OTLP test\", \"ide\": \"cursor\", \"window_title\": \"otlp - Cursor\", \"project_path\": \"$PWD\"}"
+
+echo "2. Starting task..."
+post /api/task/update_state "{\"task_id\": \"$TASK_ID\", \"status\": \"running\", \"source\": \"hook\"}"
+
+echo "3. Running two stages..."
+post /api/task/update_state "{\"task_id\": \"$TASK_ID\", \"source\": \"hook\", \"current_stage\": \"Editing files\"}"
+sleep 1
+post /api/task/update_state "{\"task_id\": \"$TASK_ID\", \"source\": \"hook\", \"current_stage\": \"Running tests\"}"
+sleep 1
+
+echo "4. Completing task..."
+post /api/task/update_state "{\"task_id\": \"$TASK_ID\", \"status\": \"completed\", \"source\": \"hook\"}"
+
+# 导出在任务结束后的后台线程中进行（会先计算 git 改动统计）
+for _ in $(seq 1 10); do
+  grep -q "resourceSpans" "$OUTPUT" && break
+  sleep 1
+done
+
+echo ""
+if grep -q "resourceSpans" "$OUTPUT"; then
+  echo "=== Trace received by collector ==="
+  cat "$OUTPUT"
+else
+  echo "No trace received on port $COLLECTOR_PORT - is OTLP export enabled in Settings?"
+  exit 1
+fi